@@ -0,0 +1,185 @@
+use futures_util::future::{ready, Ready};
+
+use actix_web::{
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{self, HeaderName},
+    Error, HttpMessage,
+};
+
+use crate::IdentityPolicy;
+
+/// Read the identity from an `Authorization: Bearer <token>` request header.
+///
+/// The client holds the token, so remembering or forgetting an identity does not touch the
+/// response. Combine it with a cookie policy through `ChainIdentityPolicy` to serve both API
+/// clients and browsers.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_identity::{BearerIdentityPolicy, IdentityService};
+///
+/// let app = App::new()
+///     // wrap policy into auth middleware
+///     .wrap(IdentityService::new(BearerIdentityPolicy::new()));
+/// ```
+pub struct BearerIdentityPolicy {
+    header: HeaderName,
+    scheme: String,
+}
+
+/// Request extension marking the requests whose identity was read from the bearer header
+struct BearerIdentity;
+
+impl Default for BearerIdentityPolicy {
+    fn default() -> Self {
+        BearerIdentityPolicy::new()
+    }
+}
+
+impl BearerIdentityPolicy {
+    /// Create new `BearerIdentityPolicy` reading the `Authorization` header.
+    pub fn new() -> BearerIdentityPolicy {
+        BearerIdentityPolicy {
+            header: header::AUTHORIZATION,
+            scheme: "Bearer".to_owned(),
+        }
+    }
+
+    /// Sets the header holding the token, `Authorization` by default.
+    pub fn header(mut self, value: HeaderName) -> Self {
+        self.header = value;
+        self
+    }
+
+    /// Sets the authentication scheme in front of the token, `Bearer` by default.
+    ///
+    /// The scheme is matched case insensitively.
+    pub fn scheme(mut self, value: impl Into<String>) -> Self {
+        self.scheme = value.into();
+        self
+    }
+
+    /// Whether the identity of `req` was read from a bearer token.
+    ///
+    /// A request whose header this policy can't use may still be identified by the policy
+    /// chained after it, e.g. the cookie of a browser.
+    pub fn identified<R: HttpMessage>(req: &R) -> bool {
+        req.extensions().get::<BearerIdentity>().is_some()
+    }
+
+    fn token(&self, value: &str) -> Option<String> {
+        let mut parts = value.trim().splitn(2, ' ');
+        let scheme = parts.next()?;
+        let token = parts.next()?.trim();
+
+        if scheme.eq_ignore_ascii_case(&self.scheme) && !token.is_empty() {
+            Some(token.to_owned())
+        } else {
+            None
+        }
+    }
+}
+
+impl IdentityPolicy for BearerIdentityPolicy {
+    type Future = Ready<Result<Option<String>, Error>>;
+    type ResponseFuture = Ready<Result<(), Error>>;
+
+    fn from_request(&self, req: &mut ServiceRequest) -> Self::Future {
+        let token = req
+            .headers()
+            .get(&self.header)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| self.token(value));
+        if token.is_some() {
+            req.extensions_mut().insert(BearerIdentity);
+        }
+
+        ready(Ok(token))
+    }
+
+    fn to_response<B>(
+        &self,
+        _id: Option<String>,
+        _changed: bool,
+        _res: &mut ServiceResponse<B>,
+    ) -> Self::ResponseFuture {
+        ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+    use crate::{tests::*, Identity, IdentityService};
+
+    #[actix_rt::test]
+    async fn test_bearer_identity() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(IdentityService::new(BearerIdentityPolicy::new()))
+                .service(
+                    web::resource("/index")
+                        .to(|id: Identity| HttpResponse::Ok().json(id.identity())),
+                )
+                .service(web::resource("/login").to(|id: Identity| {
+                    id.remember(COOKIE_LOGIN.to_string());
+                    HttpResponse::Ok()
+                })),
+        )
+        .await;
+
+        let identity = |value: &'static str| {
+            let req = TestRequest::with_uri("/index")
+                .insert_header((header::AUTHORIZATION, value))
+                .to_request();
+            let srv = &srv;
+            async move {
+                let resp = test::call_service(srv, req).await;
+                let bytes = test::read_body(resp).await;
+                serde_json::from_slice::<Option<String>>(&bytes[..]).unwrap()
+            }
+        };
+
+        assert_eq!(identity("Bearer test").await, Some("test".to_string()));
+        assert_eq!(identity("bearer  test ").await, Some("test".to_string()));
+        assert_eq!(identity("Basic dGVzdA==").await, None);
+        assert_eq!(identity("Bearer ").await, None);
+
+        let resp = test::call_service(&srv, TestRequest::with_uri("/login").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().get(header::SET_COOKIE).is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_bearer_identified() {
+        let policy = BearerIdentityPolicy::new();
+        let mut bearer = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer test"))
+            .to_srv_request();
+        let mut basic = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Basic dGVzdA=="))
+            .to_srv_request();
+
+        assert_eq!(
+            policy.from_request(&mut bearer).await.unwrap(),
+            Some("test".to_string())
+        );
+        assert_eq!(policy.from_request(&mut basic).await.unwrap(), None);
+        assert!(BearerIdentityPolicy::identified(&bearer));
+        assert!(!BearerIdentityPolicy::identified(&basic));
+    }
+
+    #[test]
+    fn test_bearer_custom_scheme() {
+        let policy = BearerIdentityPolicy::new().scheme("Token");
+        assert_eq!(policy.token("Token abc"), Some("abc".to_string()));
+        assert_eq!(policy.token("Bearer abc"), None);
+    }
+}
@@ -0,0 +1,169 @@
+use futures_util::future::{FutureExt, LocalBoxFuture};
+
+use actix_web::{
+    dev::{ServiceRequest, ServiceResponse},
+    Error,
+};
+
+use crate::IdentityPolicy;
+
+/// Try two identity policies in order, the identity of the first one that finds one wins.
+///
+/// Nest chains to try more backends. Both policies read every request and both see remembered
+/// or forgotten identities, so a login through the chain still sets the cookie of a cookie
+/// policy.
+///
+/// # Examples
+/// ```
+/// use actix_web::App;
+/// use actix_identity::{
+///     BearerIdentityPolicy, ChainIdentityPolicy, CookieIdentityPolicy, IdentityService,
+/// };
+///
+/// let policy = ChainIdentityPolicy::new(
+///     BearerIdentityPolicy::new(),
+///     CookieIdentityPolicy::new(&[0; 32]).name("auth-cookie"),
+/// );
+///
+/// let app = App::new()
+///     // wrap policy into auth middleware
+///     .wrap(IdentityService::new(policy));
+/// ```
+pub struct ChainIdentityPolicy<A, B> {
+    first: A,
+    then: B,
+}
+
+impl<A, B> ChainIdentityPolicy<A, B> {
+    /// Create a chain trying `first`, then `then`.
+    pub fn new(first: A, then: B) -> ChainIdentityPolicy<A, B> {
+        ChainIdentityPolicy { first, then }
+    }
+}
+
+impl<A, B> IdentityPolicy for ChainIdentityPolicy<A, B>
+where
+    A: IdentityPolicy,
+    B: IdentityPolicy,
+    A::Future: 'static,
+    B::Future: 'static,
+    A::ResponseFuture: 'static,
+    B::ResponseFuture: 'static,
+{
+    type Future = LocalBoxFuture<'static, Result<Option<String>, Error>>;
+    type ResponseFuture = LocalBoxFuture<'static, Result<(), Error>>;
+
+    fn from_request(&self, req: &mut ServiceRequest) -> Self::Future {
+        // the request can't be held across an await, so both policies read it up front
+        let first = self.first.from_request(req);
+        let then = self.then.from_request(req);
+
+        async move {
+            match first.await? {
+                Some(id) => Ok(Some(id)),
+                None => then.await,
+            }
+        }
+        .boxed_local()
+    }
+
+    fn to_response<R>(
+        &self,
+        id: Option<String>,
+        changed: bool,
+        res: &mut ServiceResponse<R>,
+    ) -> Self::ResponseFuture {
+        let first = self.first.to_response(id.clone(), changed, res);
+        let then = self.then.to_response(id, changed, res);
+
+        async move {
+            first.await?;
+            then.await
+        }
+        .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        cookie::Cookie,
+        dev::ServiceResponse,
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+    use crate::{tests::*, BearerIdentityPolicy, CookieIdentityPolicy, Identity, IdentityService};
+
+    async fn create_chain_server() -> impl actix_service::Service<
+        actix_http::Request,
+        Response = ServiceResponse<actix_web::body::Body>,
+        Error = Error,
+    > {
+        test::init_service(
+            App::new()
+                .wrap(IdentityService::new(ChainIdentityPolicy::new(
+                    BearerIdentityPolicy::new(),
+                    CookieIdentityPolicy::new(&COOKIE_KEY_MASTER)
+                        .name(COOKIE_NAME)
+                        .secure(false),
+                )))
+                .service(
+                    web::resource("/index")
+                        .to(|id: Identity| HttpResponse::Ok().json(id.identity())),
+                )
+                .service(web::resource("/login").to(|id: Identity| {
+                    id.remember(COOKIE_LOGIN.to_string());
+                    HttpResponse::Ok()
+                })),
+        )
+        .await
+    }
+
+    async fn identity_of<S>(srv: &S, req: TestRequest) -> Option<String>
+    where
+        S: actix_service::Service<
+            actix_http::Request,
+            Response = ServiceResponse<actix_web::body::Body>,
+            Error = Error,
+        >,
+    {
+        let resp = test::call_service(srv, req.uri("/index").to_request()).await;
+        let bytes = test::read_body(resp).await;
+        serde_json::from_slice(&bytes[..]).unwrap()
+    }
+
+    fn login_cookie(resp: &ServiceResponse) -> Cookie<'static> {
+        resp.response()
+            .cookies()
+            .find(|c| c.name() == COOKIE_NAME)
+            .unwrap()
+            .into_owned()
+    }
+
+    #[actix_rt::test]
+    async fn test_chain_prefers_the_first_policy() {
+        let srv = create_chain_server().await;
+        let resp = test::call_service(&srv, TestRequest::with_uri("/login").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let cookie = login_cookie(&resp);
+
+        let from_cookie = TestRequest::default().cookie(cookie.clone());
+        assert_eq!(
+            identity_of(&srv, from_cookie).await,
+            Some(COOKIE_LOGIN.to_string())
+        );
+
+        let from_both = TestRequest::default()
+            .cookie(cookie)
+            .insert_header((header::AUTHORIZATION, "Bearer token"));
+        assert_eq!(
+            identity_of(&srv, from_both).await,
+            Some("token".to_string())
+        );
+
+        assert_eq!(identity_of(&srv, TestRequest::default()).await, None);
+    }
+}
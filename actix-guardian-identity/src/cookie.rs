@@ -14,9 +14,26 @@ use actix_web::{
 
 use crate::IdentityPolicy;
 
-struct CookieIdentityInner {
+/// The keys derived from one master key, `key` for legacy cookies and `key_v2` for json ones
+struct CookieKeys {
     key: Key,
     key_v2: Key,
+}
+
+impl CookieKeys {
+    fn new(key: &[u8]) -> CookieKeys {
+        let key_v2: Vec<u8> = [key, &[1, 0, 0, 0]].concat();
+
+        CookieKeys {
+            key: Key::derive_from(key),
+            key_v2: Key::derive_from(&key_v2),
+        }
+    }
+}
+
+struct CookieIdentityInner {
+    keys: CookieKeys,
+    fallback_keys: Vec<CookieKeys>,
     name: String,
     path: String,
     domain: Option<String>,
@@ -42,15 +59,15 @@ struct CookieValue {
 #[derive(Debug)]
 struct CookieIdentityExtension {
     login_timestamp: Option<SystemTime>,
+    /// The cookie was encrypted with a fallback key and is re-issued with the primary one
+    rotated: bool,
 }
 
 impl CookieIdentityInner {
     fn new(key: &[u8]) -> CookieIdentityInner {
-        let key_v2: Vec<u8> = [key, &[1, 0, 0, 0]].concat();
-
         CookieIdentityInner {
-            key: Key::derive_from(key),
-            key_v2: Key::derive_from(&key_v2),
+            keys: CookieKeys::new(key),
+            fallback_keys: Vec::new(),
             name: "actix-auth".to_owned(),
             path: "/".to_owned(),
             domain: None,
@@ -101,9 +118,9 @@ impl CookieIdentityInner {
         let mut jar = CookieJar::new();
 
         let key = if self.legacy_supported() {
-            &self.key
+            &self.keys.key
         } else {
-            &self.key_v2
+            &self.keys.key_v2
         };
 
         if add_cookie {
@@ -121,13 +138,25 @@ impl CookieIdentityInner {
         Ok(())
     }
 
-    fn load(&self, req: &ServiceRequest) -> Option<CookieValue> {
+    /// The cookie value, and whether it was encrypted with a fallback key
+    fn load(&self, req: &ServiceRequest) -> Option<(CookieValue, bool)> {
         let cookie = req.cookie(&self.name)?;
         let mut jar = CookieJar::new();
         jar.add_original(cookie.clone());
 
+        self.load_with(&mut jar, &self.keys)
+            .map(|value| (value, false))
+            .or_else(|| {
+                self.fallback_keys
+                    .iter()
+                    .find_map(|keys| self.load_with(&mut jar, keys))
+                    .map(|value| (value, true))
+            })
+    }
+
+    fn load_with(&self, jar: &mut CookieJar, keys: &CookieKeys) -> Option<CookieValue> {
         let res = if self.legacy_supported() {
-            jar.private(&self.key).get(&self.name).map(|n| CookieValue {
+            jar.private(&keys.key).get(&self.name).map(|n| CookieValue {
                 identity: n.value().to_string(),
                 login_timestamp: None,
                 visit_timestamp: None,
@@ -137,7 +166,7 @@ impl CookieIdentityInner {
         };
 
         res.or_else(|| {
-            jar.private(&self.key_v2)
+            jar.private(&keys.key_v2)
                 .get(&self.name)
                 .and_then(|c| self.parse(c))
         })
@@ -177,6 +206,13 @@ impl CookieIdentityInner {
     fn requires_oob_data(&self) -> bool {
         self.login_deadline.is_some()
     }
+
+    fn rotated<B>(&self, res: &ServiceResponse<B>) -> bool {
+        res.request()
+            .extensions()
+            .get::<CookieIdentityExtension>()
+            .map_or(false, |ext| ext.rotated)
+    }
 }
 
 /// Use cookies for request auth storage.
@@ -215,6 +251,19 @@ impl CookieIdentityPolicy {
         CookieIdentityPolicy(Rc::new(CookieIdentityInner::new(key)))
     }
 
+    /// Also accept cookies encrypted with a previous private key.
+    ///
+    /// Cookies are always issued with the key given to `new`, a cookie read with a fallback key
+    /// is re-issued with it. Add the retired key here when rotating keys so users stay logged in,
+    /// and drop it once the cookies it encrypted have expired.
+    ///
+    /// # Panics
+    /// Panics if `key` is less than 32 bytes in length.
+    pub fn fallback_key(mut self, key: &[u8]) -> CookieIdentityPolicy {
+        self.inner_mut().fallback_keys.push(CookieKeys::new(key));
+        self
+    }
+
     /// Sets the name of issued cookies.
     pub fn name(mut self, value: impl Into<String>) -> CookieIdentityPolicy {
         self.inner_mut().name = value.into();
@@ -301,16 +350,18 @@ impl IdentityPolicy for CookieIdentityPolicy {
     type ResponseFuture = Ready<Result<(), Error>>;
 
     fn from_request(&self, req: &mut ServiceRequest) -> Self::Future {
-        ready(Ok(self.0.load(req).map(|value| {
+        ready(Ok(self.0.load(req).map(|(value, rotated)| {
             let CookieValue {
                 identity,
                 login_timestamp,
                 ..
             } = value;
 
-            if self.0.requires_oob_data() {
-                req.extensions_mut()
-                    .insert(CookieIdentityExtension { login_timestamp });
+            if self.0.requires_oob_data() || rotated {
+                req.extensions_mut().insert(CookieIdentityExtension {
+                    login_timestamp,
+                    rotated,
+                });
             }
 
             identity
@@ -334,12 +385,13 @@ impl IdentityPolicy for CookieIdentityPolicy {
                     visit_timestamp: self.0.visit_deadline.map(|_| login_timestamp),
                 }),
             )
-        } else if self.0.always_update_cookie() && id.is_some() {
+        } else if id.is_some() && (self.0.always_update_cookie() || self.0.rotated(res)) {
             let visit_timestamp = SystemTime::now();
 
             let login_timestamp = if self.0.requires_oob_data() {
-                let CookieIdentityExtension { login_timestamp } =
-                    res.request().extensions_mut().remove().unwrap();
+                let CookieIdentityExtension {
+                    login_timestamp, ..
+                } = res.request().extensions_mut().remove().unwrap();
 
                 login_timestamp
             } else {
@@ -815,4 +867,29 @@ mod tests {
         );
         assert_logged_in(resp, Some(COOKIE_LOGIN)).await;
     }
+
+    #[actix_rt::test]
+    async fn test_identity_cookie_of_fallback_key_is_reissued() {
+        let old_key = [1; 32];
+        let mut jar = CookieJar::new();
+        jar.private(&Key::derive_from(&old_key))
+            .add(Cookie::new(COOKIE_NAME, COOKIE_LOGIN));
+        let cookie = jar.get(COOKIE_NAME).unwrap().clone();
+
+        let srv = create_identity_server(|c| c.fallback_key(&old_key)).await;
+        let mut resp = test::call_service(
+            &srv,
+            TestRequest::with_uri("/")
+                .cookie(cookie.clone())
+                .to_request(),
+        )
+        .await;
+        assert_legacy_login_cookie(&mut resp, COOKIE_LOGIN);
+        assert_logged_in(resp, Some(COOKIE_LOGIN)).await;
+
+        let srv = create_identity_server(|c| c).await;
+        let resp =
+            test::call_service(&srv, TestRequest::with_uri("/").cookie(cookie).to_request()).await;
+        assert_logged_in(resp, None).await;
+    }
 }
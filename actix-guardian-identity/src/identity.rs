@@ -67,6 +67,29 @@ impl Identity {
         }
     }
 
+    /// Re-issue the current auth, e.g. after a privilege change.
+    ///
+    /// Policies treat this like a new login: the redis policy moves the auth to a fresh session
+    /// id so an id planted or observed before the change is worthless. Does nothing for
+    /// anonymous requests.
+    pub fn renew(&self) {
+        if let Some(id) = self.0.extensions_mut().get_mut::<IdentityItem>() {
+            if id.id.is_some() {
+                id.changed = true;
+            }
+        }
+    }
+
+    /// Whether the auth of `req` was remembered, forgotten or renewed while handling it.
+    ///
+    /// Middleware running inside the identity service reads it before the policy writes the
+    /// new auth to the response.
+    pub fn is_changed(req: &HttpRequest) -> bool {
+        req.extensions()
+            .get::<IdentityItem>()
+            .map_or(false, |id| id.changed)
+    }
+
     pub(crate) fn get_identity(extensions: &Extensions) -> Option<String> {
         let id = extensions.get::<IdentityItem>()?;
         id.id.clone()
@@ -0,0 +1,664 @@
+use std::{
+    collections::HashMap,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use actix::Addr;
+use actix_redis::{Command, RedisActor};
+use futures_util::future::{ready, FutureExt, LocalBoxFuture};
+use rand::{distributions::Alphanumeric, Rng};
+use redis_async::{resp::RespValue, resp_array};
+use time::Duration;
+
+use actix_web::{
+    cookie::{Cookie, CookieJar, Key, SameSite},
+    dev::{ServiceRequest, ServiceResponse},
+    error::{self, Error, Result},
+    http::header::{self, HeaderValue},
+    HttpMessage,
+};
+
+use crate::IdentityPolicy;
+
+/// Length of the random session ids handed out in the cookie
+const SESSION_ID_LEN: usize = 32;
+
+/// Read an identity and restart its ttl in one round trip
+const GET_AND_EXPIRE: &str = r#"
+local identity = redis.call('GET', KEYS[1])
+if identity then
+    redis.call('EXPIRE', KEYS[1], ARGV[1])
+end
+return identity
+"#;
+
+/// Server side storage of the identities behind the session ids of `RedisIdentityPolicy`.
+pub trait IdentityStore: 'static {
+    /// Load the identity saved for `session_id`.
+    ///
+    /// When `ttl` is given the entry expires `ttl` from now, which gives sliding expiration.
+    fn load(
+        &self,
+        session_id: &str,
+        ttl: Option<Duration>,
+    ) -> LocalBoxFuture<'static, Result<Option<String>, Error>>;
+
+    /// Save `identity` under `session_id` for `ttl`.
+    fn save(
+        &self,
+        session_id: &str,
+        identity: String,
+        ttl: Duration,
+    ) -> LocalBoxFuture<'static, Result<(), Error>>;
+
+    /// Remove the identity of `session_id`, the session is invalid from then on.
+    fn remove(&self, session_id: &str) -> LocalBoxFuture<'static, Result<(), Error>>;
+}
+
+/// Keep identities in redis through an `actix_redis::RedisActor`.
+pub struct RedisIdentityStore {
+    addr: Addr<RedisActor>,
+    prefix: String,
+}
+
+impl RedisIdentityStore {
+    /// Create a store sending its commands to `addr`.
+    pub fn new(addr: Addr<RedisActor>) -> RedisIdentityStore {
+        RedisIdentityStore {
+            addr,
+            prefix: "identity:".to_owned(),
+        }
+    }
+
+    /// Sets the prefix of the redis keys, `identity:` by default.
+    pub fn prefix(mut self, value: impl Into<String>) -> RedisIdentityStore {
+        self.prefix = value.into();
+        self
+    }
+
+    fn key(&self, session_id: &str) -> String {
+        format!("{}{}", self.prefix, session_id)
+    }
+
+    fn send(&self, command: RespValue) -> LocalBoxFuture<'static, Result<RespValue, Error>> {
+        let addr = self.addr.clone();
+
+        async move {
+            match addr.send(Command(command)).await {
+                Ok(Ok(value)) => Ok(value),
+                Ok(Err(e)) => Err(error::ErrorInternalServerError(e)),
+                Err(e) => Err(error::ErrorInternalServerError(e)),
+            }
+        }
+        .boxed_local()
+    }
+}
+
+impl IdentityStore for RedisIdentityStore {
+    fn load(
+        &self,
+        session_id: &str,
+        ttl: Option<Duration>,
+    ) -> LocalBoxFuture<'static, Result<Option<String>, Error>> {
+        let key = self.key(session_id);
+        let command = match ttl {
+            Some(ttl) => resp_array![
+                "EVAL",
+                GET_AND_EXPIRE,
+                "1",
+                key,
+                ttl.whole_seconds().max(1).to_string()
+            ],
+            None => resp_array!["GET", key],
+        };
+        let reply = self.send(command);
+
+        async move {
+            match reply.await? {
+                RespValue::Nil => Ok(None),
+                RespValue::BulkString(bytes) => String::from_utf8(bytes)
+                    .map(Some)
+                    .map_err(error::ErrorInternalServerError),
+                value => Err(error::ErrorInternalServerError(format!(
+                    "unexpected identity reply {:?}",
+                    value
+                ))),
+            }
+        }
+        .boxed_local()
+    }
+
+    fn save(
+        &self,
+        session_id: &str,
+        identity: String,
+        ttl: Duration,
+    ) -> LocalBoxFuture<'static, Result<(), Error>> {
+        let seconds = ttl.whole_seconds().max(1).to_string();
+        let command = resp_array!["SET", self.key(session_id), identity, "EX", seconds];
+        self.send(command)
+            .map(|reply| reply.map(|_| ()))
+            .boxed_local()
+    }
+
+    fn remove(&self, session_id: &str) -> LocalBoxFuture<'static, Result<(), Error>> {
+        let command = resp_array!["DEL", self.key(session_id)];
+        self.send(command)
+            .map(|reply| reply.map(|_| ()))
+            .boxed_local()
+    }
+}
+
+/// Keep identities in process memory, a stand-in for redis in tests and local development.
+///
+/// Clones share their entries, the entries are lost on restart.
+#[derive(Clone, Default)]
+pub struct MemoryIdentityStore {
+    entries: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+}
+
+impl MemoryIdentityStore {
+    /// The number of sessions held, expired ones included until they are loaded.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether the store holds no sessions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn expiry(ttl: Duration) -> Instant {
+        Instant::now() + std::time::Duration::from_secs(ttl.whole_seconds().max(0) as u64)
+    }
+}
+
+impl IdentityStore for MemoryIdentityStore {
+    fn load(
+        &self,
+        session_id: &str,
+        ttl: Option<Duration>,
+    ) -> LocalBoxFuture<'static, Result<Option<String>, Error>> {
+        let mut entries = self.entries.lock().unwrap();
+        let identity = match entries.get_mut(session_id) {
+            Some((_, expires_at)) if *expires_at <= Instant::now() => {
+                entries.remove(session_id);
+                None
+            }
+            Some((identity, expires_at)) => {
+                if let Some(ttl) = ttl {
+                    *expires_at = MemoryIdentityStore::expiry(ttl);
+                }
+                Some(identity.clone())
+            }
+            None => None,
+        };
+
+        ready(Ok(identity)).boxed_local()
+    }
+
+    fn save(
+        &self,
+        session_id: &str,
+        identity: String,
+        ttl: Duration,
+    ) -> LocalBoxFuture<'static, Result<(), Error>> {
+        self.entries.lock().unwrap().insert(
+            session_id.to_owned(),
+            (identity, MemoryIdentityStore::expiry(ttl)),
+        );
+        ready(Ok(())).boxed_local()
+    }
+
+    fn remove(&self, session_id: &str) -> LocalBoxFuture<'static, Result<(), Error>> {
+        self.entries.lock().unwrap().remove(session_id);
+        ready(Ok(())).boxed_local()
+    }
+}
+
+struct RedisIdentityInner<S> {
+    store: S,
+    key: Key,
+    name: String,
+    path: String,
+    domain: Option<String>,
+    secure: bool,
+    max_age: Option<Duration>,
+    http_only: bool,
+    same_site: Option<SameSite>,
+    ttl: Duration,
+    sliding: bool,
+}
+
+/// The session id a request was loaded with, replaced or removed once the identity changes
+struct RedisIdentityExtension {
+    session_id: String,
+}
+
+impl<S: IdentityStore> RedisIdentityInner<S> {
+    fn session_id(&self, req: &ServiceRequest) -> Option<String> {
+        let cookie = req.cookie(&self.name)?;
+        let mut jar = CookieJar::new();
+        jar.add_original(cookie.clone());
+
+        jar.signed(&self.key)
+            .get(&self.name)
+            .map(|cookie| cookie.value().to_owned())
+    }
+
+    fn set_cookie<B>(
+        &self,
+        resp: &mut ServiceResponse<B>,
+        session_id: Option<String>,
+    ) -> Result<()> {
+        let add_cookie = session_id.is_some();
+        let mut cookie = Cookie::new(self.name.clone(), session_id.unwrap_or_default());
+        cookie.set_path(self.path.clone());
+        cookie.set_secure(self.secure);
+        cookie.set_http_only(self.http_only);
+
+        if let Some(ref domain) = self.domain {
+            cookie.set_domain(domain.clone());
+        }
+
+        if let Some(max_age) = self.max_age {
+            cookie.set_max_age(max_age);
+        }
+
+        if let Some(same_site) = self.same_site {
+            cookie.set_same_site(same_site);
+        }
+
+        let mut jar = CookieJar::new();
+        if add_cookie {
+            jar.signed(&self.key).add(cookie);
+        } else {
+            jar.add_original(cookie.clone());
+            jar.remove(cookie);
+        }
+
+        for cookie in jar.delta() {
+            let val = HeaderValue::from_str(&cookie.to_string())?;
+            resp.headers_mut().append(header::SET_COOKIE, val);
+        }
+
+        Ok(())
+    }
+}
+
+fn new_session_id() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SESSION_ID_LEN)
+        .map(char::from)
+        .collect()
+}
+
+/// Keep identities server side and only an opaque, signed session id in the cookie.
+///
+/// Identities expire `ttl` after they were last used, and removing a session from the store
+/// logs it out at once. Every login hands out a new session id.
+///
+/// # Examples
+/// ```
+/// use actix_redis::RedisActor;
+/// use actix_web::App;
+/// use actix_identity::{IdentityService, RedisIdentityPolicy, RedisIdentityStore};
+///
+/// let store = RedisIdentityStore::new(RedisActor::start("127.0.0.1:6379"));
+/// let policy = RedisIdentityPolicy::new(store, &[0; 32])
+///            .name("auth-session")
+///            .ttl(time::Duration::hours(8));
+///
+/// let app = App::new()
+///     // wrap policy into auth middleware
+///     .wrap(IdentityService::new(policy));
+/// ```
+pub struct RedisIdentityPolicy<S>(Rc<RedisIdentityInner<S>>);
+
+impl<S: IdentityStore> RedisIdentityPolicy<S> {
+    /// Create new `RedisIdentityPolicy` instance keeping identities in `store`.
+    ///
+    /// Key argument signs the session id cookies. If this value is changed, all issued cookies
+    /// are invalidated.
+    ///
+    /// # Panics
+    /// Panics if `key` is less than 32 bytes in length.
+    pub fn new(store: S, key: &[u8]) -> RedisIdentityPolicy<S> {
+        RedisIdentityPolicy(Rc::new(RedisIdentityInner {
+            store,
+            key: Key::derive_from(key),
+            name: "actix-auth-session".to_owned(),
+            path: "/".to_owned(),
+            domain: None,
+            secure: true,
+            max_age: None,
+            http_only: true,
+            same_site: None,
+            ttl: Duration::days(1),
+            sliding: true,
+        }))
+    }
+
+    /// Sets the name of issued cookies.
+    pub fn name(mut self, value: impl Into<String>) -> Self {
+        self.inner_mut().name = value.into();
+        self
+    }
+
+    /// Sets the `Path` attribute of issued cookies.
+    pub fn path(mut self, value: impl Into<String>) -> Self {
+        self.inner_mut().path = value.into();
+        self
+    }
+
+    /// Sets the `Domain` attribute of issued cookies.
+    pub fn domain(mut self, value: impl Into<String>) -> Self {
+        self.inner_mut().domain = Some(value.into());
+        self
+    }
+
+    /// Sets the `Secure` attribute of issued cookies.
+    pub fn secure(mut self, value: bool) -> Self {
+        self.inner_mut().secure = value;
+        self
+    }
+
+    /// Sets the `Max-Age` attribute of issued cookies.
+    ///
+    /// By default, issued cookies last until the browser closes.
+    pub fn max_age(mut self, value: Duration) -> Self {
+        self.inner_mut().max_age = Some(value);
+        self
+    }
+
+    /// Sets the `HttpOnly` attribute of issued cookies, on by default.
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.inner_mut().http_only = http_only;
+        self
+    }
+
+    /// Sets the `SameSite` attribute of issued cookies.
+    ///
+    /// By default, the `SameSite` attribute is omitted from issued cookies.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.inner_mut().same_site = Some(same_site);
+        self
+    }
+
+    /// Sets how long an identity is kept in the store, one day by default.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.inner_mut().ttl = ttl;
+        self
+    }
+
+    /// Restart the ttl of an identity whenever it is used, on by default.
+    ///
+    /// When off, an identity expires `ttl` after the login regardless of activity.
+    pub fn sliding(mut self, sliding: bool) -> Self {
+        self.inner_mut().sliding = sliding;
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut RedisIdentityInner<S> {
+        Rc::get_mut(&mut self.0).unwrap()
+    }
+}
+
+impl<S: IdentityStore> IdentityPolicy for RedisIdentityPolicy<S> {
+    type Future = LocalBoxFuture<'static, Result<Option<String>, Error>>;
+    type ResponseFuture = LocalBoxFuture<'static, Result<(), Error>>;
+
+    fn from_request(&self, req: &mut ServiceRequest) -> Self::Future {
+        let session_id = match self.0.session_id(req) {
+            Some(session_id) => session_id,
+            None => return ready(Ok(None)).boxed_local(),
+        };
+
+        let ttl = if self.0.sliding {
+            Some(self.0.ttl)
+        } else {
+            None
+        };
+        let identity = self.0.store.load(&session_id, ttl);
+        req.extensions_mut()
+            .insert(RedisIdentityExtension { session_id });
+
+        identity
+    }
+
+    fn to_response<B>(
+        &self,
+        id: Option<String>,
+        changed: bool,
+        res: &mut ServiceResponse<B>,
+    ) -> Self::ResponseFuture {
+        if !changed {
+            return ready(Ok(())).boxed_local();
+        }
+
+        let previous = res
+            .request()
+            .extensions_mut()
+            .remove::<RedisIdentityExtension>()
+            .map(|ext| ext.session_id);
+        let removed = previous.map(|session_id| self.0.store.remove(&session_id));
+
+        // a fresh session id on every login keeps a planted cookie from being promoted
+        let (saved, cookie) = match id {
+            Some(identity) => {
+                let session_id = new_session_id();
+                let saved = self.0.store.save(&session_id, identity, self.0.ttl);
+                (Some(saved), self.0.set_cookie(res, Some(session_id)))
+            }
+            None => (None, self.0.set_cookie(res, None)),
+        };
+
+        async move {
+            cookie?;
+            if let Some(removed) = removed {
+                removed.await?;
+            }
+            if let Some(saved) = saved {
+                saved.await?;
+            }
+            Ok(())
+        }
+        .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        cookie::{Cookie, CookieJar, Key},
+        dev::ServiceResponse,
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+    use crate::{tests::*, Identity, IdentityService};
+
+    async fn create_session_server(
+        store: MemoryIdentityStore,
+    ) -> impl actix_service::Service<
+        actix_http::Request,
+        Response = ServiceResponse<actix_web::body::Body>,
+        Error = Error,
+    > {
+        test::init_service(
+            App::new()
+                .wrap(IdentityService::new(
+                    RedisIdentityPolicy::new(store, &COOKIE_KEY_MASTER)
+                        .name(COOKIE_NAME)
+                        .secure(false),
+                ))
+                .service(
+                    web::resource("/index")
+                        .to(|id: Identity| HttpResponse::Ok().json(id.identity())),
+                )
+                .service(web::resource("/login").to(|id: Identity| {
+                    id.remember(COOKIE_LOGIN.to_string());
+                    HttpResponse::Ok()
+                }))
+                .service(web::resource("/renew").to(|id: Identity| {
+                    id.renew();
+                    HttpResponse::Ok()
+                }))
+                .service(web::resource("/logout").to(|id: Identity| {
+                    id.forget();
+                    HttpResponse::Ok()
+                })),
+        )
+        .await
+    }
+
+    fn session_cookie(response: &ServiceResponse) -> Cookie<'static> {
+        response
+            .response()
+            .cookies()
+            .find(|c| c.name() == COOKIE_NAME)
+            .unwrap()
+            .into_owned()
+    }
+
+    fn session_id(cookie: &Cookie<'static>) -> String {
+        let mut jar = CookieJar::new();
+        jar.add_original(cookie.clone());
+        jar.signed(&Key::derive_from(&COOKIE_KEY_MASTER))
+            .get(COOKIE_NAME)
+            .unwrap()
+            .value()
+            .to_owned()
+    }
+
+    async fn identity_of<S>(srv: &S, cookie: Cookie<'static>) -> Option<String>
+    where
+        S: actix_service::Service<
+            actix_http::Request,
+            Response = ServiceResponse<actix_web::body::Body>,
+            Error = Error,
+        >,
+    {
+        let req = TestRequest::with_uri("/index").cookie(cookie).to_request();
+        let resp = test::call_service(srv, req).await;
+        let bytes = test::read_body(resp).await;
+        serde_json::from_slice(&bytes[..]).unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_redis_identity_flow() {
+        let store = MemoryIdentityStore::default();
+        let srv = create_session_server(store.clone()).await;
+
+        let resp = test::call_service(&srv, TestRequest::with_uri("/login").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let cookie = session_cookie(&resp);
+        assert!(!cookie.value().contains(COOKIE_LOGIN));
+        assert_eq!(session_id(&cookie).len(), SESSION_ID_LEN);
+        assert_eq!(store.len(), 1);
+
+        assert_eq!(
+            identity_of(&srv, cookie.clone()).await,
+            Some(COOKIE_LOGIN.to_string())
+        );
+
+        let req = TestRequest::with_uri("/logout")
+            .cookie(cookie.clone())
+            .to_request();
+        let resp = test::call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(session_cookie(&resp).value(), "");
+        assert!(store.is_empty());
+        assert_eq!(identity_of(&srv, cookie).await, None);
+    }
+
+    #[actix_rt::test]
+    async fn test_redis_identity_invalidated_server_side() {
+        let store = MemoryIdentityStore::default();
+        let srv = create_session_server(store.clone()).await;
+
+        let resp = test::call_service(&srv, TestRequest::with_uri("/login").to_request()).await;
+        let cookie = session_cookie(&resp);
+        store.remove(&session_id(&cookie)).await.unwrap();
+
+        assert_eq!(identity_of(&srv, cookie).await, None);
+    }
+
+    #[actix_rt::test]
+    async fn test_redis_identity_rotates_session_id_on_login() {
+        let store = MemoryIdentityStore::default();
+        let srv = create_session_server(store.clone()).await;
+
+        let resp = test::call_service(&srv, TestRequest::with_uri("/login").to_request()).await;
+        let first = session_cookie(&resp);
+        let req = TestRequest::with_uri("/login")
+            .cookie(first.clone())
+            .to_request();
+        let resp = test::call_service(&srv, req).await;
+        let second = session_cookie(&resp);
+
+        assert_ne!(session_id(&first), session_id(&second));
+        assert_eq!(store.len(), 1);
+        assert_eq!(identity_of(&srv, first).await, None);
+    }
+
+    #[actix_rt::test]
+    async fn test_redis_identity_renew_moves_the_session() {
+        let store = MemoryIdentityStore::default();
+        let srv = create_session_server(store.clone()).await;
+
+        let resp = test::call_service(&srv, TestRequest::with_uri("/login").to_request()).await;
+        let before = session_cookie(&resp);
+        let req = TestRequest::with_uri("/renew")
+            .cookie(before.clone())
+            .to_request();
+        let resp = test::call_service(&srv, req).await;
+        let after = session_cookie(&resp);
+
+        assert_ne!(session_id(&before), session_id(&after));
+        assert_eq!(identity_of(&srv, before).await, None);
+        assert_eq!(
+            identity_of(&srv, after).await,
+            Some(COOKIE_LOGIN.to_string())
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_redis_identity_rejects_unsigned_session_id() {
+        let store = MemoryIdentityStore::default();
+        let srv = create_session_server(store.clone()).await;
+
+        let resp = test::call_service(&srv, TestRequest::with_uri("/login").to_request()).await;
+        let forged = Cookie::new(COOKIE_NAME, session_id(&session_cookie(&resp)));
+
+        assert_eq!(identity_of(&srv, forged).await, None);
+    }
+
+    #[actix_rt::test]
+    async fn test_memory_store_expires_identities() {
+        let store = MemoryIdentityStore::default();
+        store
+            .save("expired", "test".to_string(), Duration::zero())
+            .await
+            .unwrap();
+        store
+            .save("current", "test".to_string(), Duration::hours(1))
+            .await
+            .unwrap();
+
+        assert_eq!(store.load("expired", None).await.unwrap(), None);
+        assert_eq!(
+            store
+                .load("current", Some(Duration::hours(1)))
+                .await
+                .unwrap(),
+            Some("test".to_string())
+        );
+        assert_eq!(store.len(), 1);
+    }
+}
@@ -1,6 +1,11 @@
-extern crate prost_build;
+extern crate tonic_build;
 
 fn main() {
-    // build proto
-    prost_build::compile_protos(&["src/service_proto.proto"], &["src/"]).unwrap();
+    // build proto, the grpc services are only generated when the grpc server is enabled
+    let with_services = std::env::var("CARGO_FEATURE_SERVER_GRPC").is_ok();
+    tonic_build::configure()
+        .build_server(with_services)
+        .build_client(with_services)
+        .compile(&["src/service_proto.proto"], &["src/"])
+        .unwrap();
 }
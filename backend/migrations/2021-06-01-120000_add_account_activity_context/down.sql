@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS account_activities_correlation_id_idx;
+DROP INDEX IF EXISTS account_activities_account_id_created_at_idx;
+
+ALTER TABLE account_activities DROP COLUMN IF EXISTS correlation_id;
+ALTER TABLE account_activities DROP COLUMN IF EXISTS user_agent;
+ALTER TABLE account_activities DROP COLUMN IF EXISTS ip;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- an activity log holds many rows per log name, subject and causer
+ALTER TABLE account_activities DROP CONSTRAINT IF EXISTS account_activities_log_name_key;
+ALTER TABLE account_activities DROP CONSTRAINT IF EXISTS account_activities_subject_id_subject_type_key;
+ALTER TABLE account_activities DROP CONSTRAINT IF EXISTS account_activities_causer_id_causer_type_key;
+
+-- failed logins of unknown identities have no account
+ALTER TABLE account_activities ALTER COLUMN account_id DROP NOT NULL;
+ALTER TABLE account_activities ALTER COLUMN created_by DROP NOT NULL;
+ALTER TABLE account_activities ALTER COLUMN updated_by DROP NOT NULL;
+
+ALTER TABLE account_activities ADD COLUMN IF NOT EXISTS ip VARCHAR NULL;
+ALTER TABLE account_activities ADD COLUMN IF NOT EXISTS user_agent VARCHAR NULL;
+ALTER TABLE account_activities ADD COLUMN IF NOT EXISTS correlation_id VARCHAR NULL;
+
+CREATE INDEX IF NOT EXISTS account_activities_account_id_created_at_idx
+    ON account_activities (account_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS account_activities_correlation_id_idx
+    ON account_activities (correlation_id);
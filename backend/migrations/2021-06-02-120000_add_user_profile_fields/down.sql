@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS user_profiles_fields_idx;
+DROP INDEX IF EXISTS user_profiles_account_id_idx;
+
+ALTER TABLE user_profiles DROP COLUMN IF EXISTS fields;
+ALTER TABLE user_profiles DROP COLUMN IF EXISTS account_id;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+-- custom profile fields declared by the profile schema, keyed by field name
+ALTER TABLE user_profiles ADD COLUMN IF NOT EXISTS account_id UUID NOT NULL REFERENCES accounts (id) ON DELETE CASCADE;
+ALTER TABLE user_profiles ADD COLUMN IF NOT EXISTS fields JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+CREATE UNIQUE INDEX IF NOT EXISTS user_profiles_account_id_idx ON user_profiles (account_id);
+CREATE INDEX IF NOT EXISTS user_profiles_fields_idx ON user_profiles USING GIN (fields);
@@ -1,25 +1,42 @@
 use std::time::Duration;
 
-use actix_web::web::Data;
+use actix_guardian_identity::Identity;
 use actix_web::{web, Error, HttpRequest, HttpResponse};
+use juniper::Variables;
 use juniper_graphql_ws::ConnectionConfig;
 
 use juniper_actix::subscriptions::subscriptions_handler;
 use juniper_actix::{graphiql_handler, graphql_handler, playground_handler};
 
-use crate::api::graphql::schema::context::Context;
+use crate::api::graphql::schema::context::{Context, IdentityChange};
 use crate::api::graphql::schema::root::Schema;
-use crate::common::auth::account::IdentityAccount;
-use crate::data::stores::base_store_trait::BoxedStoreType;
+use crate::common::audit::audit_from_request;
+use crate::common::auth::extractors::account_from_request;
+use crate::common::auth::revocation::current_account;
+use crate::common::auth::utils::decode_jwt;
+use crate::common::errors::ApiError;
+use crate::common::tenant::{tenant_from_request, TenantStore};
 
 pub async fn graphql(
     req: actix_web::HttpRequest,
     payload: actix_web::web::Payload,
     schema: web::Data<Schema>,
-    store: Data<BoxedStoreType>,
+    store: TenantStore,
+    id: Identity,
 ) -> Result<HttpResponse, Error> {
-    let context = Context::new(store);
-    graphql_handler(&schema, &context, req, payload).await
+    let auth = current_account(&store, account_from_request(&req)).await;
+    let context = Context::new(store.into_data(), auth, audit_from_request(&req));
+    let rsp = graphql_handler(&schema, &context, req, payload).await;
+
+    // apply the session changes made by the resolvers
+    match context.take_identity_change() {
+        Some(IdentityChange::Remember(token)) => id.remember(token),
+        Some(IdentityChange::Forget) => id.forget(),
+        Some(IdentityChange::Renew) => id.renew(),
+        None => {}
+    }
+
+    rsp
 }
 
 pub async fn graphiql_playground() -> Result<HttpResponse, Error> {
@@ -33,15 +50,38 @@ pub async fn playground() -> Result<HttpResponse, Error> {
 pub async fn subscriptions(
     req: HttpRequest,
     stream: web::Payload,
-    store: Data<BoxedStoreType>,
+    store: TenantStore,
     schema: web::Data<Schema>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let context = Context::new(store);
+    let auth = account_from_request(&req);
+    let audit = audit_from_request(&req);
+    let tenant = tenant_from_request(&req);
     let schema = schema.into_inner();
-    let config = ConnectionConfig::new(context);
-    // set the keep alive interval to 15 secs so that it doesn't timeout in playground
-    // playground has a hard-coded timeout set to 20 secs
-    let config = config.with_keep_alive_interval(Duration::from_secs(15));
 
-    subscriptions_handler(req, stream, schema, config).await
+    // the connection is authenticated with the session cookie, the authorization header
+    // or an `authToken` sent in the connection_init payload
+    let init = move |params: Variables| async move {
+        let auth = auth.or_else(|| {
+            params
+                .get("authToken")
+                .and_then(|token| token.as_string_value())
+                .and_then(|token| decode_jwt(token).ok())
+                .filter(|claim| claim.tenant() == tenant)
+                .map(|claim| claim.into())
+        });
+        let auth = current_account(&store, auth).await;
+
+        if auth.is_none() {
+            return Err(ApiError::Unauthorized(
+                "a valid authToken is required to subscribe".to_string(),
+            ));
+        }
+
+        let config = ConnectionConfig::new(Context::new(store.into_data(), auth, audit));
+        // set the keep alive interval to 15 secs so that it doesn't timeout in playground
+        // playground has a hard-coded timeout set to 20 secs
+        Ok(config.with_keep_alive_interval(Duration::from_secs(15)))
+    };
+
+    subscriptions_handler(req, stream, schema, init).await
 }
@@ -2,27 +2,163 @@ use juniper::FieldResult;
 
 use crate::api::graphql::schema::context::Context;
 use crate::api::services;
-use crate::common::auth::utils::{create_jwt, PrivateClaim};
-use crate::data::dtos::account_dto::PostAccountResponse;
-use crate::data::dtos::auth_dto::PostAccountRequest;
+use crate::common::validate::validate_input;
+use crate::data::dtos::account_dto::{
+    AccountUpdateResponse, PostAccountResponse, PostSessionResponse,
+};
+use crate::data::dtos::auth_dto::{
+    ChangePasswordRequest, ConfirmChangeRequest, PostAccountRequest, UpdateAccountRequest,
+};
+use crate::data::dtos::profile_dto::{ProfileResponse, UpdateProfileRequest};
 
 pub struct AccountMutations;
 
+/// Remember the refreshed token of an updated account in the session cookie
+fn remember_token(context: &Context, rsp: &AccountUpdateResponse) {
+    if let Some(token) = &rsp.id_token {
+        context.remember(token.clone());
+    }
+}
+
 #[juniper::graphql_object(context = Context)]
 impl AccountMutations {
     async fn create(
         context: &Context,
         input: PostAccountRequest,
     ) -> FieldResult<PostAccountResponse> {
-        let acct = services::account_service::create_account(&context.store, &input).await?;
-        // id.remember(acct.id.clone());
+        let acct =
+            services::account_service::create_account(&context.store, &input, &context.audit)
+                .await?;
 
-        // create jwt token
-        let pc = PrivateClaim::new(acct.id, acct.email, acct.username, acct.mobile);
-        let token = create_jwt(pc).unwrap_or_default();
+        let token =
+            services::session_service::open_session(&context.store, &acct, &context.audit).await?;
 
         Ok(PostAccountResponse {
             id_token: Option::from(token),
         })
     }
+
+    /// Update the username, email or mobile of the logged in account
+    async fn update(
+        context: &Context,
+        input: UpdateAccountRequest,
+    ) -> FieldResult<AccountUpdateResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        let rsp = services::account_service::update_current_account(
+            &context.store,
+            &auth.id,
+            &input,
+            &context.audit,
+        )
+        .await?;
+        remember_token(context, &rsp);
+        Ok(rsp)
+    }
+
+    /// Change the password of the logged in account, its other sessions are revoked
+    async fn changePassword(
+        context: &Context,
+        input: ChangePasswordRequest,
+    ) -> FieldResult<PostSessionResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        let rsp = services::account_service::change_password(
+            &context.store,
+            &auth.id,
+            &input,
+            &context.audit,
+        )
+        .await?;
+        if let Some(token) = &rsp.id_token {
+            context.remember(token.clone());
+        }
+        Ok(rsp)
+    }
+
+    /// Confirm a pending email change with the token sent to the new email
+    async fn confirmEmail(
+        context: &Context,
+        input: ConfirmChangeRequest,
+    ) -> FieldResult<AccountUpdateResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        let rsp = services::account_service::confirm_email(
+            &context.store,
+            &auth.id,
+            &input,
+            &context.audit,
+        )
+        .await?;
+        remember_token(context, &rsp);
+        Ok(rsp)
+    }
+
+    /// Verify a pending mobile change with the one time code sent to the new mobile
+    async fn verifyMobile(
+        context: &Context,
+        input: ConfirmChangeRequest,
+    ) -> FieldResult<AccountUpdateResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        let rsp = services::account_service::verify_mobile(
+            &context.store,
+            &auth.id,
+            &input,
+            &context.audit,
+        )
+        .await?;
+        remember_token(context, &rsp);
+        Ok(rsp)
+    }
+
+    /// Update the custom profile fields of the logged in account
+    async fn updateProfile(
+        context: &Context,
+        input: UpdateProfileRequest,
+    ) -> FieldResult<ProfileResponse> {
+        let auth = context.require_auth()?;
+        let rsp = services::profile_service::update_profile(
+            &context.store,
+            &auth.id,
+            &input,
+            &context.audit,
+        )
+        .await?;
+        Ok(rsp)
+    }
+
+    /// Lock the logged in account
+    async fn lock(context: &Context) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        let rsp = services::account_service::lock_account(
+            &context.store,
+            auth.id.clone(),
+            &context.audit,
+        )
+        .await?;
+        Ok(rsp)
+    }
+
+    /// Unlock the logged in account
+    async fn unlock(context: &Context) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        let rsp = services::account_service::unlock_account(
+            &context.store,
+            auth.id.clone(),
+            &context.audit,
+        )
+        .await?;
+        Ok(rsp)
+    }
+
+    /// Delete the logged in account and end its session
+    async fn delete(context: &Context) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        let rsp =
+            services::account_service::delete_account(&context.store, auth.clone(), &context.audit)
+                .await?;
+        context.forget();
+        Ok(rsp)
+    }
 }
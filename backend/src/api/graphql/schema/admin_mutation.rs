@@ -0,0 +1,141 @@
+use juniper::FieldResult;
+
+use crate::api::graphql::schema::context::{guard_error, Context};
+use crate::api::services::admin_service;
+use crate::common::validate::validate_input;
+use crate::data::dtos::account_dto::AccountResponse;
+use crate::data::dtos::api_key_dto::{
+    CreateApiKeyRequest, CreateServiceAccountRequest, CreatedApiKeyResponse,
+};
+use crate::data::dtos::auth_dto::{SetAccountRolesRequest, UpdateAccountRequest};
+use crate::data::dtos::session_dto::ImpersonationResponse;
+
+pub struct AdminMutations;
+
+#[juniper::graphql_object(context = Context)]
+impl AdminMutations {
+    /// Update the email, username or mobile of an account
+    async fn update(
+        context: &Context,
+        id: String,
+        input: UpdateAccountRequest,
+    ) -> FieldResult<AccountResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        admin_service::update_account(&context.store, auth, &id, &input, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Lock an account
+    async fn lock(context: &Context, id: String) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        admin_service::lock_account(&context.store, auth, &id, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Unlock an account
+    async fn unlock(context: &Context, id: String) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        admin_service::unlock_account(&context.store, auth, &id, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Force an account to change its password
+    async fn requireNewPassword(context: &Context, id: String) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        admin_service::require_new_password(&context.store, auth, &id, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Delete an account, `hard` removes it permanently
+    async fn delete(context: &Context, id: String, hard: Option<bool>) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        admin_service::delete_account(
+            &context.store,
+            auth,
+            &id,
+            hard.unwrap_or(false),
+            &context.audit,
+        )
+        .await
+        .map_err(guard_error)
+    }
+
+    /// Restore a soft deleted account
+    async fn restore(context: &Context, id: String) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        admin_service::restore_account(&context.store, auth, &id, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Set the confirmed status of an account
+    async fn setConfirmed(context: &Context, id: String, confirmed: bool) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        admin_service::set_confirmed(&context.store, auth, &id, confirmed, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Replace the roles and permissions of an account
+    async fn setRoles(
+        context: &Context,
+        id: String,
+        input: SetAccountRolesRequest,
+    ) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        let rsp =
+            admin_service::set_account_roles(&context.store, auth, &id, &input, &context.audit)
+                .await
+                .map_err(guard_error)?;
+        if auth.id == id {
+            context.renew();
+        }
+        Ok(rsp)
+    }
+
+    /// Create a service account that authenticates with api keys
+    async fn createServiceAccount(
+        context: &Context,
+        input: CreateServiceAccountRequest,
+    ) -> FieldResult<AccountResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        admin_service::create_service_account(&context.store, auth, &input, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Create an api key of a service account, the key is only returned here
+    async fn createApiKey(
+        context: &Context,
+        id: String,
+        input: CreateApiKeyRequest,
+    ) -> FieldResult<CreatedApiKeyResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        admin_service::create_api_key(&context.store, auth, &id, &input, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Revoke an api key of a service account
+    async fn revokeApiKey(context: &Context, id: String, key_id: String) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        admin_service::revoke_api_key(&context.store, auth, &id, &key_id, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Issue a short lived token acting as an account, the admin's own session is kept
+    async fn impersonate(context: &Context, id: String) -> FieldResult<ImpersonationResponse> {
+        let auth = context.require_auth()?;
+        admin_service::impersonate_account(&context.store, auth, &id, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+}
@@ -1,20 +1,136 @@
-use crate::data::stores::base_store_trait::BoxedStoreType;
+use std::sync::{Arc, Mutex};
+
 use actix_web::web::Data;
-use juniper::futures::lock::Mutex;
+use juniper::{graphql_value, FieldError, FieldResult};
+
+use crate::common::audit::AuditContext;
+use crate::common::auth::account::IdentityAccount;
+use crate::common::auth::guard;
+use crate::common::errors::ApiError;
+use crate::data::models::oso_init::{Actor, ADMIN_ROLE};
+use crate::data::stores::base_store_trait::BoxedStoreType;
+
+/// Cookie identity changes requested by a resolver. They are applied by the handler
+/// once the operation has run since the actix `Identity` can't live inside the context.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IdentityChange {
+    Remember(String),
+    Forget,
+    /// Re-issue the current identity after a privilege change
+    Renew,
+}
 
 #[derive(Clone)]
 pub struct Context {
     pub store: Data<BoxedStoreType>,
-    // pub auth: IdentityAccount,
+    pub auth: Option<IdentityAccount>,
+    pub audit: AuditContext,
+    identity_change: Arc<Mutex<Option<IdentityChange>>>,
 }
 
 // To make our context usable by Juniper, we have to implement a marker trait.
 impl juniper::Context for Context {}
 
 impl Context {
-    pub fn new(store: Data<BoxedStoreType>) -> Self {
+    pub fn new(
+        store: Data<BoxedStoreType>,
+        auth: Option<IdentityAccount>,
+        audit: AuditContext,
+    ) -> Self {
         Context {
             store: store.clone(),
+            auth,
+            audit,
+            identity_change: Arc::new(Mutex::new(None)),
         }
     }
+
+    /// Returns the calling account or an `UNAUTHENTICATED` field error
+    pub fn require_auth(&self) -> FieldResult<&IdentityAccount> {
+        self.auth.as_ref().ok_or_else(|| {
+            FieldError::new(
+                "you must be logged in to access this field",
+                graphql_value!({ "code": "UNAUTHENTICATED" }),
+            )
+        })
+    }
+
+    /// Returns the calling actor when it holds the admin role
+    pub async fn require_admin(&self) -> FieldResult<Actor> {
+        let auth = self.require_auth()?;
+        guard::require_role(&self.store, auth, ADMIN_ROLE)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Returns the calling actor when the policy allows `action` on the account `resource_id`
+    pub async fn authorize(&self, action: &str, resource_id: &str) -> FieldResult<Actor> {
+        let auth = self.require_auth()?;
+        guard::authorize(&self.store, auth, action, resource_id)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Remember a new id token in the session cookie
+    pub fn remember(&self, token: String) {
+        self.set_identity_change(IdentityChange::Remember(token));
+    }
+
+    /// Clear the session cookie
+    pub fn forget(&self) {
+        self.set_identity_change(IdentityChange::Forget);
+    }
+
+    /// Re-issue the session identity, e.g. after the caller's privileges changed
+    pub fn renew(&self) {
+        self.set_identity_change(IdentityChange::Renew);
+    }
+
+    pub fn take_identity_change(&self) -> Option<IdentityChange> {
+        self.identity_change
+            .lock()
+            .expect("Could not acquire lock")
+            .take()
+    }
+
+    fn set_identity_change(&self, change: IdentityChange) {
+        let mut identity_change = self.identity_change.lock().expect("Could not acquire lock");
+        *identity_change = Some(change);
+    }
+}
+
+/// Field error returned when the caller is authenticated but not allowed to access a field
+pub fn forbidden_error(message: &str) -> FieldError {
+    FieldError::new(message, graphql_value!({ "code": "FORBIDDEN" }))
+}
+
+/// Convert a service error into a field error, keeping the `FORBIDDEN` code of policy denials
+pub fn guard_error(error: ApiError) -> FieldError {
+    match error {
+        ApiError::Forbidden(message) => forbidden_error(&message),
+        error => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::helpers::tests::get_data_store;
+
+    #[test]
+    fn it_rejects_anonymous_callers() {
+        let context = Context::new(get_data_store(), None, AuditContext::default());
+        assert!(context.require_auth().is_err());
+    }
+
+    #[test]
+    fn it_records_identity_changes() {
+        let context = Context::new(get_data_store(), None, AuditContext::default());
+        context.remember("token".to_string());
+        assert_eq!(
+            context.take_identity_change(),
+            Some(IdentityChange::Remember("token".to_string()))
+        );
+        assert_eq!(context.take_identity_change(), None);
+    }
 }
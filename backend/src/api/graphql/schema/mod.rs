@@ -1,7 +1,9 @@
 pub mod account_mutation;
+pub mod admin_mutation;
 pub mod context;
 pub mod mutation;
+pub mod organization_mutation;
 pub mod query;
 pub mod root;
 pub mod session_mutation;
-mod subscription;
+pub mod subscription;
@@ -1,5 +1,7 @@
 use crate::api::graphql::schema::account_mutation::AccountMutations;
+use crate::api::graphql::schema::admin_mutation::AdminMutations;
 use crate::api::graphql::schema::context::Context;
+use crate::api::graphql::schema::organization_mutation::OrganizationMutations;
 use crate::api::graphql::schema::session_mutation::SessionMutations;
 use juniper::FieldResult;
 
@@ -14,4 +16,14 @@ impl MutationRoot {
     fn account() -> FieldResult<AccountMutations> {
         Ok(AccountMutations {})
     }
+
+    /// Manage other accounts, every mutation is checked against the authorization policy
+    fn admin() -> FieldResult<AdminMutations> {
+        Ok(AdminMutations {})
+    }
+
+    /// Manage the organizations of the logged in account, roles are checked per organization
+    fn organization() -> FieldResult<OrganizationMutations> {
+        Ok(OrganizationMutations {})
+    }
 }
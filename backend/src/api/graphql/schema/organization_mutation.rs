@@ -0,0 +1,110 @@
+use juniper::FieldResult;
+
+use crate::api::graphql::schema::context::{guard_error, Context};
+use crate::api::services::organization_service;
+use crate::common::validate::validate_input;
+use crate::data::dtos::organization_dto::*;
+
+pub struct OrganizationMutations;
+
+#[juniper::graphql_object(context = Context)]
+impl OrganizationMutations {
+    /// Create an organization owned by the logged in account
+    async fn create(
+        context: &Context,
+        input: CreateOrganizationRequest,
+    ) -> FieldResult<OrganizationResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        let rsp =
+            organization_service::create_organization(&context.store, auth, &input, &context.audit)
+                .await?;
+        Ok(rsp)
+    }
+
+    /// Make the organization the one the session acts in, the reissued token is remembered
+    async fn activate(context: &Context, id: String) -> FieldResult<ActiveOrganizationResponse> {
+        let auth = context.require_auth()?;
+        let rsp =
+            organization_service::activate_organization(&context.store, auth, &id, &context.audit)
+                .await?;
+        context.remember(rsp.id_token.clone());
+        Ok(rsp)
+    }
+
+    /// Email an invitation to join the organization `id`
+    async fn invite(
+        context: &Context,
+        id: String,
+        input: CreateInvitationRequest,
+    ) -> FieldResult<InvitationResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        organization_service::invite_member(&context.store, auth, &id, &input, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// Revoke an open invitation of the organization `id`
+    async fn revokeInvitation(
+        context: &Context,
+        id: String,
+        invitation_id: String,
+    ) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        organization_service::revoke_invitation(
+            &context.store,
+            auth,
+            &id,
+            &invitation_id,
+            &context.audit,
+        )
+        .await
+        .map_err(guard_error)
+    }
+
+    /// Join an organization with the token of an emailed invitation
+    async fn acceptInvitation(
+        context: &Context,
+        input: AcceptInvitationRequest,
+    ) -> FieldResult<OrganizationResponse> {
+        let auth = context.require_auth()?;
+        validate_input(&input)?;
+        let rsp = organization_service::accept_invitation_token(
+            &context.store,
+            auth,
+            &input,
+            &context.audit,
+        )
+        .await?;
+        Ok(rsp)
+    }
+
+    /// Change the role of a member, only owners manage owners
+    async fn setMemberRole(
+        context: &Context,
+        id: String,
+        account_id: String,
+        input: UpdateMemberRequest,
+    ) -> FieldResult<MemberResponse> {
+        let auth = context.require_auth()?;
+        organization_service::set_member_role(
+            &context.store,
+            auth,
+            &id,
+            &account_id,
+            &input,
+            &context.audit,
+        )
+        .await
+        .map_err(guard_error)
+    }
+
+    /// Remove a member, members can remove themselves to leave
+    async fn removeMember(context: &Context, id: String, account_id: String) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        organization_service::remove_member(&context.store, auth, &id, &account_id, &context.audit)
+            .await
+            .map_err(guard_error)
+    }
+}
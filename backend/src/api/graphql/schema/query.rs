@@ -1,9 +1,27 @@
-use crate::api::graphql::schema::context::Context;
-use crate::api::services::account_service;
-use crate::data::dtos::account_dto::AccountResponse;
-use crate::data::dtos::auth_dto::IdentifierRequest;
+use crate::api::graphql::schema::context::{guard_error, Context};
+use crate::api::services::{
+    account_service, activity_service, admin_service, organization_service, profile_service,
+    session_service,
+};
+use crate::common::auth::guard::ACCOUNT_READ;
+use crate::data::dtos::account_dto::{AccountResponse, SignupTokenResponse};
+use crate::data::dtos::activity_dto::{ActivitiesResponse, PageRequest};
+use crate::data::dtos::api_key_dto::ApiKeysResponse;
+use crate::data::dtos::auth_dto::{IdentifierRequest, SignupTokenRequest};
+use crate::data::dtos::organization_dto::{
+    InvitationsResponse, MembersResponse, OrganizationResponse, OrganizationsResponse,
+};
+use crate::data::dtos::profile_dto::{ProfileResponse, ProfileSchemaResponse};
+use crate::data::dtos::session_dto::ActiveSessionsResponse;
 use juniper::FieldResult;
 
+fn page_request(page: Option<i32>, per_page: Option<i32>) -> PageRequest {
+    PageRequest {
+        page: page.map(i64::from),
+        per_page: per_page.map(i64::from),
+    }
+}
+
 pub struct QueryRoot;
 
 #[juniper::graphql_object(context = Context)]
@@ -12,20 +30,147 @@ impl QueryRoot {
         "1.0"
     }
 
+    /// The currently logged in account
+    async fn me(context: &Context) -> FieldResult<AccountResponse> {
+        let auth = context.require_auth()?;
+        let rsp = account_service::get_account(&context.store, &auth.id).await?;
+        Ok(rsp)
+    }
+
+    /// The custom profile fields of the currently logged in account
+    async fn myProfile(context: &Context) -> FieldResult<ProfileResponse> {
+        let auth = context.require_auth()?;
+        let rsp = profile_service::get_profile(&context.store, &auth.id).await?;
+        Ok(rsp)
+    }
+
+    /// The custom profile fields accepted at signup and profile update
+    fn profileSchema() -> ProfileSchemaResponse {
+        profile_service::get_profile_schema()
+    }
+
     #[graphql(arguments(identity(description = "auth of the account")))]
     async fn account(context: &Context, identity: String) -> FieldResult<AccountResponse> {
-        let rsp =
-            account_service::find_account(&context.store, &IdentifierRequest { identity }).await?;
+        let _ = context.require_auth()?;
+        let rsp = account_service::find_account(
+            &context.store,
+            &IdentifierRequest {
+                identity,
+                signup_token: None,
+            },
+        )
+        .await?;
+
+        let _ = context.authorize(ACCOUNT_READ, &rsp.id).await?;
         Ok(rsp)
     }
 
-    #[graphql(arguments(identity(description = "auth of the account")))]
-    async fn accountAvailable(context: &Context, identity: String) -> FieldResult<bool> {
+    #[graphql(arguments(id(description = "id of the account")))]
+    async fn accountById(context: &Context, id: String) -> FieldResult<AccountResponse> {
+        let auth = context.require_auth()?;
+        admin_service::get_account(&context.store, auth, &id)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// The activity log of the currently logged in account, newest first
+    async fn myActivities(
+        context: &Context,
+        page: Option<i32>,
+        per_page: Option<i32>,
+    ) -> FieldResult<ActivitiesResponse> {
+        let auth = context.require_auth()?;
+        let page = page_request(page, per_page);
         let rsp =
-            account_service::find_account(&context.store, &IdentifierRequest { identity }).await;
-        match rsp {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
-        }
+            activity_service::list_activities(&context.store, Some(auth.id.clone()), &page).await?;
+        Ok(rsp)
+    }
+
+    /// The devices the currently logged in account is signed in from
+    async fn mySessions(context: &Context) -> FieldResult<ActiveSessionsResponse> {
+        let auth = context.require_auth()?;
+        let rsp = session_service::list_sessions(&context.store, auth).await?;
+        Ok(rsp)
+    }
+
+    /// The organizations of the currently logged in account, the most recently joined first
+    async fn organizations(context: &Context) -> FieldResult<OrganizationsResponse> {
+        let auth = context.require_auth()?;
+        let rsp = organization_service::list_organizations(&context.store, auth).await?;
+        Ok(rsp)
+    }
+
+    #[graphql(arguments(id(description = "id of the organization")))]
+    async fn organization(context: &Context, id: String) -> FieldResult<OrganizationResponse> {
+        let auth = context.require_auth()?;
+        let rsp = organization_service::get_organization(&context.store, auth, &id).await?;
+        Ok(rsp)
+    }
+
+    /// The members of the organization `id`, oldest first
+    async fn members(context: &Context, id: String) -> FieldResult<MembersResponse> {
+        let auth = context.require_auth()?;
+        let rsp = organization_service::list_members(&context.store, auth, &id).await?;
+        Ok(rsp)
+    }
+
+    /// The open invitations of the organization `id`, for its admins and owners
+    async fn invitations(context: &Context, id: String) -> FieldResult<InvitationsResponse> {
+        let auth = context.require_auth()?;
+        organization_service::list_invitations(&context.store, auth, &id)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// The activity log of every account or of `accountId`, newest first
+    async fn activities(
+        context: &Context,
+        account_id: Option<String>,
+        page: Option<i32>,
+        per_page: Option<i32>,
+    ) -> FieldResult<ActivitiesResponse> {
+        let auth = context.require_auth()?;
+        let page = page_request(page, per_page);
+        admin_service::list_activities(&context.store, auth, account_id, &page)
+            .await
+            .map_err(guard_error)
+    }
+
+    /// The api keys of the service account `id`, newest first
+    async fn apiKeys(context: &Context, id: String) -> FieldResult<ApiKeysResponse> {
+        let auth = context.require_auth()?;
+        admin_service::list_api_keys(&context.store, auth, &id)
+            .await
+            .map_err(guard_error)
+    }
+
+    #[graphql(arguments(
+        identity(description = "auth of the account"),
+        signup_token(description = "required when enumeration protection is on")
+    ))]
+    async fn accountAvailable(
+        context: &Context,
+        identity: String,
+        signup_token: Option<String>,
+    ) -> FieldResult<bool> {
+        let cmd = IdentifierRequest {
+            identity,
+            signup_token,
+        };
+        let rsp = account_service::check_availability(&context.store, &cmd).await?;
+        Ok(rsp)
+    }
+
+    /// A short lived token for `accountAvailable`
+    #[graphql(arguments(challenge_token(
+        description = "token of a solved challenge, required when a challenge provider is configured"
+    )))]
+    async fn signupToken(
+        context: &Context,
+        challenge_token: Option<String>,
+    ) -> FieldResult<SignupTokenResponse> {
+        let cmd = SignupTokenRequest { challenge_token };
+        let rsp = account_service::issue_signup_token(&cmd, &context.audit).await?;
+        Ok(rsp)
     }
 }
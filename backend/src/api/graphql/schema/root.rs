@@ -1,11 +1,11 @@
-use juniper::{EmptySubscription, RootNode};
+use juniper::RootNode;
 
-use crate::api::graphql::schema::context::Context;
 use crate::api::graphql::schema::mutation::MutationRoot;
 use crate::api::graphql::schema::query::QueryRoot;
+use crate::api::graphql::schema::subscription::Subscription;
 
-pub type Schema = RootNode<'static, QueryRoot, MutationRoot, EmptySubscription<Context>>;
+pub type Schema = RootNode<'static, QueryRoot, MutationRoot, Subscription>;
 
 pub fn create_schema() -> Schema {
-    Schema::new(QueryRoot, MutationRoot, EmptySubscription::new())
+    Schema::new(QueryRoot, MutationRoot, Subscription)
 }
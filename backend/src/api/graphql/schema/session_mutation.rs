@@ -2,9 +2,10 @@ use juniper::FieldResult;
 
 use crate::api::graphql::schema::context::Context;
 use crate::api::services;
-use crate::common::auth::utils::{create_jwt, PrivateClaim};
+use crate::common::validate::validate_input;
 use crate::data::dtos::account_dto::PostSessionResponse;
-use crate::data::dtos::auth_dto::PostSessionRequest;
+use crate::data::dtos::auth_dto::{PostSessionRequest, RenewPasswordRequest};
+use crate::data::dtos::session_dto::RevokedSessionsResponse;
 
 pub struct SessionMutations;
 
@@ -14,27 +15,65 @@ impl SessionMutations {
         context: &Context,
         input: PostSessionRequest,
     ) -> FieldResult<PostSessionResponse> {
-        let acct = services::session_service::create_session(&context.store, &input).await?;
-        // id.remember(acct.id.clone());
-
-        // create jwt token
-        let pc = PrivateClaim::new(acct.id, acct.email, acct.username, acct.mobile);
-        let token = create_jwt(pc).unwrap_or_default();
+        let rsp = services::session_service::create_session(&context.store, &input, &context.audit)
+            .await?;
+        if let Some(token) = &rsp.id_token {
+            context.remember(token.clone());
+        }
+        Ok(rsp)
+    }
 
-        Ok(PostSessionResponse {
-            id_token: Option::from(token),
-        })
+    /// Replace an expired password with the change password token of a login
+    async fn renewPassword(
+        context: &Context,
+        input: RenewPasswordRequest,
+    ) -> FieldResult<PostSessionResponse> {
+        validate_input(&input)?;
+        let rsp = services::session_service::renew_password(&context.store, &input, &context.audit)
+            .await?;
+        if let Some(token) = &rsp.id_token {
+            context.remember(token.clone());
+        }
+        Ok(rsp)
     }
 
+    /// Log out and clear the session cookie
     async fn delete(context: &Context) -> FieldResult<bool> {
-        // let acct = services::session_service::create_session(&context.store, &input).await?;
-        // id.remember(acct.id.clone());
-        Ok(false)
+        let auth = context.require_auth()?;
+        let rsp =
+            services::session_service::delete_session(&context.store, auth, &context.audit).await?;
+        context.forget();
+        Ok(rsp)
+    }
+
+    /// Sign out of one session of the logged in account
+    #[graphql(arguments(id(description = "id of the session")))]
+    async fn revoke(context: &Context, id: String) -> FieldResult<bool> {
+        let auth = context.require_auth()?;
+        let rsp =
+            services::session_service::revoke_session(&context.store, auth, &id, &context.audit)
+                .await?;
+        Ok(rsp)
+    }
+
+    /// Sign out of every session but the current one
+    async fn revokeOthers(context: &Context) -> FieldResult<RevokedSessionsResponse> {
+        let auth = context.require_auth()?;
+        let rsp =
+            services::session_service::revoke_other_sessions(&context.store, auth, &context.audit)
+                .await?;
+        Ok(rsp)
     }
 
-    async fn refresh(context: &Context) -> FieldResult<bool> {
-        // let acct = services::session_service::create_session(&context.store, &input).await?;
-        // id.remember(acct.id.clone());
-        Ok(false)
+    /// Issue a fresh id token for the logged in account
+    async fn refresh(context: &Context) -> FieldResult<PostSessionResponse> {
+        let auth = context.require_auth()?;
+        let acct = services::account_service::get_account(&context.store, &auth.id).await?;
+
+        let token =
+            services::session_service::session_token(&context.store, &acct, &context.audit).await?;
+        context.remember(token.clone());
+
+        Ok(PostSessionResponse::new(token))
     }
 }
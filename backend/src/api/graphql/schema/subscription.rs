@@ -1,2 +1,72 @@
-#[allow(unused)]
-struct Subscription;
+use std::pin::Pin;
+
+use futures::{future, Stream, StreamExt};
+use juniper::{FieldError, FieldResult};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api::graphql::schema::context::Context;
+use crate::data::dtos::account_dto::AccountEventResponse;
+use crate::events::{subscribe_events, AccountEvent, AccountEventKind};
+
+type AccountEventStream =
+    Pin<Box<dyn Stream<Item = Result<AccountEventResponse, FieldError>> + Send>>;
+
+const SESSION_EVENTS: [AccountEventKind; 3] = [
+    AccountEventKind::LoggedIn,
+    AccountEventKind::LoggedOut,
+    AccountEventKind::Locked,
+];
+
+const LIFECYCLE_EVENTS: [AccountEventKind; 5] = [
+    AccountEventKind::Created,
+    AccountEventKind::Updated,
+    AccountEventKind::Locked,
+    AccountEventKind::Unlocked,
+    AccountEventKind::Deleted,
+];
+
+/// Stream the events of the event bus that match the filter
+fn event_stream<F>(filter: F) -> AccountEventStream
+where
+    F: Fn(&AccountEvent) -> bool + Send + 'static,
+{
+    let stream = futures::stream::unfold(subscribe_events(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => return Some((event, rx)),
+                // a slow subscriber skips the events it missed
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .filter(move |event| future::ready(filter(event)))
+    .map(|event| Ok(event.into()));
+
+    Box::pin(stream)
+}
+
+pub struct Subscription;
+
+#[juniper::graphql_subscription(context = Context)]
+impl Subscription {
+    /// Logins, logouts and locks of the logged in account
+    async fn sessionEvents(context: &Context) -> FieldResult<AccountEventStream> {
+        let account_id = context.require_auth()?.id.clone();
+        let tenant_id = context.store.tenant().to_string();
+        Ok(event_stream(move |event| {
+            event.tenant_id == tenant_id
+                && event.account_id == account_id
+                && SESSION_EVENTS.contains(&event.kind)
+        }))
+    }
+
+    /// Lifecycle events of every account of the tenant, only available to admins
+    async fn accountEvents(context: &Context) -> FieldResult<AccountEventStream> {
+        let _ = context.require_admin().await?;
+        let tenant_id = context.store.tenant().to_string();
+        Ok(event_stream(move |event| {
+            event.tenant_id == tenant_id && LIFECYCLE_EVENTS.contains(&event.kind)
+        }))
+    }
+}
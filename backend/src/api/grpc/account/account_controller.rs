@@ -1,97 +1,202 @@
-/*
-use actix_identity::Identity;
-use actix_protobuf::ProtoBuf;
-use actix_protobuf::*;
-use actix_web::web::Data;
-use actix_web::HttpResponse;
-
-use crate::common::auth::utils::{create_jwt, PrivateClaim};
-use crate::common::errors::ApiError;
-use crate::common::helpers::{respond_json, AppResult};
-use crate::common::validate::validate;
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::api::grpc::auth::{audit_context, authenticate, tenant_store};
+use crate::api::services;
+use crate::common::validate::validate_input;
+use crate::data::dtos::auth_dto::{
+    ChangePasswordRequest, ConfirmChangeRequest, IdentifierRequest, PostAccountRequest,
+    SignupTokenRequest, UpdateAccountRequest,
+};
 use crate::data::stores::base_store_trait::BoxedStoreType;
-use crate::dtos::account_dto::{AccountResponse, PostAccountResponse};
-use crate::dtos::auth_dto::*;
-use crate::services;
-
-/// Handler to login a user and create a session for the user
-pub async fn post_session_grpc(
-    id: Identity,
-    store: Data<BoxedStoreType>,
-    msg: ProtoBuf<PostSessionRequest>,
-) -> AppResult<HttpResponse<PostAccountResponse>> {
-    validate(&msg)?;
-
-    let cmd = PostSessionRequest {
-        auth: msg.auth.clone(),
-        password: msg.password.clone(),
-    };
-    let acct = services::account_services::login_account(&store, &cmd).await?;
-    id.remember(acct.id.clone());
-
-    // create jwt token
-    let pc = PrivateClaim::new(acct.id, acct.email, acct.username, acct.mobile);
-    let token = create_jwt(pc)?;
-
-    HttpResponse::Ok().protobuf(PostAccountResponse {
-        id_token: Some(token),
-    })
+use crate::proto::gen::account_service_server::AccountService;
+use crate::proto::gen::{
+    AccountCreateRequest, AccountCreateResponse, AccountUpdateRequest, AccountUpdateResponse,
+    AvailabilityResponse, ChangePasswordRequest as ChangePasswordMessage,
+    ConfirmChangeRequest as ConfirmChangeMessage, EmptyRequest,
+    IdentifierRequest as IdentifierMessage, SessionResponse,
+    SignupTokenRequest as SignupTokenMessage, SignupTokenResponse, StatusResponse,
+};
+
+/// gRPC account service, it shares the account services with the REST api
+pub struct AccountController {
+    store: Arc<BoxedStoreType>,
 }
 
-/// Handler for creating a new account
-pub async fn post_account_grpc(
-    store: Data<BoxedStoreType>,
-    msg: ProtoBuf<PostAccountRequest>,
-) -> AppResult<HttpResponse<PostAccountResponse>> {
-    validate(&msg)?;
-
-    let cmd = PostAccountRequest {
-        email: msg.email.clone(),
-        password: msg.password.clone(),
-        confirm_password: msg.confirm_password.clone(),
-        username: msg.username.clone(),
-        mobile: msg.mobile.clone(),
-    };
-    let acct = services::account_services::create_account(&store, &cmd).await?;
-
-    // create jwt token
-    let pc = PrivateClaim::new(acct.id, acct.email, acct.username, acct.mobile);
-    let token = create_jwt(pc).unwrap_or_default();
-
-    HttpResponse::Ok().protobuf(PostAccountResponse {
-        id_token: Some(token),
-    })
+impl AccountController {
+    pub fn new(store: Arc<BoxedStoreType>) -> Self {
+        AccountController { store }
+    }
 }
 
-/// This handler checks if an account is available or not.
-pub async fn get_available_account_grpc(
-    id: Identity,
-    store: Data<BoxedStoreType>,
-    msg: ProtoBuf<IdentifierRequest>,
-) -> AppResult<HttpResponse<AccountResponse>> {
-    validate(&msg)?;
-
-    println!("Helllo {}", id.auth().unwrap_or_default());
-    let cmd = IdentifierRequest {
-        auth: msg.auth.clone(),
-    };
-    let acct = services::account_services::find_account(&store, &cmd).await;
-
-    match acct {
-        Ok(_) => HttpResponse::Ok().protobuf(true),
-        Err(_) => HttpResponse::Ok().protobuf(false),
+#[tonic::async_trait]
+impl AccountService for AccountController {
+    /// Handler for creating a new account
+    async fn create_account(
+        &self,
+        request: Request<AccountCreateRequest>,
+    ) -> Result<Response<AccountCreateResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let audit = audit_context(&request);
+        let cmd: PostAccountRequest = request.into_inner().into();
+        validate_input(&cmd)?;
+
+        let acct = services::account_service::create_account(&store, &cmd, &audit).await?;
+
+        let token = services::session_service::open_session(&store, &acct, &audit).await?;
+
+        Ok(Response::new(AccountCreateResponse { id_token: token }))
+    }
+
+    /// This handler checks if an account is available or not.
+    async fn account_available(
+        &self,
+        request: Request<IdentifierMessage>,
+    ) -> Result<Response<AvailabilityResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let cmd: IdentifierRequest = request.into_inner().into();
+        validate_input(&cmd)?;
+
+        let available = services::account_service::check_availability(&store, &cmd).await?;
+        Ok(Response::new(AvailabilityResponse { available }))
+    }
+
+    /// Handler for issuing the signup token the availability check asks for
+    async fn create_signup_token(
+        &self,
+        request: Request<SignupTokenMessage>,
+    ) -> Result<Response<SignupTokenResponse>, Status> {
+        let audit = audit_context(&request);
+        let cmd: SignupTokenRequest = request.into_inner().into();
+        let rsp = services::account_service::issue_signup_token(&cmd, &audit).await?;
+        Ok(Response::new(rsp.into()))
+    }
+
+    /// Handler for locking accounts
+    async fn lock_account(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let status =
+            services::account_service::lock_account(&store, current_account.id, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for unlocking accounts
+    async fn unlock_account(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let status =
+            services::account_service::unlock_account(&store, current_account.id, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for deleting accounts
+    async fn delete_account(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let status =
+            services::account_service::delete_account(&store, current_account, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for the calling account updating its username, email or mobile
+    async fn update_account(
+        &self,
+        request: Request<AccountUpdateRequest>,
+    ) -> Result<Response<AccountUpdateResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let cmd: UpdateAccountRequest = request.into_inner().into();
+        validate_input(&cmd)?;
+
+        let rsp = services::account_service::update_current_account(
+            &store,
+            &current_account.id,
+            &cmd,
+            &audit,
+        )
+        .await?;
+        Ok(Response::new(rsp.into()))
+    }
+
+    /// Handler for confirming a pending email change
+    async fn confirm_email(
+        &self,
+        request: Request<ConfirmChangeMessage>,
+    ) -> Result<Response<AccountUpdateResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let cmd: ConfirmChangeRequest = request.into_inner().into();
+        validate_input(&cmd)?;
+
+        let rsp =
+            services::account_service::confirm_email(&store, &current_account.id, &cmd, &audit)
+                .await?;
+        Ok(Response::new(rsp.into()))
+    }
+
+    /// Handler for verifying a pending mobile change
+    async fn verify_mobile(
+        &self,
+        request: Request<ConfirmChangeMessage>,
+    ) -> Result<Response<AccountUpdateResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let cmd: ConfirmChangeRequest = request.into_inner().into();
+        validate_input(&cmd)?;
+
+        let rsp =
+            services::account_service::verify_mobile(&store, &current_account.id, &cmd, &audit)
+                .await?;
+        Ok(Response::new(rsp.into()))
+    }
+
+    /// Handler for the calling account changing its password, other sessions are revoked
+    async fn change_password(
+        &self,
+        request: Request<ChangePasswordMessage>,
+    ) -> Result<Response<SessionResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let cmd: ChangePasswordRequest = request.into_inner().into();
+        validate_input(&cmd)?;
+
+        let rsp =
+            services::account_service::change_password(&store, &current_account.id, &cmd, &audit)
+                .await?;
+        Ok(Response::new(rsp.into()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::tests::helpers::tests::get_store;
 
     #[actix_rt::test]
-    async fn test_login_with_bad_request() {
-        // let response = get_health().await.unwrap();
-        // assert_eq!(response.into_inner().status, "ok".to_string());
+    async fn test_lock_account_without_token() {
+        let controller = AccountController::new(Arc::new(get_store()));
+        let status = controller
+            .lock_account(Request::new(EmptyRequest {}))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
     }
 }
-
- */
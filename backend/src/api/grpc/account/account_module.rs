@@ -1,21 +1,29 @@
-//! Device module crate
-/*
-use actix_web::web;
+//! Account grpc module crate
+use std::sync::Arc;
 
-use super::account_controller::*;
+use super::account_controller::AccountController;
+use super::session_controller::SessionController;
 use crate::config::CONFIG;
+use crate::data::stores::base_store_trait::BoxedStoreType;
+use crate::proto::gen::account_service_server::AccountServiceServer;
+use crate::proto::gen::session_service_server::SessionServiceServer;
 
-pub fn accounts_module_grpc(cfg: &mut web::ServiceConfig) {
-    if CONFIG.features.auth.enable_signup {
-        cfg.service(
-            web::scope("/accounts")
-                .route("", web::post().to(post_account_grpc))
-                .route("/available", web::get().to(get_available_account_grpc)),
-        );
+pub fn accounts_grpc_service(
+    store: Arc<BoxedStoreType>,
+) -> Option<AccountServiceServer<AccountController>> {
+    if !CONFIG.features.auth.enable_signup {
+        return None;
     }
 
-    if CONFIG.features.auth.enable_login {
-        cfg.service(web::scope("/session").route("", web::post().to(post_session_grpc)));
+    Some(AccountServiceServer::new(AccountController::new(store)))
+}
+
+pub fn session_grpc_service(
+    store: Arc<BoxedStoreType>,
+) -> Option<SessionServiceServer<SessionController>> {
+    if !CONFIG.features.auth.enable_login {
+        return None;
     }
+
+    Some(SessionServiceServer::new(SessionController::new(store)))
 }
-*/
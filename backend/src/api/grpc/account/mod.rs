@@ -1,4 +1,6 @@
-#[cfg(feature = "server-actix")]
+#[cfg(feature = "server-grpc")]
 pub mod account_controller;
-#[cfg(feature = "server-actix")]
+#[cfg(feature = "server-grpc")]
 pub mod account_module;
+#[cfg(feature = "server-grpc")]
+pub mod session_controller;
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::api::grpc::auth::{audit_context, authenticate, tenant_store};
+use crate::api::services;
+use crate::common::validate::validate_input;
+use crate::data::dtos::auth_dto::{PostSessionRequest, RenewPasswordRequest};
+use crate::data::stores::base_store_trait::BoxedStoreType;
+use crate::proto::gen::session_service_server::SessionService;
+use crate::proto::gen::{
+    ActiveSessionsResponse, EmptyRequest, RenewPasswordRequest as RenewPasswordMessage,
+    RevokeSessionRequest, RevokedSessionsResponse, SessionCreateRequest, SessionResponse,
+    StatusResponse,
+};
+
+/// gRPC session service, it shares the session services with the REST api
+pub struct SessionController {
+    store: Arc<BoxedStoreType>,
+}
+
+impl SessionController {
+    pub fn new(store: Arc<BoxedStoreType>) -> Self {
+        SessionController { store }
+    }
+}
+
+#[tonic::async_trait]
+impl SessionService for SessionController {
+    /// Handler to login a user and create a session for the user
+    async fn create_session(
+        &self,
+        request: Request<SessionCreateRequest>,
+    ) -> Result<Response<SessionResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let audit = audit_context(&request);
+        let cmd: PostSessionRequest = request.into_inner().into();
+        validate_input(&cmd)?;
+
+        let rsp = services::session_service::create_session(&store, &cmd, &audit).await?;
+        Ok(Response::new(rsp.into()))
+    }
+
+    /// Handler to replace an expired password with the change password token of a login
+    async fn renew_password(
+        &self,
+        request: Request<RenewPasswordMessage>,
+    ) -> Result<Response<SessionResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let audit = audit_context(&request);
+        let cmd: RenewPasswordRequest = request.into_inner().into();
+        validate_input(&cmd)?;
+
+        let rsp = services::session_service::renew_password(&store, &cmd, &audit).await?;
+        Ok(Response::new(rsp.into()))
+    }
+
+    /// Handler to log out. gRPC clients hold the token themselves so there is no cookie to clear
+    async fn delete_session(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let status =
+            services::session_service::delete_session(&store, &current_account, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler to list the active sessions of the calling account
+    async fn list_sessions(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<ActiveSessionsResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let rsp = services::session_service::list_sessions(&store, &current_account).await?;
+        Ok(Response::new(rsp.into()))
+    }
+
+    /// Handler to sign the calling account out of one of its sessions
+    async fn revoke_session(
+        &self,
+        request: Request<RevokeSessionRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let id = request.into_inner().id;
+        let status =
+            services::session_service::revoke_session(&store, &current_account, &id, &audit)
+                .await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler to sign the calling account out of every session but the one of the request
+    async fn revoke_other_sessions(
+        &self,
+        request: Request<EmptyRequest>,
+    ) -> Result<Response<RevokedSessionsResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let current_account = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&current_account.id);
+        let rsp =
+            services::session_service::revoke_other_sessions(&store, &current_account, &audit)
+                .await?;
+        Ok(Response::new(rsp.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::helpers::tests::get_store;
+
+    #[actix_rt::test]
+    async fn test_login_with_bad_request() {
+        let controller = SessionController::new(Arc::new(get_store()));
+        let status = controller
+            .create_session(Request::new(SessionCreateRequest {
+                identity: "".to_string(),
+                password: "".to_string(),
+                challenge_token: "".to_string(),
+                invitation_token: "".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+}
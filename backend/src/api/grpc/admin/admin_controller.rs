@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::api::grpc::auth::{audit_context, authenticate, tenant_store};
+use crate::api::services::admin_service;
+use crate::common::validate::validate_input;
+use crate::data::dtos::auth_dto::{SetAccountRolesRequest, UpdateAccountRequest};
+use crate::data::stores::base_store_trait::BoxedStoreType;
+use crate::proto::gen::admin_service_server::AdminService;
+use crate::proto::gen::{
+    AccountResponse, AdminAccountRequest, AdminConfirmAccountRequest, AdminDeleteAccountRequest,
+    AdminRolesRequest, AdminUpdateAccountRequest, StatusResponse,
+};
+
+/// gRPC admin service, it shares the admin services with the REST api
+pub struct AdminController {
+    store: Arc<BoxedStoreType>,
+}
+
+impl AdminController {
+    pub fn new(store: Arc<BoxedStoreType>) -> Self {
+        AdminController { store }
+    }
+}
+
+#[tonic::async_trait]
+impl AdminService for AdminController {
+    /// Handler for getting any account
+    async fn get_account(
+        &self,
+        request: Request<AdminAccountRequest>,
+    ) -> Result<Response<AccountResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let id = request.into_inner().id;
+        let acct = admin_service::get_account(&store, &caller, &id).await?;
+        Ok(Response::new(acct.into()))
+    }
+
+    /// Handler for updating another account
+    async fn update_account(
+        &self,
+        request: Request<AdminUpdateAccountRequest>,
+    ) -> Result<Response<AccountResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&caller.id);
+        let msg = request.into_inner();
+        let id = msg.id.clone();
+        let cmd: UpdateAccountRequest = msg.into();
+        validate_input(&cmd)?;
+
+        let acct = admin_service::update_account(&store, &caller, &id, &cmd, &audit).await?;
+        Ok(Response::new(acct.into()))
+    }
+
+    /// Handler for locking another account
+    async fn lock_account(
+        &self,
+        request: Request<AdminAccountRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&caller.id);
+        let id = request.into_inner().id;
+        let status = admin_service::lock_account(&store, &caller, &id, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for unlocking another account
+    async fn unlock_account(
+        &self,
+        request: Request<AdminAccountRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&caller.id);
+        let id = request.into_inner().id;
+        let status = admin_service::unlock_account(&store, &caller, &id, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for forcing another account to change its password
+    async fn require_new_password(
+        &self,
+        request: Request<AdminAccountRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&caller.id);
+        let id = request.into_inner().id;
+        let status = admin_service::require_new_password(&store, &caller, &id, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for soft or hard deleting another account
+    async fn delete_account(
+        &self,
+        request: Request<AdminDeleteAccountRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&caller.id);
+        let msg = request.into_inner();
+        let status =
+            admin_service::delete_account(&store, &caller, &msg.id, msg.hard, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for restoring a soft deleted account
+    async fn restore_account(
+        &self,
+        request: Request<AdminAccountRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&caller.id);
+        let id = request.into_inner().id;
+        let status = admin_service::restore_account(&store, &caller, &id, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for setting the confirmed status of another account
+    async fn set_confirmed(
+        &self,
+        request: Request<AdminConfirmAccountRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&caller.id);
+        let msg = request.into_inner();
+        let status =
+            admin_service::set_confirmed(&store, &caller, &msg.id, msg.confirmed, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+
+    /// Handler for replacing the roles and permissions of an account
+    async fn set_roles(
+        &self,
+        request: Request<AdminRolesRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let store = tenant_store(&self.store, &request)?;
+        let caller = authenticate(&store, &request).await?;
+        let audit = audit_context(&request).with_actor(&caller.id);
+        let msg = request.into_inner();
+        let id = msg.id.clone();
+        let cmd: SetAccountRolesRequest = msg.into();
+
+        let status = admin_service::set_account_roles(&store, &caller, &id, &cmd, &audit).await?;
+        Ok(Response::new(StatusResponse { status }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::helpers::tests::get_store;
+
+    #[actix_rt::test]
+    async fn test_get_account_without_token() {
+        let controller = AdminController::new(Arc::new(get_store()));
+        let status = controller
+            .get_account(Request::new(AdminAccountRequest {
+                id: "1234".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+}
@@ -0,0 +1,10 @@
+//! Admin grpc module crate
+use std::sync::Arc;
+
+use super::admin_controller::AdminController;
+use crate::data::stores::base_store_trait::BoxedStoreType;
+use crate::proto::gen::admin_service_server::AdminServiceServer;
+
+pub fn admin_grpc_service(store: Arc<BoxedStoreType>) -> AdminServiceServer<AdminController> {
+    AdminServiceServer::new(AdminController::new(store))
+}
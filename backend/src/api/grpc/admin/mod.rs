@@ -0,0 +1,4 @@
+#[cfg(feature = "server-grpc")]
+pub mod admin_controller;
+#[cfg(feature = "server-grpc")]
+pub mod admin_module;
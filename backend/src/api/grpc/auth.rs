@@ -0,0 +1,140 @@
+use tonic::{Request, Status};
+
+use crate::common::audit::{AuditContext, CORRELATION_HEADERS};
+use crate::common::auth::account::IdentityAccount;
+use crate::common::auth::api_key::authenticate_api_key;
+use crate::common::auth::revocation::ensure_current;
+use crate::common::auth::utils::decode_jwt;
+use crate::common::middleware::api_key::api_key_from_header;
+use crate::common::tenant::resolve_tenant;
+use crate::config::CONFIG;
+use crate::data::stores::base_store_trait::BoxedStoreType;
+
+/// The account store of the tenant named by the tenant metadata entry of `request`
+///
+/// gRPC has no host or path to resolve a tenant from, only the header source applies.
+pub fn tenant_store<T>(
+    store: &BoxedStoreType,
+    request: &Request<T>,
+) -> Result<BoxedStoreType, Status> {
+    let header = request
+        .metadata()
+        .get(CONFIG.tenancy.header_name.as_str())
+        .and_then(|value| value.to_str().ok());
+    let resolved = resolve_tenant(&CONFIG.tenancy, None, header, "")?;
+
+    Ok(store.for_tenant(&resolved.id))
+}
+
+/// Resolve the calling account from the `authorization: Bearer <id_token>` metadata entry.
+///
+/// This is the gRPC counterpart of the `IdentityAccount` extractor.
+pub fn authorize<T>(request: &Request<T>) -> Result<IdentityAccount, Status> {
+    let token = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+
+    let private_claim = decode_jwt(token)?;
+    Ok(private_claim.into())
+}
+
+/// Resolve the calling account like `authorize` and reject tokens revoked since they were issued
+///
+/// Service accounts authenticate with an `authorization: ApiKey <key>` entry instead.
+/// Either is looked up in the tenant of `store`.
+pub async fn authenticate<T>(
+    store: &BoxedStoreType,
+    request: &Request<T>,
+) -> Result<IdentityAccount, Status> {
+    let api_key = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(api_key_from_header);
+    if let Some(key) = api_key {
+        return Ok(authenticate_api_key(store, key).await?);
+    }
+
+    let account = authorize(request)?;
+    if account.tenant_id.as_deref() != Some(store.tenant()) {
+        return Err(Status::unauthenticated(
+            "token was issued in another tenant",
+        ));
+    }
+    ensure_current(store, &account).await?;
+    Ok(account)
+}
+
+/// Build the audit context of a gRPC request from its metadata and peer address
+pub fn audit_context<T>(request: &Request<T>) -> AuditContext {
+    let metadata_value = |name: &str| {
+        request
+            .metadata()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+
+    let correlation_id = CORRELATION_HEADERS
+        .iter()
+        .find_map(|name| metadata_value(name));
+
+    let account = authorize(request).ok();
+    let session_id = account.as_ref().and_then(|a| a.session_id.clone());
+    let impersonator_id = account.as_ref().and_then(|a| a.impersonator_id.clone());
+    AuditContext::new(
+        account.map(|account| account.id),
+        request.remote_addr().map(|addr| addr.ip().to_string()),
+        metadata_value("user-agent"),
+        correlation_id,
+    )
+    .in_session(session_id)
+    .impersonated_by(impersonator_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::auth::utils::{create_jwt, PrivateClaim};
+
+    #[test]
+    fn it_rejects_requests_without_a_token() {
+        let request = Request::new(());
+        let status = authorize(&request).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn it_authorizes_requests_with_a_bearer_token() {
+        let email = Option::from("test@test.com".to_string());
+        let private_claim = PrivateClaim::new("1234".to_string(), email, None, None);
+        let token = create_jwt(private_claim).unwrap();
+
+        let mut request = Request::new(());
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+
+        let account = authorize(&request).unwrap();
+        assert_eq!(account.id, "1234".to_string());
+        let audit = audit_context(&request);
+        assert_eq!(audit.actor_id, Some("1234".to_string()));
+        assert_eq!(audit.session_id, account.session_id);
+    }
+
+    #[test]
+    fn it_reads_the_correlation_id() {
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("x-correlation-id", "abc".parse().unwrap());
+
+        let audit = audit_context(&request);
+        assert_eq!(audit.correlation_id, "abc".to_string());
+        assert_eq!(audit.actor_id, None);
+    }
+}
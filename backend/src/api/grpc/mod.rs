@@ -1 +1,4 @@
 pub mod account;
+pub mod admin;
+#[cfg(feature = "server-grpc")]
+pub mod auth;
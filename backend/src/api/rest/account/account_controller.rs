@@ -1,78 +1,226 @@
-use actix_web::web::{Data, Json};
+use actix_web::web::{Json, Query};
+use actix_web::{HttpRequest, HttpResponse};
 
 use crate::api::services;
+use crate::common::audit::AuditContext;
 use crate::common::auth::account::IdentityAccount;
-use crate::common::auth::utils::{create_jwt, PrivateClaim};
+use crate::common::errors::ApiError;
 use crate::common::helpers::{respond_json, AppResult};
-use crate::common::validate::validate;
-use crate::data::dtos::account_dto::PostAccountResponse;
+use crate::common::tenant::TenantStore;
+use crate::common::utils::protobuf_utils::{respond_negotiated, ProtoOrJson};
+use crate::common::validate::validate_input;
+use crate::data::dtos::account_dto::{AccountUpdateResponse, PostAccountResponse};
+use crate::data::dtos::activity_dto::{ActivitiesResponse, PageRequest};
 use crate::data::dtos::auth_dto::*;
-use crate::data::stores::base_store_trait::BoxedStoreType;
+use crate::data::dtos::profile_dto::{
+    ProfileResponse, ProfileSchemaResponse, UpdateProfileRequest,
+};
+use crate::proto::gen;
 use actix_guardian_identity::Identity;
 
 /// Handler for creating a new account
 pub async fn post_account(
-    store: Data<BoxedStoreType>,
-    body: Json<PostAccountRequest>,
-) -> AppResult<Json<PostAccountResponse>> {
-    validate(&body)?;
+    req: HttpRequest,
+    store: TenantStore,
+    audit: AuditContext,
+    body: ProtoOrJson<PostAccountRequest, gen::AccountCreateRequest>,
+) -> AppResult<HttpResponse> {
+    validate_input(&*body)?;
 
     let cmd: PostAccountRequest = body.into_inner();
-    let acct = services::account_service::create_account(&store, &cmd).await?;
+    let acct = services::account_service::create_account(&store, &cmd, &audit).await?;
 
-    // create jwt token
-    let pc = PrivateClaim::new(acct.id, acct.email, acct.username, acct.mobile);
-    let token = create_jwt(pc).unwrap_or_default();
+    let token = services::session_service::open_session(&store, &acct, &audit).await?;
 
-    respond_json(PostAccountResponse {
-        id_token: Some(token),
-    })
+    respond_negotiated::<_, gen::AccountCreateResponse>(
+        &req,
+        PostAccountResponse {
+            id_token: Some(token),
+        },
+    )
+}
+
+/// Remember the refreshed token of an updated account in the session cookie
+fn remember_token(id: &Identity, rsp: &AccountUpdateResponse) {
+    if let Some(token) = &rsp.id_token {
+        id.remember(token.clone());
+    }
+}
+
+/// Handler for the logged in account updating its username, email or mobile
+pub async fn update_account(
+    store: TenantStore,
+    id: Identity,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    body: Json<UpdateAccountRequest>,
+) -> AppResult<Json<AccountUpdateResponse>> {
+    validate_input(&*body)?;
+
+    let account_id = current_account.id;
+    let rsp = services::account_service::update_current_account(&store, &account_id, &body, &audit)
+        .await?;
+    remember_token(&id, &rsp);
+    respond_json(rsp)
+}
+
+/// Handler for confirming a pending email change
+pub async fn confirm_email(
+    store: TenantStore,
+    id: Identity,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    body: Json<ConfirmChangeRequest>,
+) -> AppResult<Json<AccountUpdateResponse>> {
+    validate_input(&*body)?;
+
+    let account_id = current_account.id;
+    let rsp = services::account_service::confirm_email(&store, &account_id, &body, &audit).await?;
+    remember_token(&id, &rsp);
+    respond_json(rsp)
+}
+
+/// Handler for verifying a pending mobile change
+pub async fn verify_mobile(
+    store: TenantStore,
+    id: Identity,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    body: Json<ConfirmChangeRequest>,
+) -> AppResult<Json<AccountUpdateResponse>> {
+    validate_input(&*body)?;
+
+    let account_id = current_account.id;
+    let rsp = services::account_service::verify_mobile(&store, &account_id, &body, &audit).await?;
+    remember_token(&id, &rsp);
+    respond_json(rsp)
+}
+
+/// Handler for the logged in account changing its password, other sessions are revoked
+pub async fn change_password(
+    req: HttpRequest,
+    store: TenantStore,
+    id: Identity,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    body: ProtoOrJson<ChangePasswordRequest, gen::ChangePasswordRequest>,
+) -> AppResult<HttpResponse> {
+    validate_input(&*body)?;
+
+    let cmd: ChangePasswordRequest = body.into_inner();
+    let account_id = current_account.id;
+    let rsp = services::account_service::change_password(&store, &account_id, &cmd, &audit).await?;
+    if let Some(token) = &rsp.id_token {
+        id.remember(token.clone());
+    }
+
+    respond_negotiated::<_, gen::SessionResponse>(&req, rsp)
 }
 
 /// Handler for deleting accounts
 pub async fn delete_account(
-    store: Data<BoxedStoreType>,
+    req: HttpRequest,
+    store: TenantStore,
     id: Identity,
     current_account: IdentityAccount,
-) -> AppResult<Json<bool>> {
-    let acct = services::account_service::delete_account(&store, &id, current_account).await?;
-    respond_json(acct)
+    audit: AuditContext,
+) -> AppResult<HttpResponse> {
+    let acct = services::account_service::delete_account(&store, current_account, &audit).await?;
+    id.forget();
+    respond_negotiated::<_, gen::StatusResponse>(&req, acct)
 }
 
 /// Handler for unlocking accounts
 pub async fn unlock_account(
-    store: Data<BoxedStoreType>,
+    req: HttpRequest,
+    store: TenantStore,
     current_account: IdentityAccount,
-) -> AppResult<Json<bool>> {
+    audit: AuditContext,
+) -> AppResult<HttpResponse> {
     let id = current_account.id;
-    let acct = services::account_service::unlock_account(&store, id).await?;
-    respond_json(acct)
+    let acct = services::account_service::unlock_account(&store, id, &audit).await?;
+    respond_negotiated::<_, gen::StatusResponse>(&req, acct)
 }
 
 /// Handler for locking accounts
 pub async fn lock_account(
-    store: Data<BoxedStoreType>,
+    req: HttpRequest,
+    store: TenantStore,
     current_account: IdentityAccount,
-) -> AppResult<Json<bool>> {
+    audit: AuditContext,
+) -> AppResult<HttpResponse> {
     let id = current_account.id;
-    let acct = services::account_service::lock_account(&store, id).await?;
-    respond_json(acct)
+    let acct = services::account_service::lock_account(&store, id, &audit).await?;
+    respond_negotiated::<_, gen::StatusResponse>(&req, acct)
 }
 
 /// This handler checks if an account is available or not.
+///
+/// The identity is read from the query string, the body is still accepted for older clients
 pub async fn get_available_account(
-    store: Data<BoxedStoreType>,
-    body: Json<IdentifierRequest>,
-) -> AppResult<Json<bool>> {
-    validate(&body)?;
+    req: HttpRequest,
+    store: TenantStore,
+    query: Option<Query<IdentifierRequest>>,
+    body: Option<ProtoOrJson<IdentifierRequest, gen::IdentifierRequest>>,
+) -> AppResult<HttpResponse> {
+    let cmd: IdentifierRequest = match (query, body) {
+        (Some(query), _) => query.into_inner(),
+        (None, Some(body)) => body.into_inner(),
+        (None, None) => return Err(ApiError::BadRequest("identity is required".to_string())),
+    };
+    validate_input(&cmd)?;
+    let available = services::account_service::check_availability(&store, &cmd).await?;
 
-    let cmd: IdentifierRequest = body.into_inner();
-    let acct = services::account_service::find_account(&store, &cmd).await;
+    respond_negotiated::<_, gen::AvailabilityResponse>(&req, available)
+}
 
-    match acct {
-        Ok(_) => respond_json(true),
-        Err(_) => respond_json(false),
-    }
+/// Handler for issuing the signup token the availability check asks for
+pub async fn post_signup_token(
+    req: HttpRequest,
+    audit: AuditContext,
+    body: Option<ProtoOrJson<SignupTokenRequest, gen::SignupTokenRequest>>,
+) -> AppResult<HttpResponse> {
+    // the body is optional while no challenge provider is configured
+    let cmd = body.map(ProtoOrJson::into_inner).unwrap_or_default();
+    let rsp = services::account_service::issue_signup_token(&cmd, &audit).await?;
+    respond_negotiated::<_, gen::SignupTokenResponse>(&req, rsp)
+}
+
+/// Handler for listing the activity log of the logged in account
+pub async fn get_account_activities(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    page: Query<PageRequest>,
+) -> AppResult<Json<ActivitiesResponse>> {
+    let id = Some(current_account.id);
+    let rsp = services::activity_service::list_activities(&store, id, &page).await?;
+    respond_json(rsp)
+}
+
+/// Handler for getting the profile fields of the logged in account
+pub async fn get_profile(
+    store: TenantStore,
+    current_account: IdentityAccount,
+) -> AppResult<Json<ProfileResponse>> {
+    let rsp = services::profile_service::get_profile(&store, &current_account.id).await?;
+    respond_json(rsp)
+}
+
+/// Handler for updating the profile fields of the logged in account
+pub async fn update_profile(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    body: Json<UpdateProfileRequest>,
+) -> AppResult<Json<ProfileResponse>> {
+    let id = current_account.id;
+    let rsp = services::profile_service::update_profile(&store, &id, &body, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for describing the custom profile fields accepted at signup and update
+pub async fn get_profile_schema() -> AppResult<Json<ProfileSchemaResponse>> {
+    respond_json(services::profile_service::get_profile_schema())
 }
 
 #[cfg(test)]
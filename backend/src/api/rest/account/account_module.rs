@@ -1,49 +1,288 @@
 //! Device module crate
+use actix_web::http::Method;
 use actix_web::web;
 
 use super::account_controller::*;
 use super::session_controller::*;
-use crate::common::auth::Authorizer;
+use crate::api::rest::docs::openapi::{ApiRoutes, OpenApiBuilder, Operation, API_PREFIX};
 use crate::config::CONFIG;
+use crate::data::dtos::account_dto::{
+    AccountUpdateResponse, PostAccountResponse, PostSessionResponse, SignupTokenResponse,
+};
+use crate::data::dtos::activity_dto::ActivitiesResponse;
+use crate::data::dtos::auth_dto::{
+    ChangePasswordRequest, ConfirmChangeRequest, PostAccountRequest, PostSessionRequest,
+    RenewPasswordRequest, SignupTokenRequest, UpdateAccountRequest,
+};
+use crate::data::dtos::profile_dto::{
+    ProfileResponse, ProfileSchemaResponse, UpdateProfileRequest,
+};
+use crate::data::dtos::session_dto::{ActiveSessionsResponse, RevokedSessionsResponse};
 
-fn private_accounts_module(cfg: &mut web::ServiceConfig) {
-    cfg.service(
-        web::scope("")
-            .wrap(Authorizer)
-            .route("/lock", web::put().to(lock_account))
-            .route("/lock", web::patch().to(lock_account))
-            .route("/unlock", web::put().to(unlock_account))
-            .route("/unlock", web::patch().to(unlock_account))
-            .route("/available", web::get().to(get_available_account)),
-    );
+fn signup_routes(routes: &mut ApiRoutes) {
+    routes
+        .route(
+            Method::POST,
+            "/accounts",
+            |r| r.to(post_account),
+            Operation::new("accounts", "Create a new account")
+                .request::<PostAccountRequest>()
+                .response::<PostAccountResponse>()
+                .protobuf(),
+        )
+        .route(
+            Method::DELETE,
+            "/accounts",
+            |r| r.to(delete_account),
+            Operation::new("accounts", "Delete the logged in account")
+                .status_response()
+                .secured()
+                .protobuf(),
+        )
+        .route(
+            Method::GET,
+            "/accounts/available",
+            |r| r.to(get_available_account),
+            Operation::new("accounts", "Check if an account identity is taken")
+                .query("identity", true, "The username, email or mobile to check")
+                .query(
+                    "signup_token",
+                    false,
+                    "Token from `POST /api/v1/accounts/signup-token`, needed with enumeration protection",
+                )
+                .status_response()
+                .protobuf(),
+        )
+        .route(
+            Method::POST,
+            "/accounts/signup-token",
+            |r| r.to(post_signup_token),
+            Operation::new(
+                "accounts",
+                "Issue the signup token the availability check asks for",
+            )
+            .request::<SignupTokenRequest>()
+            .response::<SignupTokenResponse>()
+            .protobuf(),
+        )
+        .route(
+            Method::GET,
+            "/accounts/activities",
+            |r| r.to(get_account_activities),
+            Operation::new(
+                "accounts",
+                "List the activity log of the logged in account, `?page=&per_page=`",
+            )
+            .response::<ActivitiesResponse>()
+            .secured(),
+        )
+        .route(
+            Method::GET,
+            "/accounts/profile",
+            |r| r.to(get_profile),
+            Operation::new(
+                "accounts",
+                "Get the profile fields of the logged in account",
+            )
+            .response::<ProfileResponse>()
+            .secured(),
+        )
+        .route(
+            Method::GET,
+            "/accounts/profile/schema",
+            |r| r.to(get_profile_schema),
+            Operation::new("accounts", "Describe the custom profile fields")
+                .response::<ProfileSchemaResponse>(),
+        )
+        .route(
+            Method::PUT,
+            "/accounts/password",
+            |r| r.to(change_password),
+            Operation::new(
+                "accounts",
+                "Change the password of the logged in account and revoke its other sessions",
+            )
+            .request::<ChangePasswordRequest>()
+            .response::<PostSessionResponse>()
+            .secured()
+            .protobuf(),
+        )
+        .route(
+            Method::POST,
+            "/accounts/email/confirm",
+            |r| r.to(confirm_email),
+            Operation::new(
+                "accounts",
+                "Confirm a pending email change with the token sent to the new email",
+            )
+            .request::<ConfirmChangeRequest>()
+            .response::<AccountUpdateResponse>()
+            .secured(),
+        )
+        .route(
+            Method::POST,
+            "/accounts/mobile/verify",
+            |r| r.to(verify_mobile),
+            Operation::new(
+                "accounts",
+                "Verify a pending mobile change with the one time code sent to the new mobile",
+            )
+            .request::<ConfirmChangeRequest>()
+            .response::<AccountUpdateResponse>()
+            .secured(),
+        );
+
+    for method in &[Method::PUT, Method::PATCH] {
+        routes
+            .route(
+                method.clone(),
+                "/accounts",
+                |r| r.to(update_account),
+                Operation::new(
+                    "accounts",
+                    "Update the username, email or mobile of the logged in account",
+                )
+                .request::<UpdateAccountRequest>()
+                .response::<AccountUpdateResponse>()
+                .secured(),
+            )
+            .route(
+                method.clone(),
+                "/accounts/profile",
+                |r| r.to(update_profile),
+                Operation::new(
+                    "accounts",
+                    "Update the profile fields of the logged in account",
+                )
+                .request::<UpdateProfileRequest>()
+                .response::<ProfileResponse>()
+                .secured(),
+            )
+            .route(
+                method.clone(),
+                "/accounts/lock",
+                |r| r.to(lock_account),
+                Operation::new("accounts", "Lock the logged in account")
+                    .status_response()
+                    .secured()
+                    .protobuf(),
+            )
+            .route(
+                method.clone(),
+                "/accounts/unlock",
+                |r| r.to(unlock_account),
+                Operation::new("accounts", "Unlock the logged in account")
+                    .status_response()
+                    .secured()
+                    .protobuf(),
+            );
+    }
 }
 
-fn public_accounts_module(cfg: &mut web::ServiceConfig) {
-    if CONFIG.features.auth.enable_signup {
-        cfg.service(
-            web::scope("")
-                .route("", web::post().to(post_account))
-                .route("", web::delete().to(delete_account))
-                .route("/available", web::get().to(get_available_account)),
+fn login_routes(routes: &mut ApiRoutes) {
+    routes
+        .route(
+            Method::POST,
+            "/session",
+            |r| r.to(post_session),
+            Operation::new(
+                "session",
+                "Login and create a session, an expired password returns a change password token",
+            )
+            .request::<PostSessionRequest>()
+            .response::<PostSessionResponse>()
+            .protobuf(),
+        )
+        .route(
+            Method::DELETE,
+            "/session",
+            |r| r.to(delete_session),
+            Operation::new("session", "Logout of the current session")
+                .status_response()
+                .secured()
+                .protobuf(),
+        )
+        .route(
+            Method::PUT,
+            "/session/password",
+            |r| r.to(renew_password),
+            Operation::new(
+                "session",
+                "Replace an expired password and create a session",
+            )
+            .request::<RenewPasswordRequest>()
+            .response::<PostSessionResponse>()
+            .protobuf(),
+        )
+        .route(
+            Method::GET,
+            "/sessions",
+            |r| r.to(get_sessions),
+            Operation::new(
+                "session",
+                "List the devices the logged in account is signed in from",
+            )
+            .response::<ActiveSessionsResponse>()
+            .secured()
+            .protobuf(),
+        )
+        .route(
+            Method::DELETE,
+            "/sessions",
+            |r| r.to(delete_other_sessions),
+            Operation::new("session", "Sign out of every session but the current one")
+                .response::<RevokedSessionsResponse>()
+                .secured()
+                .protobuf(),
+        )
+        .route(
+            Method::DELETE,
+            "/sessions/{id}",
+            |r| r.to(delete_session_by_id),
+            Operation::new(
+                "session",
+                "Sign out of one session of the logged in account",
+            )
+            .status_response()
+            .secured()
+            .protobuf(),
         );
-    }
 }
 
-pub fn accounts_module(cfg: &mut web::ServiceConfig) {
+/// The account and session routes, declared once for the app and the OpenAPI document
+fn accounts_routes() -> ApiRoutes {
+    let mut routes = ApiRoutes::new(API_PREFIX);
     if CONFIG.features.auth.enable_signup {
-        cfg.service(
-            web::scope("/accounts")
-                .configure(private_accounts_module)
-                .configure(public_accounts_module),
-        );
+        signup_routes(&mut routes);
     }
-
     if CONFIG.features.auth.enable_login {
-        cfg.service(
-            web::scope("/session")
-                .route("", web::post().to(post_session))
-                .wrap(Authorizer)
-                .route("", web::delete().to(delete_session)),
-        );
+        login_routes(&mut routes);
+    }
+    routes
+}
+
+pub fn accounts_module(cfg: &mut web::ServiceConfig) {
+    accounts_routes().register(cfg);
+}
+
+/// OpenAPI description of the routes registered by `accounts_module`
+pub fn accounts_api_spec(spec: &mut OpenApiBuilder) {
+    accounts_routes().describe(spec);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_documents_the_availability_check_as_public() {
+        let mut builder = OpenApiBuilder::new();
+        accounts_api_spec(&mut builder);
+        let doc = builder.build();
+
+        let available = &doc["paths"]["/api/v1/accounts/available"]["get"];
+        assert!(available.is_object());
+        assert!(available["security"].is_null());
+        assert!(doc["paths"]["/api/v1/accounts"]["put"]["security"].is_array());
     }
 }
@@ -1,42 +1,101 @@
 use actix_guardian_identity::Identity;
-use actix_web::web::{Data, Json};
+use actix_web::web::Path;
+use actix_web::{HttpRequest, HttpResponse};
 
 use crate::api::services;
-use crate::common::auth::utils::{create_jwt, PrivateClaim};
-use crate::common::helpers::{respond_json, AppResult};
-use crate::common::validate::validate;
-use crate::data::dtos::account_dto::PostAccountResponse;
-use crate::data::dtos::auth_dto::PostSessionRequest;
-use crate::data::stores::base_store_trait::BoxedStoreType;
+use crate::common::audit::AuditContext;
+use crate::common::auth::account::IdentityAccount;
+use crate::common::helpers::AppResult;
+use crate::common::tenant::TenantStore;
+use crate::common::utils::protobuf_utils::{respond_negotiated, ProtoOrJson};
+use crate::common::validate::validate_input;
+use crate::data::dtos::auth_dto::{PostSessionRequest, RenewPasswordRequest};
+use crate::proto::gen;
 
 /// Handler to login a user and create a session for the user
 pub async fn post_session(
+    req: HttpRequest,
     id: Identity,
-    store: Data<BoxedStoreType>,
-    body: Json<PostSessionRequest>,
-) -> AppResult<Json<PostAccountResponse>> {
-    validate(&body)?;
+    store: TenantStore,
+    audit: AuditContext,
+    body: ProtoOrJson<PostSessionRequest, gen::SessionCreateRequest>,
+) -> AppResult<HttpResponse> {
+    validate_input(&*body)?;
 
     let cmd: PostSessionRequest = body.into_inner();
-    let acct = services::session_service::create_session(&store, &cmd).await?;
+    let rsp = services::session_service::create_session(&store, &cmd, &audit).await?;
+    if let Some(token) = &rsp.id_token {
+        id.remember(token.clone());
+    }
+
+    respond_negotiated::<_, gen::SessionResponse>(&req, rsp)
+}
 
-    // create jwt token
-    let pc = PrivateClaim::new(acct.id, acct.email, acct.username, acct.mobile);
-    let token = create_jwt(pc)?;
-    id.remember(token.clone());
+/// Handler to replace an expired password with the change password token of a login
+pub async fn renew_password(
+    req: HttpRequest,
+    id: Identity,
+    store: TenantStore,
+    audit: AuditContext,
+    body: ProtoOrJson<RenewPasswordRequest, gen::RenewPasswordRequest>,
+) -> AppResult<HttpResponse> {
+    validate_input(&*body)?;
+
+    let cmd: RenewPasswordRequest = body.into_inner();
+    let rsp = services::session_service::renew_password(&store, &cmd, &audit).await?;
+    if let Some(token) = &rsp.id_token {
+        id.remember(token.clone());
+    }
 
-    respond_json(PostAccountResponse {
-        id_token: Some(token),
-    })
+    respond_negotiated::<_, gen::SessionResponse>(&req, rsp)
 }
 
 /// Handler to delete account from store
 pub async fn delete_session(
+    req: HttpRequest,
     identity: Identity,
-    store: Data<BoxedStoreType>,
-) -> AppResult<Json<bool>> {
-    let resp = services::session_service::delete_session(&store, &identity).await?;
-    respond_json(resp)
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+) -> AppResult<HttpResponse> {
+    let resp = services::session_service::delete_session(&store, &current_account, &audit).await?;
+    identity.forget();
+    respond_negotiated::<_, gen::StatusResponse>(&req, resp)
+}
+
+/// Handler to list the active sessions of the logged in account
+pub async fn get_sessions(
+    req: HttpRequest,
+    store: TenantStore,
+    current_account: IdentityAccount,
+) -> AppResult<HttpResponse> {
+    let rsp = services::session_service::list_sessions(&store, &current_account).await?;
+    respond_negotiated::<_, gen::ActiveSessionsResponse>(&req, rsp)
+}
+
+/// Handler to sign the logged in account out of one of its sessions
+pub async fn delete_session_by_id(
+    req: HttpRequest,
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+) -> AppResult<HttpResponse> {
+    let rsp =
+        services::session_service::revoke_session(&store, &current_account, &id, &audit).await?;
+    respond_negotiated::<_, gen::StatusResponse>(&req, rsp)
+}
+
+/// Handler to sign the logged in account out of every session but the current one
+pub async fn delete_other_sessions(
+    req: HttpRequest,
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+) -> AppResult<HttpResponse> {
+    let rsp =
+        services::session_service::revoke_other_sessions(&store, &current_account, &audit).await?;
+    respond_negotiated::<_, gen::RevokedSessionsResponse>(&req, rsp)
 }
 
 #[cfg(test)]
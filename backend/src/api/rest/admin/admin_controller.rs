@@ -0,0 +1,218 @@
+use actix_guardian_identity::Identity;
+use actix_web::web::{Json, Path, Query};
+
+use crate::api::services::admin_service;
+use crate::common::audit::AuditContext;
+use crate::common::auth::account::IdentityAccount;
+use crate::common::helpers::{respond_json, AppResult};
+use crate::common::tenant::TenantStore;
+use crate::common::validate::validate;
+use crate::data::dtos::account_dto::AccountResponse;
+use crate::data::dtos::activity_dto::{ActivitiesQuery, ActivitiesResponse, PageRequest};
+use crate::data::dtos::api_key_dto::{
+    ApiKeysResponse, CreateApiKeyRequest, CreateServiceAccountRequest, CreatedApiKeyResponse,
+};
+use crate::data::dtos::auth_dto::{
+    DeleteAccountQuery, SetAccountRolesRequest, SetConfirmedRequest, UpdateAccountRequest,
+};
+use crate::data::dtos::session_dto::ImpersonationResponse;
+
+/// Handler for getting any account
+pub async fn get_account(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    id: Path<String>,
+) -> AppResult<Json<AccountResponse>> {
+    let acct = admin_service::get_account(&store, &current_account, &id).await?;
+    respond_json(acct)
+}
+
+/// Handler for updating another account
+pub async fn update_account(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+    body: Json<UpdateAccountRequest>,
+) -> AppResult<Json<AccountResponse>> {
+    validate(&body)?;
+
+    let acct = admin_service::update_account(&store, &current_account, &id, &body, &audit).await?;
+    respond_json(acct)
+}
+
+/// Handler for locking another account
+pub async fn lock_account(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+) -> AppResult<Json<bool>> {
+    let rsp = admin_service::lock_account(&store, &current_account, &id, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for unlocking another account
+pub async fn unlock_account(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+) -> AppResult<Json<bool>> {
+    let rsp = admin_service::unlock_account(&store, &current_account, &id, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for forcing another account to change its password
+pub async fn require_new_password(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+) -> AppResult<Json<bool>> {
+    let rsp = admin_service::require_new_password(&store, &current_account, &id, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for deleting another account, `?hard=true` removes it permanently
+pub async fn delete_account(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+    query: Query<DeleteAccountQuery>,
+) -> AppResult<Json<bool>> {
+    let rsp =
+        admin_service::delete_account(&store, &current_account, &id, query.hard, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for restoring a soft deleted account
+pub async fn restore_account(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+) -> AppResult<Json<bool>> {
+    let rsp = admin_service::restore_account(&store, &current_account, &id, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for setting the confirmed status of another account
+pub async fn put_account_confirmed(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+    body: Json<SetConfirmedRequest>,
+) -> AppResult<Json<bool>> {
+    let rsp =
+        admin_service::set_confirmed(&store, &current_account, &id, body.confirmed, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for replacing the roles and permissions of an account
+///
+/// An admin changing their own privileges gets their session identity re-issued
+pub async fn put_account_roles(
+    store: TenantStore,
+    identity: Identity,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+    body: Json<SetAccountRolesRequest>,
+) -> AppResult<Json<bool>> {
+    let rsp =
+        admin_service::set_account_roles(&store, &current_account, &id, &body, &audit).await?;
+    if current_account.id == *id {
+        identity.renew();
+    }
+    respond_json(rsp)
+}
+
+/// Handler for listing the activity log of every account, `?account_id=` narrows it to one
+pub async fn get_activities(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    query: Query<ActivitiesQuery>,
+) -> AppResult<Json<ActivitiesResponse>> {
+    let page = PageRequest::from(&*query);
+    let account_id = query.into_inner().account_id;
+    let rsp = admin_service::list_activities(&store, &current_account, account_id, &page).await?;
+    respond_json(rsp)
+}
+
+/// Handler for listing the activity log of another account
+pub async fn get_account_activities(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    id: Path<String>,
+    page: Query<PageRequest>,
+) -> AppResult<Json<ActivitiesResponse>> {
+    let account_id = Some(id.into_inner());
+    let rsp = admin_service::list_activities(&store, &current_account, account_id, &page).await?;
+    respond_json(rsp)
+}
+
+/// Handler for creating a service account
+pub async fn post_service_account(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    body: Json<CreateServiceAccountRequest>,
+) -> AppResult<Json<AccountResponse>> {
+    validate(&body)?;
+
+    let rsp =
+        admin_service::create_service_account(&store, &current_account, &body, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for listing the api keys of a service account
+pub async fn get_api_keys(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    id: Path<String>,
+) -> AppResult<Json<ApiKeysResponse>> {
+    let rsp = admin_service::list_api_keys(&store, &current_account, &id).await?;
+    respond_json(rsp)
+}
+
+/// Handler for creating an api key of a service account, the key is only returned here
+pub async fn post_api_key(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+    body: Json<CreateApiKeyRequest>,
+) -> AppResult<Json<CreatedApiKeyResponse>> {
+    validate(&body)?;
+
+    let rsp = admin_service::create_api_key(&store, &current_account, &id, &body, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for revoking an api key of a service account
+pub async fn delete_api_key(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    path: Path<(String, String)>,
+) -> AppResult<Json<bool>> {
+    let (id, key_id) = path.into_inner();
+    let rsp = admin_service::revoke_api_key(&store, &current_account, &id, &key_id, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for an admin starting to impersonate another account
+///
+/// The token is only returned, the admin's own session cookie is left alone
+pub async fn post_impersonation(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+) -> AppResult<Json<ImpersonationResponse>> {
+    let rsp = admin_service::impersonate_account(&store, &current_account, &id, &audit).await?;
+    respond_json(rsp)
+}
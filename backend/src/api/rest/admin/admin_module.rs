@@ -0,0 +1,183 @@
+//! Admin module crate
+use actix_web::http::Method;
+use actix_web::web;
+
+use super::admin_controller::*;
+use crate::api::rest::docs::openapi::{ApiRoutes, OpenApiBuilder, Operation, API_PREFIX};
+use crate::data::dtos::account_dto::AccountResponse;
+use crate::data::dtos::activity_dto::ActivitiesResponse;
+use crate::data::dtos::api_key_dto::{
+    ApiKeysResponse, CreateApiKeyRequest, CreateServiceAccountRequest, CreatedApiKeyResponse,
+};
+use crate::data::dtos::auth_dto::{
+    SetAccountRolesRequest, SetConfirmedRequest, UpdateAccountRequest,
+};
+use crate::data::dtos::session_dto::ImpersonationResponse;
+
+/// Account management routes, every call is checked against the oso policy
+fn admin_routes() -> ApiRoutes {
+    let mut routes = ApiRoutes::new(API_PREFIX);
+    routes
+        .route(
+            Method::GET,
+            "/admin/activities",
+            |r| r.to(get_activities),
+            Operation::new(
+                "admin",
+                "List the activity log of every account, `?account_id=&page=&per_page=`",
+            )
+            .response::<ActivitiesResponse>()
+            .secured(),
+        )
+        .route(
+            Method::GET,
+            "/admin/accounts/{id}/activities",
+            |r| r.to(get_account_activities),
+            Operation::new("admin", "List the activity log of an account")
+                .response::<ActivitiesResponse>()
+                .secured(),
+        )
+        .route(
+            Method::GET,
+            "/admin/accounts/{id}",
+            |r| r.to(get_account),
+            Operation::new("admin", "Get an account")
+                .response::<AccountResponse>()
+                .secured(),
+        )
+        .route(
+            Method::DELETE,
+            "/admin/accounts/{id}",
+            |r| r.to(delete_account),
+            Operation::new(
+                "admin",
+                "Delete an account, `?hard=true` removes it permanently",
+            )
+            .status_response()
+            .secured(),
+        )
+        .route(
+            Method::PUT,
+            "/admin/accounts/{id}/confirmed",
+            |r| r.to(put_account_confirmed),
+            Operation::new("admin", "Set the confirmed status of an account")
+                .request::<SetConfirmedRequest>()
+                .status_response()
+                .secured(),
+        )
+        .route(
+            Method::PUT,
+            "/admin/accounts/{id}/restore",
+            |r| r.to(restore_account),
+            Operation::new("admin", "Restore a soft deleted account")
+                .status_response()
+                .secured(),
+        )
+        .route(
+            Method::PUT,
+            "/admin/accounts/{id}/require-new-password",
+            |r| r.to(require_new_password),
+            Operation::new("admin", "Force an account to change its password")
+                .status_response()
+                .secured(),
+        )
+        .route(
+            Method::PUT,
+            "/admin/accounts/{id}/roles",
+            |r| r.to(put_account_roles),
+            Operation::new("admin", "Replace the roles and permissions of an account")
+                .request::<SetAccountRolesRequest>()
+                .status_response()
+                .secured(),
+        )
+        .route(
+            Method::POST,
+            "/admin/service-accounts",
+            |r| r.to(post_service_account),
+            Operation::new(
+                "admin",
+                "Create a service account that authenticates with api keys",
+            )
+            .request::<CreateServiceAccountRequest>()
+            .response::<AccountResponse>()
+            .secured(),
+        )
+        .route(
+            Method::GET,
+            "/admin/accounts/{id}/api-keys",
+            |r| r.to(get_api_keys),
+            Operation::new("admin", "List the api keys of a service account")
+                .response::<ApiKeysResponse>()
+                .secured(),
+        )
+        .route(
+            Method::POST,
+            "/admin/accounts/{id}/api-keys",
+            |r| r.to(post_api_key),
+            Operation::new(
+                "admin",
+                "Create an api key of a service account, the key is only shown once",
+            )
+            .request::<CreateApiKeyRequest>()
+            .response::<CreatedApiKeyResponse>()
+            .secured(),
+        )
+        .route(
+            Method::DELETE,
+            "/admin/accounts/{id}/api-keys/{key_id}",
+            |r| r.to(delete_api_key),
+            Operation::new("admin", "Revoke an api key of a service account")
+                .status_response()
+                .secured(),
+        )
+        .route(
+            Method::POST,
+            "/admin/accounts/{id}/impersonate",
+            |r| r.to(post_impersonation),
+            Operation::new(
+                "admin",
+                "Issue a short lived token acting as an account, logging it out ends the impersonation",
+            )
+            .response::<ImpersonationResponse>()
+            .secured(),
+        );
+
+    for method in &[Method::PUT, Method::PATCH] {
+        routes
+            .route(
+                method.clone(),
+                "/admin/accounts/{id}",
+                |r| r.to(update_account),
+                Operation::new("admin", "Update an account")
+                    .request::<UpdateAccountRequest>()
+                    .response::<AccountResponse>()
+                    .secured(),
+            )
+            .route(
+                method.clone(),
+                "/admin/accounts/{id}/lock",
+                |r| r.to(lock_account),
+                Operation::new("admin", "Lock an account")
+                    .status_response()
+                    .secured(),
+            )
+            .route(
+                method.clone(),
+                "/admin/accounts/{id}/unlock",
+                |r| r.to(unlock_account),
+                Operation::new("admin", "Unlock an account")
+                    .status_response()
+                    .secured(),
+            );
+    }
+    routes
+}
+
+pub fn admin_module(cfg: &mut web::ServiceConfig) {
+    admin_routes().register(cfg);
+}
+
+/// OpenAPI description of the routes registered by `admin_module`
+pub fn admin_api_spec(spec: &mut OpenApiBuilder) {
+    admin_routes().describe(spec);
+}
@@ -0,0 +1,4 @@
+#[cfg(feature = "server-actix")]
+pub mod admin_controller;
+#[cfg(feature = "server-actix")]
+pub mod admin_module;
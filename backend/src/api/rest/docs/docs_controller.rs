@@ -0,0 +1,74 @@
+use actix_web::web::Json;
+use actix_web::HttpResponse;
+use serde_json::Value;
+
+use crate::api::rest::account::account_module::accounts_api_spec;
+use crate::api::rest::admin::admin_module::admin_api_spec;
+use crate::api::rest::docs::openapi::OpenApiBuilder;
+use crate::api::rest::health::health_controller::health_api_spec;
+use crate::api::rest::organization::organization_module::organization_api_spec;
+use crate::common::helpers::{respond_json, AppResult};
+
+/// Swagger UI page, `cargo make vendor_swagger_ui` vendors its assets in `backend/static/swagger-ui`
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8"/>
+    <title>Guardian API</title>
+    <link rel="stylesheet" href="/swagger-ui/swagger-ui.css"/>
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="/swagger-ui/swagger-ui-bundle.js"></script>
+<script>
+    window.onload = function () {
+        SwaggerUIBundle({ url: "/api/v1/openapi.json", dom_id: "#swagger-ui" });
+    };
+</script>
+</body>
+</html>"##;
+
+lazy_static! {
+    static ref OPENAPI_SPEC: Value = build_api_spec();
+}
+
+/// Describe every REST route in an OpenAPI v3 document
+pub fn build_api_spec() -> Value {
+    let mut builder = OpenApiBuilder::new();
+    health_api_spec(&mut builder);
+    accounts_api_spec(&mut builder);
+    admin_api_spec(&mut builder);
+    organization_api_spec(&mut builder);
+    builder.build()
+}
+
+/// Handler to get the OpenAPI specification of the REST api
+pub async fn get_openapi_spec() -> AppResult<Json<Value>> {
+    respond_json(OPENAPI_SPEC.clone())
+}
+
+/// Handler serving the swagger ui for the OpenAPI specification
+pub async fn get_swagger_ui() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(SWAGGER_UI)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_get_openapi_spec() {
+        let response = get_openapi_spec().await.unwrap();
+        let spec = response.into_inner();
+        assert_eq!(spec["openapi"], "3.0.3");
+        assert!(spec["paths"]["/health"]["get"].is_object());
+    }
+
+    #[test]
+    fn test_swagger_ui_loads_vendored_assets() {
+        assert!(!SWAGGER_UI.contains("https://"));
+        assert!(SWAGGER_UI.contains("/swagger-ui/swagger-ui-bundle.js"));
+    }
+}
@@ -0,0 +1,17 @@
+//! Api documentation module crate
+use actix_web::web;
+
+use super::docs_controller::*;
+use crate::config::CONFIG;
+
+pub fn docs_module(cfg: &mut web::ServiceConfig) {
+    if !CONFIG.features.api.enable_openapi {
+        return;
+    }
+
+    cfg.route("/openapi.json", web::get().to(get_openapi_spec));
+
+    if CONFIG.features.api.enable_swagger_ui {
+        cfg.route("/docs", web::get().to(get_swagger_ui));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod docs_controller;
+pub mod docs_module;
+pub mod openapi;
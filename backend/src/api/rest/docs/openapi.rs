@@ -0,0 +1,434 @@
+//! OpenAPI v3 document builder
+//!
+//! Paperclip's actix plugin only targets actix-web 3 and swagger 2.0, so the schemas are
+//! derived with `Apiv2Schema` on the dtos and each route is declared once in an `ApiRoutes`,
+//! which both registers it with actix and describes it in the document.
+use actix_web::http::Method;
+use actix_web::{web, Route};
+use paperclip::v2::schema::Apiv2Schema;
+use serde_json::{json, Map, Value};
+
+use crate::common::auth::Authorizer;
+use crate::common::errors::ErrorResponse;
+use crate::common::utils::protobuf_utils::PROTOBUF_CONTENT_TYPE;
+use crate::config::CONFIG;
+
+pub const SESSION_SECURITY_SCHEME: &str = "sessionCookie";
+/// Prefix the REST routes are mounted under
+pub const API_PREFIX: &str = "/api/v1";
+
+/// The json schema of a request or response body
+enum BodySchema {
+    Inline(Value),
+    /// A dto, its schema is added to the components of the document
+    Dto(fn(&mut OpenApiBuilder) -> Value),
+}
+
+impl BodySchema {
+    fn resolve(&self, spec: &mut OpenApiBuilder) -> Value {
+        match self {
+            BodySchema::Inline(schema) => schema.clone(),
+            BodySchema::Dto(schema) => schema(spec),
+        }
+    }
+}
+
+/// A single api operation
+pub struct Operation {
+    summary: String,
+    tag: String,
+    request_body: Option<BodySchema>,
+    query_parameters: Vec<Value>,
+    response: BodySchema,
+    secured: bool,
+    protobuf: bool,
+}
+
+impl Operation {
+    pub fn new(tag: &str, summary: &str) -> Self {
+        Operation {
+            summary: summary.to_string(),
+            tag: tag.to_string(),
+            request_body: None,
+            query_parameters: vec![],
+            response: BodySchema::Inline(json!({ "type": "object" })),
+            secured: false,
+            protobuf: false,
+        }
+    }
+
+    /// The dto of the request body
+    pub fn request<T: Apiv2Schema>(mut self) -> Self {
+        self.request_body = Some(BodySchema::Dto(OpenApiBuilder::schema::<T>));
+        self
+    }
+
+    /// A string query parameter
+    pub fn query(mut self, name: &str, required: bool, description: &str) -> Self {
+        self.query_parameters.push(json!({
+            "name": name,
+            "in": "query",
+            "required": required,
+            "description": description,
+            "schema": { "type": "string" },
+        }));
+        self
+    }
+
+    /// The dto of the successful response body
+    pub fn response<T: Apiv2Schema>(mut self) -> Self {
+        self.response = BodySchema::Dto(OpenApiBuilder::schema::<T>);
+        self
+    }
+
+    /// The successful response is the boolean outcome of the action
+    pub fn status_response(mut self) -> Self {
+        self.response = BodySchema::Inline(json!({ "type": "boolean" }));
+        self
+    }
+
+    /// Requires a logged in session
+    pub fn secured(mut self) -> Self {
+        self.secured = true;
+        self
+    }
+
+    /// The operation also accepts and returns `application/x-protobuf` bodies
+    pub fn protobuf(mut self) -> Self {
+        self.protobuf = true;
+        self
+    }
+
+    fn content(&self, schema: &Value) -> Value {
+        let mut content = Map::new();
+        content.insert("application/json".to_string(), json!({ "schema": schema }));
+        if self.protobuf {
+            content.insert(
+                PROTOBUF_CONTENT_TYPE.to_string(),
+                json!({ "schema": { "type": "string", "format": "binary" } }),
+            );
+        }
+        Value::Object(content)
+    }
+
+    fn into_value(self, path: &str, spec: &mut OpenApiBuilder) -> Value {
+        let error_schema = spec.schema::<ErrorResponse>();
+        let response = self.response.resolve(spec);
+        let request_body = self.request_body.as_ref().map(|body| body.resolve(spec));
+
+        let mut op = json!({
+            "summary": self.summary,
+            "tags": [self.tag],
+            "responses": {
+                "200": { "description": "OK", "content": self.content(&response) },
+                "default": {
+                    "description": "Error",
+                    "content": { "application/json": { "schema": error_schema } }
+                }
+            }
+        });
+
+        let mut parameters = path_parameters(path);
+        parameters.extend(self.query_parameters.iter().cloned());
+        if !parameters.is_empty() {
+            op["parameters"] = Value::Array(parameters);
+        }
+
+        if let Some(ref body) = request_body {
+            op["requestBody"] = json!({ "required": true, "content": self.content(body) });
+        }
+
+        if self.secured {
+            op["security"] = json!([{ SESSION_SECURITY_SCHEME: [] }]);
+            op["responses"]["401"] = json!({ "description": "Unauthorized" });
+        }
+        op
+    }
+}
+
+/// Collects the operations and dto schemas of the api
+#[derive(Default)]
+pub struct OpenApiBuilder {
+    paths: Map<String, Value>,
+    schemas: Map<String, Value>,
+}
+
+impl OpenApiBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the schema of a dto and return a reference to it
+    pub fn schema<T: Apiv2Schema>(&mut self) -> Value {
+        let raw = serde_json::to_value(T::raw_schema()).unwrap_or_else(|_| json!({}));
+        let name = match T::NAME {
+            Some(name) => name.to_string(),
+            None => return to_v3_schema(raw),
+        };
+
+        self.schemas.insert(name.clone(), to_v3_schema(raw));
+        json!({ "$ref": format!("#/components/schemas/{}", name) })
+    }
+
+    /// Add an operation for a http method and path
+    pub fn operation(&mut self, method: &str, path: &str, op: Operation) -> &mut Self {
+        let value = op.into_value(path, self);
+        let item = self
+            .paths
+            .entry(path.to_string())
+            .or_insert_with(|| json!({}));
+        item[method.to_lowercase()] = value;
+        self
+    }
+
+    pub fn build(self) -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": CONFIG.name,
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "paths": self.paths,
+            "components": {
+                "schemas": self.schemas,
+                "securitySchemes": {
+                    SESSION_SECURITY_SCHEME: {
+                        "type": "apiKey",
+                        "in": "cookie",
+                        "name": CONFIG.security.session_name,
+                    }
+                }
+            }
+        })
+    }
+}
+
+struct ApiRoute {
+    method: Method,
+    path: String,
+    route: Route,
+    operation: Operation,
+}
+
+/// Routes registered with actix and described in the OpenAPI document from one declaration
+///
+/// The routes of a path share a resource. Paths only serving secured operations are wrapped
+/// in the `Authorizer`, the handlers of secured operations on a shared path extract the
+/// `IdentityAccount` to reject anonymous callers.
+pub struct ApiRoutes {
+    /// Prefix of the documented paths, the app mounts the routes below it
+    prefix: String,
+    routes: Vec<ApiRoute>,
+}
+
+impl ApiRoutes {
+    pub fn new(prefix: &str) -> Self {
+        ApiRoutes {
+            prefix: prefix.to_string(),
+            routes: vec![],
+        }
+    }
+
+    /// Declare the `method` route of `path`, `handler` sets its handler, e.g. `|r| r.to(f)`
+    pub fn route<F>(&mut self, method: Method, path: &str, handler: F, op: Operation) -> &mut Self
+    where
+        F: FnOnce(Route) -> Route,
+    {
+        self.routes.push(ApiRoute {
+            route: handler(web::method(method.clone())),
+            method,
+            path: path.to_string(),
+            operation: op,
+        });
+        self
+    }
+
+    /// Add the routes to the app
+    pub fn register(self, cfg: &mut web::ServiceConfig) {
+        let mut paths: Vec<(String, bool, Vec<Route>)> = vec![];
+        for api_route in self.routes {
+            let secured = api_route.operation.secured;
+            let shared = paths
+                .iter_mut()
+                .find(|(path, _, _)| *path == api_route.path);
+            match shared {
+                Some((_, all_secured, routes)) => {
+                    *all_secured &= secured;
+                    routes.push(api_route.route);
+                }
+                None => paths.push((api_route.path, secured, vec![api_route.route])),
+            }
+        }
+
+        for (path, secured, routes) in paths {
+            let resource = routes
+                .into_iter()
+                .fold(web::resource(path.as_str()), |resource, route| {
+                    resource.route(route)
+                });
+            if secured {
+                cfg.service(resource.wrap(Authorizer));
+            } else {
+                cfg.service(resource);
+            }
+        }
+    }
+
+    /// Describe the routes in the OpenAPI document
+    pub fn describe(self, spec: &mut OpenApiBuilder) {
+        for api_route in self.routes {
+            let path = format!("{}{}", self.prefix, api_route.path);
+            spec.operation(api_route.method.as_str(), &path, api_route.operation);
+        }
+    }
+}
+
+/// Describe the `{name}` segments of a path template as string path parameters
+fn path_parameters(path: &str) -> Vec<Value> {
+    path.split('/')
+        .filter(|segment| segment.starts_with('{') && segment.ends_with('}'))
+        .map(|segment| {
+            json!({
+                "name": segment.trim_start_matches('{').trim_end_matches('}'),
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" },
+            })
+        })
+        .collect()
+}
+
+/// Rewrite the swagger 2.0 definition references into OpenAPI v3 component references
+fn to_v3_schema(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| match (key.as_str(), value) {
+                    ("$ref", Value::String(r)) => (
+                        key,
+                        Value::String(r.replace("#/definitions/", "#/components/schemas/")),
+                    ),
+                    (_, value) => (key, to_v3_schema(value)),
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(to_v3_schema).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::dtos::auth_dto::PostSessionRequest;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{App, HttpResponse};
+
+    #[test]
+    fn it_rewrites_definition_references() {
+        let schema = to_v3_schema(json!({ "items": { "$ref": "#/definitions/MobileInput" } }));
+        assert_eq!(
+            schema,
+            json!({ "items": { "$ref": "#/components/schemas/MobileInput" } })
+        );
+    }
+
+    #[test]
+    fn it_describes_path_parameters() {
+        let parameters = path_parameters("/api/v1/admin/accounts/{id}/lock");
+        assert_eq!(parameters.len(), 1);
+        assert_eq!(parameters[0]["name"], "id");
+        assert_eq!(path_parameters("/api/v1/session").len(), 0);
+    }
+
+    #[test]
+    fn it_describes_query_parameters() {
+        let op = Operation::new("accounts", "Check")
+            .query("identity", true, "")
+            .query("signup_token", false, "");
+        let value = op.into_value("/api/v1/accounts/available", &mut OpenApiBuilder::new());
+
+        assert_eq!(value["parameters"][0]["in"], "query");
+        assert_eq!(value["parameters"][0]["required"], true);
+        assert_eq!(value["parameters"][1]["name"], "signup_token");
+        assert!(value["requestBody"].is_null());
+    }
+
+    fn get_routes() -> ApiRoutes {
+        let mut routes = ApiRoutes::new(API_PREFIX);
+        routes
+            .route(
+                Method::POST,
+                "/things",
+                |r| r.to(HttpResponse::Ok),
+                Operation::new("things", "Create"),
+            )
+            .route(
+                Method::DELETE,
+                "/things",
+                |r| r.to(HttpResponse::Ok),
+                Operation::new("things", "Delete").secured(),
+            )
+            .route(
+                Method::GET,
+                "/things/{id}",
+                |r| r.to(HttpResponse::Ok),
+                Operation::new("things", "Get").secured(),
+            );
+        routes
+    }
+
+    #[test]
+    fn it_describes_the_declared_routes() {
+        let mut builder = OpenApiBuilder::new();
+        get_routes().describe(&mut builder);
+        let doc = builder.build();
+
+        let things = &doc["paths"]["/api/v1/things"];
+        assert!(things["post"]["security"].is_null());
+        assert!(things["delete"]["security"].is_array());
+        assert!(doc["paths"]["/api/v1/things/{id}"]["get"].is_object());
+    }
+
+    #[actix_rt::test]
+    async fn it_registers_the_declared_routes() {
+        let srv = test::init_service(
+            App::new().service(web::scope(API_PREFIX).configure(|cfg| get_routes().register(cfg))),
+        )
+        .await;
+        let status = |method: Method, uri: &'static str| {
+            let req = TestRequest::with_uri(uri).method(method).to_request();
+            let srv = &srv;
+            async move { test::call_service(srv, req).await.status() }
+        };
+
+        assert_eq!(status(Method::POST, "/api/v1/things").await, StatusCode::OK);
+        assert_eq!(
+            status(Method::PUT, "/api/v1/things").await,
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+        assert_eq!(
+            status(Method::GET, "/api/v1/things/1").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[test]
+    fn it_builds_a_document_from_the_dtos() {
+        let mut builder = OpenApiBuilder::new();
+        builder.operation(
+            "POST",
+            "/api/v1/session",
+            Operation::new("session", "Login").request::<PostSessionRequest>(),
+        );
+        let doc = builder.build();
+
+        assert_eq!(doc["openapi"], "3.0.3");
+        assert!(doc["paths"]["/api/v1/session"]["post"].is_object());
+        assert!(
+            doc["components"]["schemas"]["PostSessionRequest"]["properties"]["identity"]
+                .is_object()
+        );
+    }
+}
@@ -1,7 +1,9 @@
+use crate::api::rest::docs::openapi::{ApiRoutes, OpenApiBuilder, Operation};
 use crate::common::errors::ApiError;
 use crate::common::helpers::respond_json;
 use crate::data::models::health_model::HealthResponse;
-use actix_web::web::Json;
+use actix_web::http::Method;
+use actix_web::web::{self, Json};
 
 /// Handler to get the liveness of the service
 pub async fn get_health() -> Result<Json<HealthResponse>, ApiError> {
@@ -12,6 +14,27 @@ pub async fn get_health() -> Result<Json<HealthResponse>, ApiError> {
     })
 }
 
+/// The health routes, mounted at the root of the app
+fn health_routes() -> ApiRoutes {
+    let mut routes = ApiRoutes::new("");
+    routes.route(
+        Method::GET,
+        "/health",
+        |r| r.to(get_health),
+        Operation::new("health", "Liveness of the service").response::<HealthResponse>(),
+    );
+    routes
+}
+
+pub fn health_module(cfg: &mut web::ServiceConfig) {
+    health_routes().register(cfg);
+}
+
+/// OpenAPI description of the health routes
+pub fn health_api_spec(spec: &mut OpenApiBuilder) {
+    health_routes().describe(spec);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,2 +1,5 @@
 pub mod account;
+pub mod admin;
+pub mod docs;
 pub mod health;
+pub mod organization;
@@ -0,0 +1,4 @@
+#[cfg(feature = "server-actix")]
+pub mod organization_controller;
+#[cfg(feature = "server-actix")]
+pub mod organization_module;
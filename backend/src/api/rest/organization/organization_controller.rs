@@ -0,0 +1,165 @@
+use actix_guardian_identity::Identity;
+use actix_web::web::{Json, Path};
+
+use crate::api::services::organization_service;
+use crate::common::audit::AuditContext;
+use crate::common::auth::account::IdentityAccount;
+use crate::common::helpers::{respond_json, AppResult};
+use crate::common::tenant::TenantStore;
+use crate::common::validate::validate;
+use crate::data::dtos::organization_dto::*;
+
+/// Handler for creating an organization owned by the logged in account
+pub async fn post_organization(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    body: Json<CreateOrganizationRequest>,
+) -> AppResult<Json<OrganizationResponse>> {
+    validate(&body)?;
+
+    let rsp =
+        organization_service::create_organization(&store, &current_account, &body, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for listing the organizations of the logged in account
+pub async fn get_organizations(
+    store: TenantStore,
+    current_account: IdentityAccount,
+) -> AppResult<Json<OrganizationsResponse>> {
+    let rsp = organization_service::list_organizations(&store, &current_account).await?;
+    respond_json(rsp)
+}
+
+/// Handler for getting an organization of the logged in account
+pub async fn get_organization(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    id: Path<String>,
+) -> AppResult<Json<OrganizationResponse>> {
+    let rsp = organization_service::get_organization(&store, &current_account, &id).await?;
+    respond_json(rsp)
+}
+
+/// Handler for switching the session to act in an organization
+///
+/// The reissued token carrying the organization replaces the one in the session cookie
+pub async fn activate_organization(
+    store: TenantStore,
+    identity: Identity,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+) -> AppResult<Json<ActiveOrganizationResponse>> {
+    let rsp =
+        organization_service::activate_organization(&store, &current_account, &id, &audit).await?;
+    identity.remember(rsp.id_token.clone());
+    respond_json(rsp)
+}
+
+/// Handler for listing the members of an organization
+pub async fn get_members(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    id: Path<String>,
+) -> AppResult<Json<MembersResponse>> {
+    let rsp = organization_service::list_members(&store, &current_account, &id).await?;
+    respond_json(rsp)
+}
+
+/// Handler for changing the role of a member
+pub async fn put_member(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    path: Path<(String, String)>,
+    body: Json<UpdateMemberRequest>,
+) -> AppResult<Json<MemberResponse>> {
+    validate(&body)?;
+
+    let (id, account_id) = path.into_inner();
+    let rsp = organization_service::set_member_role(
+        &store,
+        &current_account,
+        &id,
+        &account_id,
+        &body,
+        &audit,
+    )
+    .await?;
+    respond_json(rsp)
+}
+
+/// Handler for removing a member, members can remove themselves to leave
+pub async fn delete_member(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    path: Path<(String, String)>,
+) -> AppResult<Json<bool>> {
+    let (id, account_id) = path.into_inner();
+    let rsp =
+        organization_service::remove_member(&store, &current_account, &id, &account_id, &audit)
+            .await?;
+    respond_json(rsp)
+}
+
+/// Handler for inviting an email to an organization
+pub async fn post_invitation(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    id: Path<String>,
+    body: Json<CreateInvitationRequest>,
+) -> AppResult<Json<InvitationResponse>> {
+    validate(&body)?;
+
+    let rsp =
+        organization_service::invite_member(&store, &current_account, &id, &body, &audit).await?;
+    respond_json(rsp)
+}
+
+/// Handler for listing the open invitations of an organization
+pub async fn get_invitations(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    id: Path<String>,
+) -> AppResult<Json<InvitationsResponse>> {
+    let rsp = organization_service::list_invitations(&store, &current_account, &id).await?;
+    respond_json(rsp)
+}
+
+/// Handler for revoking an open invitation
+pub async fn delete_invitation(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    path: Path<(String, String)>,
+) -> AppResult<Json<bool>> {
+    let (id, invitation_id) = path.into_inner();
+    let rsp = organization_service::revoke_invitation(
+        &store,
+        &current_account,
+        &id,
+        &invitation_id,
+        &audit,
+    )
+    .await?;
+    respond_json(rsp)
+}
+
+/// Handler for the logged in account accepting an invitation
+pub async fn accept_invitation(
+    store: TenantStore,
+    current_account: IdentityAccount,
+    audit: AuditContext,
+    body: Json<AcceptInvitationRequest>,
+) -> AppResult<Json<OrganizationResponse>> {
+    validate(&body)?;
+
+    let rsp =
+        organization_service::accept_invitation_token(&store, &current_account, &body, &audit)
+            .await?;
+    respond_json(rsp)
+}
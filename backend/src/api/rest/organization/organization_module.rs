@@ -0,0 +1,139 @@
+//! Organization module crate
+use actix_web::http::Method;
+use actix_web::web;
+
+use super::organization_controller::*;
+use crate::api::rest::docs::openapi::{ApiRoutes, OpenApiBuilder, Operation, API_PREFIX};
+use crate::data::dtos::organization_dto::*;
+
+/// Organization routes of the logged in account, roles are checked per organization
+fn organization_routes() -> ApiRoutes {
+    let mut routes = ApiRoutes::new(API_PREFIX);
+    routes
+        .route(
+            Method::GET,
+            "/organizations",
+            |r| r.to(get_organizations),
+            Operation::new(
+                "organizations",
+                "List the organizations of the logged in account",
+            )
+            .response::<OrganizationsResponse>()
+            .secured(),
+        )
+        .route(
+            Method::POST,
+            "/organizations",
+            |r| r.to(post_organization),
+            Operation::new(
+                "organizations",
+                "Create an organization owned by the logged in account",
+            )
+            .request::<CreateOrganizationRequest>()
+            .response::<OrganizationResponse>()
+            .secured(),
+        )
+        .route(
+            Method::POST,
+            "/organizations/invitations/accept",
+            |r| r.to(accept_invitation),
+            Operation::new(
+                "organizations",
+                "Join an organization with the token of an emailed invitation",
+            )
+            .request::<AcceptInvitationRequest>()
+            .response::<OrganizationResponse>()
+            .secured(),
+        )
+        .route(
+            Method::GET,
+            "/organizations/{id}",
+            |r| r.to(get_organization),
+            Operation::new("organizations", "Get an organization")
+                .response::<OrganizationResponse>()
+                .secured(),
+        )
+        .route(
+            Method::PUT,
+            "/organizations/{id}/activate",
+            |r| r.to(activate_organization),
+            Operation::new(
+                "organizations",
+                "Make the organization the one the session acts in and reissue its token",
+            )
+            .response::<ActiveOrganizationResponse>()
+            .secured(),
+        )
+        .route(
+            Method::GET,
+            "/organizations/{id}/members",
+            |r| r.to(get_members),
+            Operation::new("organizations", "List the members of an organization")
+                .response::<MembersResponse>()
+                .secured(),
+        )
+        .route(
+            Method::PUT,
+            "/organizations/{id}/members/{account_id}",
+            |r| r.to(put_member),
+            Operation::new(
+                "organizations",
+                "Change the role of a member, only owners manage owners",
+            )
+            .request::<UpdateMemberRequest>()
+            .response::<MemberResponse>()
+            .secured(),
+        )
+        .route(
+            Method::DELETE,
+            "/organizations/{id}/members/{account_id}",
+            |r| r.to(delete_member),
+            Operation::new(
+                "organizations",
+                "Remove a member, members can remove themselves to leave",
+            )
+            .status_response()
+            .secured(),
+        )
+        .route(
+            Method::GET,
+            "/organizations/{id}/invitations",
+            |r| r.to(get_invitations),
+            Operation::new(
+                "organizations",
+                "List the open invitations of an organization",
+            )
+            .response::<InvitationsResponse>()
+            .secured(),
+        )
+        .route(
+            Method::POST,
+            "/organizations/{id}/invitations",
+            |r| r.to(post_invitation),
+            Operation::new(
+                "organizations",
+                "Email an invitation to join an organization",
+            )
+            .request::<CreateInvitationRequest>()
+            .response::<InvitationResponse>()
+            .secured(),
+        )
+        .route(
+            Method::DELETE,
+            "/organizations/{id}/invitations/{invitation_id}",
+            |r| r.to(delete_invitation),
+            Operation::new("organizations", "Revoke an open invitation")
+                .status_response()
+                .secured(),
+        );
+    routes
+}
+
+pub fn organization_module(cfg: &mut web::ServiceConfig) {
+    organization_routes().register(cfg);
+}
+
+/// OpenAPI description of the routes registered by `organization_module`
+pub fn organization_api_spec(spec: &mut OpenApiBuilder) {
+    organization_routes().describe(spec);
+}
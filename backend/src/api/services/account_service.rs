@@ -1,17 +1,142 @@
-use actix_guardian_identity::Identity;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use slog::warn;
 
+use crate::api::services::activity_service::record_activity;
+use crate::api::services::organization_service::{accept_invitation, pending_invitation};
+use crate::api::services::profile_service::validate_profile;
+use crate::api::services::session_service::session_token;
+use crate::common::audit::AuditContext;
 use crate::common::auth::account::IdentityAccount;
-use crate::common::auth::utils::hash;
+use crate::common::auth::challenge::check_challenge;
+use crate::common::auth::password_policy::{check_not_breached, check_password};
+use crate::common::auth::utils::{
+    create_jwt, decode_scoped_jwt, hash, secrets_match, verify_password, PrivateClaim, SIGNUP_SCOPE,
+};
+use crate::common::email::{send_email_with, EMAIL_INTEGRATION};
 use crate::common::errors::ApiError;
 use crate::common::helpers::AppResult;
-use crate::data::dtos::account_dto::AccountResponse;
+use crate::common::sms::{send_sms_with, SMS_INTEGRATION};
+use crate::common::tenant::tenant_features;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::config::CONFIG;
+use crate::data::dtos::account_dto::{
+    AccountResponse, AccountUpdateResponse, PostSessionResponse, SignupTokenResponse,
+};
 use crate::data::dtos::auth_dto::*;
-use crate::data::stores::base_store_trait::{BoxedStoreType, CreateAccountCommand};
+use crate::data::models::account_activity_model::ActivityKind;
+use crate::data::models::accounts_model::AccountModel;
+use crate::data::stores::base_store_trait::{
+    BoxedStoreType, CreateAccountCommand, UpdateAccountCommand,
+};
+use crate::events::{publish_event, AccountEventKind};
+use crate::integrations::email::email_integration::EmailIntegration;
+use crate::integrations::sms::sms_integration::SmsIntegration;
+
+/// Hours an email confirmation token stays valid
+const EMAIL_CONFIRMATION_HOURS: i64 = 24;
+/// Wrong codes a pending mobile change takes before it is dropped
+const MAX_MOBILE_CODE_ATTEMPTS: i64 = 5;
+
+/// Email the confirmation `token` of a new address to `to`
+async fn send_email_confirmation(
+    integration: Option<&dyn EmailIntegration>,
+    to: &str,
+    token: &str,
+) -> AppResult<()> {
+    let url = &CONFIG.features.auth.email_confirmation_url;
+    let link = if url.is_empty() {
+        token.to_string()
+    } else {
+        url.replace("{token}", token)
+    };
+    let body = format!(
+        "Confirm your new email within {} hours: {}\n",
+        EMAIL_CONFIRMATION_HOURS, link
+    );
+    send_email_with(integration, to, "Confirm your new email", &body).await
+}
+
+/// Text the one time `code` of a new mobile to `to`
+async fn send_mobile_code(
+    integration: Option<&dyn SmsIntegration>,
+    to: &str,
+    code: &str,
+) -> AppResult<()> {
+    let body = format!("Your verification code is {}", code);
+    send_sms_with(integration, to, &body).await
+}
+
+/// The claims of an id token of the session `session_id` in `tenant` matching `account`
+///
+/// `organization` is the organization the session acts in, if any
+pub fn session_claim(
+    account: &AccountResponse,
+    session_id: uuid::Uuid,
+    tenant: &str,
+    organization: Option<&str>,
+) -> PrivateClaim {
+    PrivateClaim::new(
+        account.id.clone(),
+        account.email.clone(),
+        account.username.clone(),
+        account.mobile.clone(),
+    )
+    .for_session(session_id)
+    .for_tenant(tenant)
+    .for_organization(organization)
+}
+
+/// Create an id token of the session `session_id` in `tenant` whose claims match `account`
+pub fn issue_token(
+    account: &AccountResponse,
+    session_id: uuid::Uuid,
+    tenant: &str,
+    organization: Option<&str>,
+) -> AppResult<String> {
+    create_jwt(session_claim(account, session_id, tenant, organization))
+}
+
+/// Fail when `found` is an account other than `id`
+fn ensure_unused(found: AppResult<AccountModel>, id: &str, field: &str) -> AppResult<()> {
+    match found {
+        Ok(account) if account.id != id => {
+            Err(ApiError::Conflict(format!("{} is already taken", field)))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn invalid_code() -> ApiError {
+    ApiError::BadRequest("the code is invalid or has expired".to_string())
+}
+
+/// The updated account with a fresh token of the caller's session and its pending changes
+async fn account_update_response(
+    store: &BoxedStoreType,
+    id: &str,
+    audit: &AuditContext,
+) -> AppResult<AccountUpdateResponse> {
+    let model = store.account_find_by_id(id).await?;
+    let email_confirmation_required = model.unconfirmed_email.is_some();
+    let mobile_verification_required = model.unconfirmed_mobile.is_some();
+
+    let account: AccountResponse = model.into();
+    let id_token = Some(session_token(store, &account, audit).await?);
+
+    Ok(AccountUpdateResponse {
+        account,
+        id_token,
+        email_confirmation_required,
+        mobile_verification_required,
+    })
+}
 
 /// The service handles creating a new account
 pub async fn create_account(
     store: &BoxedStoreType,
     cmd: &PostAccountRequest,
+    audit: &AuditContext,
 ) -> AppResult<AccountResponse> {
     let mut mobile: Option<String> = None;
     match &cmd.mobile {
@@ -27,15 +152,61 @@ pub async fn create_account(
         return Err(ApiError::ValidationError(err_messages));
     }
 
+    let features = tenant_features(&CONFIG, store.tenant());
+    if !features.enable_signup {
+        return Err(ApiError::Forbidden(
+            "signup is disabled for this tenant".to_string(),
+        ));
+    }
+
+    if CONFIG.features.challenge.enable_signup {
+        check_challenge(cmd.challenge_token.as_deref(), audit.ip.as_deref()).await?;
+    }
+
+    check_password(
+        &cmd.password,
+        &[cmd.username.as_deref(), cmd.email.as_deref()],
+        features.password_strength,
+    )?;
+    check_not_breached(&cmd.password).await?;
+
+    let profile_fields = cmd.profile.as_deref().unwrap_or_default();
+    let profile = validate_profile(store, profile_fields, None, false).await?;
+    let invitation = match &cmd.invitation_token {
+        Some(token) => Some(pending_invitation(store, token, cmd.email.as_deref()).await?),
+        None => None,
+    };
+
     let new_account = CreateAccountCommand {
         username: cmd.username.clone(),
         mobile,
         email: cmd.email.clone(),
         password: hash(cmd.password.as_str()),
+        service_account: false,
     };
 
     let account = store.account_create(new_account).await?;
+    if !profile.is_empty() {
+        store
+            .profile_upsert(&account.id, profile, &account.id)
+            .await?;
+    }
     let _ = store.onetime_code_create(account.id.as_str()).await;
+    publish_event(store.tenant(), AccountEventKind::Created, &account.id);
+    let audit = audit.with_actor(&account.id);
+    record_activity(store, &audit, ActivityKind::Signup, Some(&account.id), None).await;
+    // the account exists now, an invitation that can't be accepted doesn't undo the signup
+    if let Some(invitation) = &invitation {
+        if let Err(e) = accept_invitation(store, invitation, &account.id, &audit).await {
+            warn!(
+                LOGGER,
+                "[organizations] signup of {} could not accept invitation {}: {}",
+                account.id,
+                invitation.id,
+                e
+            );
+        }
+    }
 
     Ok(account.into())
 }
@@ -52,27 +223,391 @@ pub async fn find_account(
     Ok(account.into())
 }
 
+fn signup_token_required() -> ApiError {
+    ApiError::Forbidden("a valid signup token is required".to_string())
+}
+
+/// The service handles checking whether an identity has an account
+///
+/// With enumeration protection on the caller must hold a signup token, each token allows
+/// `signup_token_checks` checks
+pub async fn check_availability(
+    store: &BoxedStoreType,
+    cmd: &IdentifierRequest,
+) -> AppResult<bool> {
+    let protection = &CONFIG.security.enumeration_protection;
+    if protection.enabled {
+        let token = cmd.signup_token.as_deref().unwrap_or_default();
+        let claim = decode_scoped_jwt(token, SIGNUP_SCOPE).map_err(|_| signup_token_required())?;
+        let uses = store
+            .signup_token_use(&claim.sub, claim.expires_at())
+            .await?;
+        if uses > protection.signup_token_checks {
+            return Err(signup_token_required());
+        }
+    }
+
+    Ok(find_account(store, cmd).await.is_ok())
+}
+
+/// The service handles issuing the signup token the availability check asks for
+///
+/// The caller must pass the configured challenge, so tokens can't be minted by a script
+pub async fn issue_signup_token(
+    cmd: &SignupTokenRequest,
+    audit: &AuditContext,
+) -> AppResult<SignupTokenResponse> {
+    check_challenge(cmd.challenge_token.as_deref(), audit.ip.as_deref()).await?;
+
+    let minutes = CONFIG.security.enumeration_protection.signup_token_minutes;
+    let claim = PrivateClaim::scoped(uuid::Uuid::new_v4().to_string(), SIGNUP_SCOPE, minutes);
+    Ok(SignupTokenResponse {
+        signup_token: create_jwt(claim)?,
+    })
+}
+
+/// The service handles getting an account by id
+pub async fn get_account(store: &BoxedStoreType, id: &str) -> AppResult<AccountResponse> {
+    let account = store.account_find_by_id(id).await?;
+    Ok(account.into())
+}
+
+/// The service handles updating an account's email, username or mobile
+pub async fn update_account(
+    store: &BoxedStoreType,
+    id: &str,
+    cmd: &UpdateAccountRequest,
+    audit: &AuditContext,
+) -> AppResult<AccountResponse> {
+    let mobile = cmd
+        .mobile
+        .as_ref()
+        .map(|m| format!("{}-{}", m.prefix, m.digit));
+
+    let update = UpdateAccountCommand {
+        email: cmd.email.clone(),
+        username: cmd.username.clone(),
+        mobile,
+        updated_by: Some(audit.actor_or(id).to_string()),
+    };
+
+    let account = store.account_update(id, update).await?;
+    let changes = serde_json::to_value(cmd).ok();
+    record_activity(
+        store,
+        audit,
+        ActivityKind::AccountUpdated,
+        Some(id),
+        changes,
+    )
+    .await;
+    Ok(account.into())
+}
+
+/// The service handles an account updating its own username, email or mobile
+///
+/// A new username applies at once, a new email waits for the confirmation token sent to it
+/// and a new mobile waits for the one time code sent to it
+pub async fn update_current_account(
+    store: &BoxedStoreType,
+    id: &str,
+    cmd: &UpdateAccountRequest,
+    audit: &AuditContext,
+) -> AppResult<AccountUpdateResponse> {
+    audit.ensure_not_impersonated()?;
+    let account = store.account_find_by_id(id).await?;
+    let mobile = cmd
+        .mobile
+        .as_ref()
+        .map(|m| format!("{}-{}", m.prefix, m.digit));
+
+    // unchanged values are ignored
+    let username = cmd
+        .username
+        .clone()
+        .filter(|u| Some(u) != account.username.as_ref());
+    let email = cmd
+        .email
+        .clone()
+        .filter(|e| Some(e) != account.email.as_ref());
+    let mobile = mobile.filter(|m| Some(m) != account.mobile.as_ref());
+
+    if let Some(u) = &username {
+        ensure_unused(store.account_find_by_username(u).await, id, "username")?;
+    }
+    if let Some(e) = &email {
+        ensure_unused(store.account_find_by_email(e).await, id, "email")?;
+    }
+    if let Some(m) = &mobile {
+        ensure_unused(store.account_find_by_mobile(m).await, id, "mobile")?;
+    }
+
+    let updated_by = audit.actor_or(id);
+
+    if let Some(u) = username {
+        let update = UpdateAccountCommand {
+            email: None,
+            username: Some(u.clone()),
+            mobile: None,
+            updated_by: Some(updated_by.to_string()),
+        };
+        store.account_update(id, update).await?;
+        publish_event(store.tenant(), AccountEventKind::Updated, id);
+        let changes = Some(json!({ "username": u }));
+        record_activity(
+            store,
+            audit,
+            ActivityKind::AccountUpdated,
+            Some(id),
+            changes,
+        )
+        .await;
+    }
+
+    if let Some(e) = email {
+        let token = uuid::Uuid::new_v4().to_string();
+        store
+            .account_set_unconfirmed_email(id, Some(&e), Some(&token), updated_by)
+            .await?;
+        // a change whose token can't be delivered could never be confirmed
+        if let Err(err) = send_email_confirmation(EMAIL_INTEGRATION.as_deref(), &e, &token).await {
+            let _ = store
+                .account_set_unconfirmed_email(id, None, None, updated_by)
+                .await;
+            return Err(err);
+        }
+        publish_event(store.tenant(), AccountEventKind::EmailChangeRequested, id);
+        let kind = ActivityKind::EmailChangeRequested;
+        record_activity(store, audit, kind, Some(id), None).await;
+    }
+
+    if let Some(m) = mobile {
+        store
+            .account_set_unconfirmed_mobile(id, Some(&m), updated_by)
+            .await?;
+        let otp = store.onetime_code_create(id).await?;
+        if let Err(err) = send_mobile_code(SMS_INTEGRATION.as_deref(), &m, &otp.code).await {
+            let _ = store
+                .account_set_unconfirmed_mobile(id, None, updated_by)
+                .await;
+            return Err(err);
+        }
+        publish_event(store.tenant(), AccountEventKind::MobileChangeRequested, id);
+        let kind = ActivityKind::MobileChangeRequested;
+        record_activity(store, audit, kind, Some(id), None).await;
+    }
+
+    account_update_response(store, id, audit).await
+}
+
+/// The service handles confirming a pending email change with the token sent to it
+pub async fn confirm_email(
+    store: &BoxedStoreType,
+    id: &str,
+    cmd: &ConfirmChangeRequest,
+    audit: &AuditContext,
+) -> AppResult<AccountUpdateResponse> {
+    audit.ensure_not_impersonated()?;
+    let account = store.account_find_by_id(id).await?;
+    let email = match (account.unconfirmed_email, account.confirmation_token) {
+        (Some(email), Some(token)) if secrets_match(&token, &cmd.code) => email,
+        _ => return Err(invalid_code()),
+    };
+
+    let expires_at = account
+        .confirmation_sent_at
+        .map(|sent| sent + Duration::hours(EMAIL_CONFIRMATION_HOURS));
+    if expires_at.map_or(true, |at| at < Utc::now()) {
+        return Err(invalid_code());
+    }
+
+    ensure_unused(store.account_find_by_email(&email).await, id, "email")?;
+
+    let updated_by = audit.actor_or(id);
+    let update = UpdateAccountCommand {
+        email: Some(email),
+        username: None,
+        mobile: None,
+        updated_by: Some(updated_by.to_string()),
+    };
+    store.account_update(id, update).await?;
+    store
+        .account_set_unconfirmed_email(id, None, None, updated_by)
+        .await?;
+
+    publish_event(store.tenant(), AccountEventKind::Updated, id);
+    record_activity(store, audit, ActivityKind::EmailChanged, Some(id), None).await;
+    account_update_response(store, id, audit).await
+}
+
+/// The service handles verifying a pending mobile change with the one time code sent to it
+pub async fn verify_mobile(
+    store: &BoxedStoreType,
+    id: &str,
+    cmd: &ConfirmChangeRequest,
+    audit: &AuditContext,
+) -> AppResult<AccountUpdateResponse> {
+    audit.ensure_not_impersonated()?;
+    let account = store.account_find_by_id(id).await?;
+    let mobile = account.unconfirmed_mobile.ok_or_else(invalid_code)?;
+
+    let otp = store
+        .onetime_code_find_by_account(id, None)
+        .await
+        .map_err(|_| invalid_code())?;
+    let expired = otp
+        .expire_at
+        .map_or(false, |at| at < Utc::now().naive_utc());
+    if expired || !secrets_match(&otp.code, &cmd.code) {
+        let kind = ActivityKind::MobileVerificationFailed;
+        record_activity(store, audit, kind, Some(id), None).await;
+
+        // counted on the code itself, a store that can't count fails the request
+        if store.onetime_code_fail(id).await? >= MAX_MOBILE_CODE_ATTEMPTS {
+            store.onetime_code_delete(id).await?;
+            store
+                .account_set_unconfirmed_mobile(id, None, audit.actor_or(id))
+                .await?;
+        }
+        return Err(invalid_code());
+    }
+
+    ensure_unused(store.account_find_by_mobile(&mobile).await, id, "mobile")?;
+
+    let updated_by = audit.actor_or(id);
+    let update = UpdateAccountCommand {
+        email: None,
+        username: None,
+        mobile: Some(mobile),
+        updated_by: Some(updated_by.to_string()),
+    };
+    store.account_update(id, update).await?;
+    store
+        .account_set_unconfirmed_mobile(id, None, updated_by)
+        .await?;
+    store.onetime_code_delete(id).await?;
+
+    publish_event(store.tenant(), AccountEventKind::Updated, id);
+    record_activity(store, audit, ActivityKind::MobileChanged, Some(id), None).await;
+    account_update_response(store, id, audit).await
+}
+
+/// Whether `password` is among the `count` most recent password hashes, `current` included
+fn is_recent_password(current: &str, history: &[String], password: &str, count: usize) -> bool {
+    std::iter::once(current)
+        .chain(history.iter().map(String::as_str))
+        .take(count)
+        .any(|p| p == password)
+}
+
+/// The service handles an account changing its own password
+///
+/// Every token issued before the change is revoked, the returned token replaces them
+pub async fn change_password(
+    store: &BoxedStoreType,
+    id: &str,
+    cmd: &ChangePasswordRequest,
+    audit: &AuditContext,
+) -> AppResult<PostSessionResponse> {
+    audit.ensure_not_impersonated()?;
+    let account = store.account_find_by_id(id).await?;
+    if !verify_password(&cmd.current_password, &account.password) {
+        let kind = ActivityKind::PasswordChangeFailed;
+        record_activity(store, audit, kind, Some(id), None).await;
+        return Err(ApiError::Unauthorized(
+            "your current password is incorrect".to_string(),
+        ));
+    }
+
+    replace_password(store, &account, &cmd.password, audit).await
+}
+
+/// Replace the password of `account` once it passes the policy and the history check
+pub async fn replace_password(
+    store: &BoxedStoreType,
+    account: &AccountModel,
+    password: &str,
+    audit: &AuditContext,
+) -> AppResult<PostSessionResponse> {
+    let id = account.id.as_str();
+    check_password(
+        password,
+        &[account.username.as_deref(), account.email.as_deref()],
+        tenant_features(&CONFIG, store.tenant()).password_strength,
+    )?;
+    check_not_breached(password).await?;
+
+    // the current password never counts as a new one
+    let history = CONFIG.security.password_history.max(1);
+    let password = hash(password);
+    if is_recent_password(
+        &account.password,
+        &account.password_history,
+        &password,
+        history,
+    ) {
+        let err = format!("password must differ from your last {} passwords", history);
+        return Err(ApiError::ValidationError(vec![err]));
+    }
+
+    store
+        .account_set_password(id, &password, history - 1, audit.actor_or(id))
+        .await?;
+    publish_event(store.tenant(), AccountEventKind::PasswordChanged, id);
+    record_activity(store, audit, ActivityKind::PasswordChanged, Some(id), None).await;
+
+    // the other sessions lost their tokens with the password, end their records too
+    store
+        .session_revoke(id, None, audit.session_id.as_deref())
+        .await?;
+
+    let account: AccountResponse = store.account_find_by_id(id).await?.into();
+    Ok(PostSessionResponse::new(
+        session_token(store, &account, audit).await?,
+    ))
+}
+
 /// The service handles deleting accounts
 pub async fn delete_account(
     store: &BoxedStoreType,
-    identity: &Identity,
     current_account: IdentityAccount,
+    audit: &AuditContext,
 ) -> AppResult<bool> {
+    audit.ensure_not_impersonated()?;
     let id = current_account.id;
-    let rsp = store.account_delete(id.as_str(), false).await?;
-    identity.forget();
+    let rsp = store
+        .account_delete(id.as_str(), false, id.as_str())
+        .await?;
+    publish_event(store.tenant(), AccountEventKind::Deleted, &id);
+    record_activity(store, audit, ActivityKind::AccountDeleted, Some(&id), None).await;
     Ok(rsp)
 }
 
 /// The service handles locking accounts
-pub async fn lock_account(store: &BoxedStoreType, id: String) -> AppResult<bool> {
-    let rsp = store.account_lock(id.as_str()).await?;
+pub async fn lock_account(
+    store: &BoxedStoreType,
+    id: String,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    audit.ensure_not_impersonated()?;
+    let rsp = store.account_lock(id.as_str(), audit.actor_or(&id)).await?;
+    publish_event(store.tenant(), AccountEventKind::Locked, &id);
+    record_activity(store, audit, ActivityKind::AccountLocked, Some(&id), None).await;
     Ok(rsp)
 }
 
 /// The service handles unlocking accounts
-pub async fn unlock_account(store: &BoxedStoreType, id: String) -> AppResult<bool> {
-    let rsp = store.account_unlock(id.as_str()).await?;
+pub async fn unlock_account(
+    store: &BoxedStoreType,
+    id: String,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    audit.ensure_not_impersonated()?;
+    let rsp = store
+        .account_unlock(id.as_str(), audit.actor_or(&id))
+        .await?;
+    publish_event(store.tenant(), AccountEventKind::Unlocked, &id);
+    record_activity(store, audit, ActivityKind::AccountUnlocked, Some(&id), None).await;
     Ok(rsp)
 }
 
@@ -80,15 +615,43 @@ pub async fn unlock_account(store: &BoxedStoreType, id: String) -> AppResult<boo
 mod test {
     use std::future::Future;
     use std::pin::Pin;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
 
     use crate::common::tests::helpers::tests::get_store;
     use crate::data::dtos::account_dto::AccountResponse;
     use crate::data::dtos::auth_dto::*;
+    use crate::integrations::email::email_integration::MessageOption;
 
     use super::*;
 
+    /// Keeps the recipient and body of every message instead of sending it
+    #[derive(Default)]
+    struct Outbox(Mutex<Vec<(String, String)>>);
+
+    #[async_trait]
+    impl EmailIntegration for Outbox {
+        async fn send_message(&self, opt: MessageOption) -> AppResult<()> {
+            let body = opt.content.unwrap_or_default();
+            self.0.lock().unwrap().push((opt.to, body));
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl SmsIntegration for Outbox {
+        async fn send_sms(&self, to: &str, body: &str) -> AppResult<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((to.to_string(), body.to_string()));
+            Ok(())
+        }
+    }
+
     pub async fn create_account_test(cmd: PostAccountRequest) -> AppResult<AccountResponse> {
-        create_account(&get_store(), &cmd).await
+        create_account(&get_store(), &cmd, &AuditContext::default()).await
     }
 
     async fn seed_account_test() {
@@ -96,10 +659,13 @@ mod test {
 
         let test_users: Vec<PostAccountRequest> = vec![PostAccountRequest {
             email: Some("user_a@example.com".to_string()),
-            password: "password".to_string(),
+            password: "@43874hfdfweak_password".to_string(),
             confirm_password: "".to_string(),
             username: Some("user_a".parse().unwrap()),
             mobile: None,
+            profile: None,
+            challenge_token: None,
+            invitation_token: None,
         }];
 
         for ts in test_users.iter() {
@@ -108,4 +674,65 @@ mod test {
 
         let _ = futures::future::join_all(futures).await;
     }
+
+    #[test]
+    fn it_issues_tokens_matching_the_account() {
+        let account = AccountResponse {
+            id: "1234".to_string(),
+            username: Some("user_b".to_string()),
+            mobile: None,
+            email: Some("user_b@example.com".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let session_id = uuid::Uuid::new_v4();
+        let token = issue_token(&account, session_id, "acme", Some("org_1")).unwrap();
+        let claims = crate::common::auth::utils::decode_jwt(&token).unwrap();
+        assert_eq!(claims.sub, account.id);
+        assert_eq!(claims.tenant(), "acme");
+        assert_eq!(claims.organization(), Some("org_1"));
+        assert_eq!(claims.session_id(), session_id.to_string());
+        assert_eq!(claims.username, account.username);
+        assert_eq!(claims.email, account.email);
+    }
+
+    #[test]
+    fn it_detects_recent_passwords() {
+        let history = vec!["b".to_string(), "c".to_string()];
+        assert_eq!(is_recent_password("a", &history, "a", 2), true);
+        assert_eq!(is_recent_password("a", &history, "b", 2), true);
+        assert_eq!(is_recent_password("a", &history, "c", 2), false);
+        assert_eq!(is_recent_password("a", &history, "a", 0), false);
+    }
+
+    #[actix_rt::test]
+    async fn it_sends_the_confirmation_token_to_the_new_email() {
+        let outbox = Outbox::default();
+        send_email_confirmation(Some(&outbox), "new@example.com", "tok_123")
+            .await
+            .unwrap();
+
+        let sent = outbox.0.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, "new@example.com");
+        assert!(sent[0].1.contains("tok_123"));
+    }
+
+    #[actix_rt::test]
+    async fn it_texts_the_code_to_the_new_mobile() {
+        let outbox = Outbox::default();
+        send_mobile_code(Some(&outbox), "234-8031234567", "482913")
+            .await
+            .unwrap();
+
+        let sent = outbox.0.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, "234-8031234567");
+        assert!(sent[0].1.contains("482913"));
+
+        assert!(send_mobile_code(None, "234-8031234567", "482913")
+            .await
+            .is_err());
+    }
 }
@@ -0,0 +1,130 @@
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use slog::warn;
+
+use crate::common::audit::AuditContext;
+use crate::common::helpers::AppResult;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::data::dtos::activity_dto::{ActivitiesResponse, PageRequest};
+use crate::data::models::account_activity_model::ActivityKind;
+use crate::data::stores::activity_store_trait::{CreateActivityCommand, FindActivitiesCommand};
+use crate::data::stores::base_store_trait::BoxedStoreType;
+
+const ACCOUNT_TYPE: &str = "account";
+
+/// `properties` naming the admin impersonating the actor, when there is one
+fn with_impersonator(properties: Option<Value>, audit: &AuditContext) -> Option<Value> {
+    let impersonator_id = match &audit.impersonator_id {
+        Some(id) => id,
+        None => return properties,
+    };
+
+    match properties {
+        Some(Value::Object(mut map)) => {
+            map.insert("impersonated_by".to_string(), json!(impersonator_id));
+            Some(Value::Object(map))
+        }
+        Some(value) => Some(json!({ "impersonated_by": impersonator_id, "value": value })),
+        None => Some(json!({ "impersonated_by": impersonator_id })),
+    }
+}
+
+/// The service handles recording an activity of `account_id` in the audit log.
+///
+/// A failure to record is logged and never fails the action being audited.
+/// Activities of an impersonated caller name the impersonating admin in their properties.
+pub async fn record_activity(
+    store: &BoxedStoreType,
+    audit: &AuditContext,
+    kind: ActivityKind,
+    account_id: Option<&str>,
+    properties: Option<Value>,
+) {
+    let cmd = CreateActivityCommand {
+        account_id: account_id.map(|id| id.to_string()),
+        log_name: kind.to_string(),
+        description: kind.description().to_string(),
+        subject_id: account_id.map(|id| id.to_string()),
+        subject_type: account_id.map(|_| ACCOUNT_TYPE.to_string()),
+        causer_id: audit.actor_id.clone(),
+        causer_type: audit.actor_id.as_ref().map(|_| ACCOUNT_TYPE.to_string()),
+        ip: audit.ip.clone(),
+        user_agent: audit.user_agent.clone(),
+        correlation_id: Some(audit.correlation_id.clone()),
+        properties: with_impersonator(properties, audit),
+    };
+
+    if let Err(e) = store.activity_create(cmd).await {
+        warn!(
+            LOGGER,
+            "[audit] could not record {} activity: {}",
+            kind.to_string(),
+            e
+        );
+    }
+}
+
+/// The service handles counting the failed logins attempted with `identity` since `since`
+///
+/// Unknown identities count like known ones, an audit log that can't be read counts none
+pub async fn recent_failed_logins(
+    store: &BoxedStoreType,
+    identity: &str,
+    since: DateTime<Utc>,
+) -> usize {
+    let failed = ActivityKind::LoginFailed.to_string();
+    match store
+        .activity_count_by_identity(&failed, identity, since)
+        .await
+    {
+        Ok(count) => count as usize,
+        Err(e) => {
+            warn!(LOGGER, "[audit] could not count failed logins: {}", e);
+            0
+        }
+    }
+}
+
+/// The service handles listing a page of activities, of one account or of all accounts
+pub async fn list_activities(
+    store: &BoxedStoreType,
+    account_id: Option<String>,
+    page: &PageRequest,
+) -> AppResult<ActivitiesResponse> {
+    let (page, per_page) = page.bounds();
+    let (activities, total) = store
+        .activity_find(FindActivitiesCommand {
+            account_id,
+            page,
+            per_page,
+        })
+        .await?;
+
+    Ok(ActivitiesResponse {
+        items: activities.into_iter().map(|a| a.into()).collect(),
+        page: page as i32,
+        per_page: per_page as i32,
+        total: total as i32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_names_the_impersonating_admin() {
+        let audit = AuditContext::default();
+        assert_eq!(with_impersonator(None, &audit), None);
+
+        let audit = audit.impersonated_by(Some("admin".to_string()));
+        assert_eq!(
+            with_impersonator(None, &audit),
+            Some(json!({ "impersonated_by": "admin" }))
+        );
+        assert_eq!(
+            with_impersonator(Some(json!({ "session_id": "1" })), &audit),
+            Some(json!({ "session_id": "1", "impersonated_by": "admin" }))
+        );
+    }
+}
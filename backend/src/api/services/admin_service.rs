@@ -0,0 +1,497 @@
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+
+use crate::api::services::account_service::{self, session_claim};
+use crate::api::services::activity_service::{self, record_activity};
+use crate::api::services::organization_service::default_organization;
+use crate::common::audit::AuditContext;
+use crate::common::auth::account::{ApiKeyGrant, IdentityAccount};
+use crate::common::auth::api_key::{generate_api_key, ALL_SCOPES};
+use crate::common::auth::guard::{self, *};
+use crate::common::auth::utils::{create_jwt, hash};
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::config::CONFIG;
+use crate::data::dtos::account_dto::AccountResponse;
+use crate::data::dtos::activity_dto::{ActivitiesResponse, PageRequest};
+use crate::data::dtos::api_key_dto::{
+    ApiKeysResponse, CreateApiKeyRequest, CreateServiceAccountRequest, CreatedApiKeyResponse,
+};
+use crate::data::dtos::auth_dto::{SetAccountRolesRequest, UpdateAccountRequest};
+use crate::data::dtos::session_dto::ImpersonationResponse;
+use crate::data::models::account_activity_model::ActivityKind;
+use crate::data::models::oso_init::{Actor, ADMIN_ROLE};
+use crate::data::stores::api_key_store_trait::CreateApiKeyCommand;
+use crate::data::stores::base_store_trait::{BoxedStoreType, CreateAccountCommand};
+use crate::data::stores::session_store_trait::CreateSessionCommand;
+use crate::events::{publish_event, AccountEventKind};
+
+/// Resource id used to authorize actions spanning every account
+const ALL_ACCOUNTS: &str = "*";
+
+/// Properties recorded with every activity performed through the admin api
+fn admin_properties(extra: Value) -> Option<Value> {
+    let mut properties = json!({ "admin": true });
+    if let (Some(properties), Value::Object(extra)) = (properties.as_object_mut(), extra) {
+        properties.extend(extra);
+    }
+    Some(properties)
+}
+
+/// The service handles getting any account on behalf of `caller`
+pub async fn get_account(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+) -> AppResult<AccountResponse> {
+    let _ = guard::authorize(store, caller, ACCOUNT_READ, id).await?;
+    account_service::get_account(store, id).await
+}
+
+/// The service handles updating another account on behalf of `caller`
+pub async fn update_account(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    cmd: &UpdateAccountRequest,
+    audit: &AuditContext,
+) -> AppResult<AccountResponse> {
+    let _ = guard::authorize(store, caller, ACCOUNT_UPDATE, id).await?;
+    account_service::update_account(store, id, cmd, &audit.with_actor(&caller.id)).await
+}
+
+/// The service handles locking another account on behalf of `caller`
+pub async fn lock_account(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let _ = guard::authorize(store, caller, ACCOUNT_LOCK, id).await?;
+    account_service::lock_account(store, id.to_string(), &audit.with_actor(&caller.id)).await
+}
+
+/// The service handles unlocking another account on behalf of `caller`
+pub async fn unlock_account(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let _ = guard::authorize(store, caller, ACCOUNT_UNLOCK, id).await?;
+    account_service::unlock_account(store, id.to_string(), &audit.with_actor(&caller.id)).await
+}
+
+/// The service handles forcing another account to change its password on next login
+pub async fn require_new_password(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let _ = guard::authorize(store, caller, ACCOUNT_REQUIRE_NEW_PASSWORD, id).await?;
+    let rsp = store.account_require_new_password(id, &caller.id).await?;
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({}));
+    let kind = ActivityKind::PasswordChangeRequired;
+    record_activity(store, &audit, kind, Some(id), properties).await;
+    Ok(rsp)
+}
+
+/// The service handles soft or hard deleting another account on behalf of `caller`
+pub async fn delete_account(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    hard_delete: bool,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let action = if hard_delete {
+        ACCOUNT_HARD_DELETE
+    } else {
+        ACCOUNT_DELETE
+    };
+    let _ = guard::authorize(store, caller, action, id).await?;
+
+    let rsp = store.account_delete(id, hard_delete, &caller.id).await?;
+    publish_event(store.tenant(), AccountEventKind::Deleted, id);
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({ "hard_delete": hard_delete }));
+    record_activity(
+        store,
+        &audit,
+        ActivityKind::AccountDeleted,
+        Some(id),
+        properties,
+    )
+    .await;
+    Ok(rsp)
+}
+
+/// The service handles restoring a soft deleted account on behalf of `caller`
+pub async fn restore_account(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let _ = guard::authorize(store, caller, ACCOUNT_RESTORE, id).await?;
+    let rsp = store.account_restore(id, &caller.id).await?;
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({}));
+    record_activity(
+        store,
+        &audit,
+        ActivityKind::AccountRestored,
+        Some(id),
+        properties,
+    )
+    .await;
+    Ok(rsp)
+}
+
+/// The service handles setting the confirmed status of another account
+pub async fn set_confirmed(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    confirmed: bool,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let _ = guard::authorize(store, caller, ACCOUNT_CONFIRM, id).await?;
+    let rsp = store
+        .account_set_confirmed(id, confirmed, &caller.id)
+        .await?;
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({ "confirmed": confirmed }));
+    record_activity(
+        store,
+        &audit,
+        ActivityKind::AccountConfirmed,
+        Some(id),
+        properties,
+    )
+    .await;
+    Ok(rsp)
+}
+
+/// Only admins hand out the admin role, other role managers grant narrower roles
+fn ensure_grantable(actor: &Actor, roles: &[String]) -> AppResult<()> {
+    if roles.iter().any(|r| r == ADMIN_ROLE) && !actor.has_role(ADMIN_ROLE.to_string()) {
+        return Err(ApiError::Forbidden(
+            "only admins can grant the admin role".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The service handles replacing the roles and permissions of an account
+pub async fn set_account_roles(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    cmd: &SetAccountRolesRequest,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let actor = guard::authorize(store, caller, ACCOUNT_MANAGE_ROLES, id).await?;
+    ensure_grantable(&actor, &cmd.roles)?;
+    let rsp = store
+        .account_set_roles(id, cmd.roles.clone(), cmd.permissions.clone(), &caller.id)
+        .await?;
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({
+        "roles": cmd.roles,
+        "permissions": cmd.permissions,
+    }));
+    record_activity(
+        store,
+        &audit,
+        ActivityKind::RolesChanged,
+        Some(id),
+        properties,
+    )
+    .await;
+    Ok(rsp)
+}
+
+/// The service handles listing the activity log of one or every account on behalf of `caller`
+pub async fn list_activities(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    account_id: Option<String>,
+    page: &PageRequest,
+) -> AppResult<ActivitiesResponse> {
+    let resource_id = account_id.as_deref().unwrap_or(ALL_ACCOUNTS);
+    let _ = guard::authorize(store, caller, ACCOUNT_AUDIT, resource_id).await?;
+    activity_service::list_activities(store, account_id, page).await
+}
+
+/// Whether a caller holding `grant` may hand out `scopes`, keys can't create wider keys
+fn grants_scopes(grant: Option<&ApiKeyGrant>, scopes: &[String]) -> bool {
+    match grant {
+        None => true,
+        Some(grant) if grant.scopes.iter().any(|s| s == ALL_SCOPES) => true,
+        Some(grant) => scopes.iter().all(|s| grant.scopes.contains(s)),
+    }
+}
+
+/// The service handles creating a service account on behalf of `caller`
+///
+/// Service accounts can't login, they authenticate with the api keys created for them
+pub async fn create_service_account(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    cmd: &CreateServiceAccountRequest,
+    audit: &AuditContext,
+) -> AppResult<AccountResponse> {
+    let _ = guard::authorize(store, caller, ACCOUNT_MANAGE_API_KEYS, ALL_ACCOUNTS).await?;
+    if !cmd.roles.is_empty() || !cmd.permissions.is_empty() {
+        let actor = guard::authorize(store, caller, ACCOUNT_MANAGE_ROLES, ALL_ACCOUNTS).await?;
+        ensure_grantable(&actor, &cmd.roles)?;
+    }
+
+    let account = store
+        .account_create(CreateAccountCommand {
+            username: Some(cmd.name.clone()),
+            email: None,
+            mobile: None,
+            // never handed out, the account has no usable password
+            password: hash(&uuid::Uuid::new_v4().to_string()),
+            service_account: true,
+        })
+        .await?;
+    if !cmd.roles.is_empty() || !cmd.permissions.is_empty() {
+        store
+            .account_set_roles(
+                &account.id,
+                cmd.roles.clone(),
+                cmd.permissions.clone(),
+                &caller.id,
+            )
+            .await?;
+    }
+    publish_event(store.tenant(), AccountEventKind::Created, &account.id);
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({
+        "name": cmd.name,
+        "roles": cmd.roles,
+        "permissions": cmd.permissions,
+    }));
+    let kind = ActivityKind::ServiceAccountCreated;
+    record_activity(store, &audit, kind, Some(&account.id), properties).await;
+    Ok(account.into())
+}
+
+/// The service handles creating an api key of the service account `id`
+///
+/// The key is part of the response only, the store keeps a hash of its secret
+pub async fn create_api_key(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    cmd: &CreateApiKeyRequest,
+    audit: &AuditContext,
+) -> AppResult<CreatedApiKeyResponse> {
+    let _ = guard::authorize(store, caller, ACCOUNT_MANAGE_API_KEYS, id).await?;
+    if !grants_scopes(caller.api_key.as_ref(), &cmd.scopes) {
+        let err_message = "an api key can't create a key with wider scopes".to_string();
+        return Err(ApiError::Forbidden(err_message));
+    }
+
+    let account = store.account_find_by_id(id).await?;
+    if !account.service_account {
+        let err_message = "api keys can only be created for service accounts".to_string();
+        return Err(ApiError::BadRequest(err_message));
+    }
+
+    let generated = generate_api_key();
+    let api_key = store
+        .api_key_create(CreateApiKeyCommand {
+            id: generated.id,
+            account_id: account.id,
+            name: cmd.name.clone(),
+            scopes: cmd.scopes.clone(),
+            secret_hash: generated.secret_hash,
+            created_by: Some(caller.id.clone()),
+            expires_at: cmd
+                .expires_in_days
+                .map(|days| Utc::now() + Duration::days(days as i64)),
+        })
+        .await?;
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({
+        "api_key_id": api_key.id,
+        "name": api_key.name,
+        "scopes": api_key.scopes,
+    }));
+    let kind = ActivityKind::ApiKeyCreated;
+    record_activity(store, &audit, kind, Some(id), properties).await;
+
+    Ok(CreatedApiKeyResponse {
+        key: generated.key,
+        api_key: api_key.into(),
+    })
+}
+
+/// The service handles listing the api keys of the account `id`, revoked ones included
+pub async fn list_api_keys(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+) -> AppResult<ApiKeysResponse> {
+    let _ = guard::authorize(store, caller, ACCOUNT_MANAGE_API_KEYS, id).await?;
+    let keys = store.api_key_find_by_account(id).await?;
+
+    Ok(ApiKeysResponse {
+        items: keys.into_iter().map(|key| key.into()).collect(),
+    })
+}
+
+/// The service handles revoking the api key `key_id` of the account `id`
+pub async fn revoke_api_key(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    key_id: &str,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let _ = guard::authorize(store, caller, ACCOUNT_MANAGE_API_KEYS, id).await?;
+    if !store.api_key_revoke(id, key_id).await? {
+        return Err(ApiError::NotFound("api key not found".to_string()));
+    }
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({ "api_key_id": key_id }));
+    let kind = ActivityKind::ApiKeyRevoked;
+    record_activity(store, &audit, kind, Some(id), properties).await;
+    Ok(true)
+}
+
+/// The service handles `caller` starting to impersonate the account `id`
+///
+/// Only admins logged in with a session of their own impersonate. The token belongs to a
+/// session of `id` that expires after `security.impersonation.minutes` and names the admin
+/// in its `act` claim, logging it out ends the impersonation
+pub async fn impersonate_account(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    audit: &AuditContext,
+) -> AppResult<ImpersonationResponse> {
+    let config = &CONFIG.security.impersonation;
+    if !config.enabled {
+        return Err(ApiError::Forbidden("impersonation is disabled".to_string()));
+    }
+    if caller.api_key.is_some() || caller.is_impersonated() {
+        let err_message = "impersonation is started from an admin session".to_string();
+        return Err(ApiError::Forbidden(err_message));
+    }
+    let _ = guard::require_role(store, caller, ADMIN_ROLE).await?;
+
+    if caller.id == id {
+        let err_message = "an account can't impersonate itself".to_string();
+        return Err(ApiError::BadRequest(err_message));
+    }
+    // the id comes straight from the path, an unknown or malformed one is not found
+    let account = store
+        .account_find_by_id(id)
+        .await
+        .map_err(|_| ApiError::NotFound("account not found".to_string()))?;
+    if account.service_account {
+        let err_message = "service accounts authenticate with api keys".to_string();
+        return Err(ApiError::BadRequest(err_message));
+    }
+
+    let session_id = uuid::Uuid::new_v4();
+    let expires_at = Utc::now() + Duration::minutes(config.minutes);
+    let organization_id = default_organization(store, id).await;
+    store
+        .session_create(CreateSessionCommand {
+            id: session_id.to_string(),
+            account_id: account.id.clone(),
+            ip: audit.ip.clone(),
+            user_agent: audit.user_agent.clone(),
+            expires_at,
+            organization_id: organization_id.clone(),
+            impersonated_by: Some(caller.id.clone()),
+        })
+        .await?;
+
+    let account: AccountResponse = account.into();
+    let claim = session_claim(
+        &account,
+        session_id,
+        store.tenant(),
+        organization_id.as_deref(),
+    );
+    let id_token = create_jwt(claim.impersonated_by(&caller.id).expiring_at(expires_at))?;
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = admin_properties(json!({
+        "session_id": session_id.to_string(),
+        "expires_at": expires_at,
+    }));
+    let kind = ActivityKind::ImpersonationStarted;
+    record_activity(store, &audit, kind, Some(id), properties).await;
+
+    Ok(ImpersonationResponse {
+        id_token,
+        session_id: session_id.to_string(),
+        expires_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_keeps_keys_from_widening_their_scopes() {
+        let scopes = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let grant = |s: &[&str]| ApiKeyGrant {
+            id: "k3Yd9aQx2LmP".to_string(),
+            scopes: scopes(s),
+        };
+
+        assert!(grants_scopes(None, &scopes(&[ALL_SCOPES])));
+        assert!(grants_scopes(
+            Some(&grant(&[ALL_SCOPES])),
+            &scopes(&["admin"])
+        ));
+        assert!(grants_scopes(
+            Some(&grant(&["account:read", "account:manage_api_keys"])),
+            &scopes(&["account:read"])
+        ));
+        assert!(!grants_scopes(
+            Some(&grant(&["account:manage_api_keys"])),
+            &scopes(&[ALL_SCOPES])
+        ));
+    }
+
+    #[test]
+    fn it_marks_admin_activities() {
+        let properties = admin_properties(json!({ "confirmed": true })).unwrap();
+        assert_eq!(properties, json!({ "admin": true, "confirmed": true }));
+    }
+
+    #[test]
+    fn it_reserves_the_admin_role_to_admins() {
+        let actor = |roles: &[&str]| Actor {
+            id: "a".to_string(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            permissions: vec![ACCOUNT_MANAGE_ROLES.to_string()],
+        };
+        let roles = vec![ADMIN_ROLE.to_string()];
+
+        assert!(ensure_grantable(&actor(&[]), &roles).is_err());
+        assert!(ensure_grantable(&actor(&[]), &["support".to_string()]).is_ok());
+        assert!(ensure_grantable(&actor(&[ADMIN_ROLE]), &roles).is_ok());
+    }
+}
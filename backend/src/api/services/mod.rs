@@ -1,3 +1,11 @@
 #[cfg(feature = "server-actix")]
 pub mod account_service;
+#[cfg(feature = "server-actix")]
+pub mod admin_service;
+pub mod activity_service;
+#[cfg(feature = "server-actix")]
+pub mod organization_service;
+#[cfg(feature = "server-actix")]
+pub mod profile_service;
+#[cfg(feature = "server-actix")]
 pub mod session_service;
@@ -0,0 +1,496 @@
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+
+use crate::api::services::activity_service::record_activity;
+use crate::api::services::session_service::session_token;
+use crate::common::audit::AuditContext;
+use crate::common::auth::account::IdentityAccount;
+use crate::common::auth::api_key::{hash_secret, random_string};
+use crate::common::email::send_email;
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::config::{OrganizationFeaturesConfig, CONFIG};
+use crate::data::dtos::account_dto::AccountResponse;
+use crate::data::dtos::organization_dto::*;
+use crate::data::models::account_activity_model::ActivityKind;
+use crate::data::models::invitation_model::InvitationModel;
+use crate::data::models::organization_model::{MembershipModel, OrganizationRole};
+use crate::data::stores::base_store_trait::BoxedStoreType;
+use crate::data::stores::organization_store_trait::{
+    CreateInvitationCommand, CreateMembershipCommand, CreateOrganizationCommand,
+};
+
+/// Length of the invitation tokens, only their hash is stored
+const INVITATION_TOKEN_LEN: usize = 40;
+
+/// Properties recorded with every activity of an organization
+fn organization_properties(organization_id: &str, extra: Value) -> Option<Value> {
+    let mut properties = json!({ "organization_id": organization_id });
+    if let (Some(properties), Value::Object(extra)) = (properties.as_object_mut(), extra) {
+        properties.extend(extra);
+    }
+    Some(properties)
+}
+
+fn invalid_invitation() -> ApiError {
+    ApiError::BadRequest("the invitation is invalid or has expired".to_string())
+}
+
+/// The invitation email, its link carries the token
+fn invitation_message(
+    config: &OrganizationFeaturesConfig,
+    organization_name: &str,
+    token: &str,
+) -> (String, String) {
+    let subject = format!("You are invited to join {}", organization_name);
+    let body = format!(
+        "You have been invited to join {} for {} hours.\n\nAccept the invitation: {}\n",
+        organization_name,
+        config.invitation_hours,
+        config.invitation_url.replace("{token}", token)
+    );
+    (subject, body)
+}
+
+/// The membership of `account_id` in `organization_id`
+///
+/// Organizations are not found by accounts outside of them
+async fn membership_of(
+    store: &BoxedStoreType,
+    organization_id: &str,
+    account_id: &str,
+) -> AppResult<MembershipModel> {
+    store
+        .membership_find(organization_id, account_id)
+        .await
+        .map_err(|_| ApiError::NotFound("organization not found".to_string()))
+}
+
+/// The membership of `caller` when it manages the members of `organization_id`
+async fn manager_of(
+    store: &BoxedStoreType,
+    organization_id: &str,
+    caller: &IdentityAccount,
+) -> AppResult<MembershipModel> {
+    let membership = membership_of(store, organization_id, &caller.id).await?;
+    if membership.role.can_manage() {
+        Ok(membership)
+    } else {
+        Err(ApiError::Forbidden(
+            "the organization admin role is required".to_string(),
+        ))
+    }
+}
+
+/// Fail unless a member with `role` may grant or take away the owner role in `roles`
+fn ensure_owner_change(role: OrganizationRole, roles: &[OrganizationRole]) -> AppResult<()> {
+    if roles.contains(&OrganizationRole::Owner) && role != OrganizationRole::Owner {
+        Err(ApiError::Forbidden(
+            "only owners manage the owners of an organization".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Fail when `membership` is the last owner of its organization
+async fn ensure_not_last_owner(
+    store: &BoxedStoreType,
+    membership: &MembershipModel,
+) -> AppResult<()> {
+    if membership.role != OrganizationRole::Owner {
+        return Ok(());
+    }
+
+    let members = store
+        .membership_find_by_organization(&membership.organization_id)
+        .await?;
+    let owners = members
+        .iter()
+        .filter(|m| m.role == OrganizationRole::Owner)
+        .count();
+    if owners > 1 {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(
+            "an organization needs at least one owner".to_string(),
+        ))
+    }
+}
+
+/// The organization a new session of `account_id` acts in, the one it joined last
+pub async fn default_organization(store: &BoxedStoreType, account_id: &str) -> Option<String> {
+    store
+        .membership_find_by_account(account_id)
+        .await
+        .ok()
+        .and_then(|memberships| memberships.into_iter().next())
+        .map(|membership| membership.organization_id)
+}
+
+/// The service handles creating an organization owned by `caller`
+pub async fn create_organization(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    cmd: &CreateOrganizationRequest,
+    audit: &AuditContext,
+) -> AppResult<OrganizationResponse> {
+    audit.ensure_not_impersonated()?;
+    let organization = store
+        .organization_create(CreateOrganizationCommand {
+            name: cmd.name.clone(),
+            created_by: caller.id.clone(),
+        })
+        .await?;
+    let membership = store
+        .membership_create(CreateMembershipCommand {
+            organization_id: organization.id.clone(),
+            account_id: caller.id.clone(),
+            role: OrganizationRole::Owner,
+            invited_by: None,
+        })
+        .await?;
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = organization_properties(&organization.id, json!({ "name": cmd.name }));
+    let kind = ActivityKind::OrganizationCreated;
+    record_activity(store, &audit, kind, Some(&caller.id), properties).await;
+
+    Ok(OrganizationResponse::new(organization, &membership))
+}
+
+/// The service handles listing the organizations of `caller`
+pub async fn list_organizations(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+) -> AppResult<OrganizationsResponse> {
+    let memberships = store.membership_find_by_account(&caller.id).await?;
+
+    let mut items = vec![];
+    for membership in memberships.iter() {
+        if let Ok(organization) = store
+            .organization_find_by_id(&membership.organization_id)
+            .await
+        {
+            items.push(OrganizationResponse::new(organization, membership));
+        }
+    }
+
+    Ok(OrganizationsResponse { items })
+}
+
+/// The service handles getting an organization of `caller`
+pub async fn get_organization(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+) -> AppResult<OrganizationResponse> {
+    let membership = membership_of(store, id, &caller.id).await?;
+    let organization = store.organization_find_by_id(id).await?;
+
+    Ok(OrganizationResponse::new(organization, &membership))
+}
+
+/// The service handles switching the session of `caller` to act in the organization `id`
+///
+/// The fresh token of the session carries the organization in its `org` claim
+pub async fn activate_organization(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    audit: &AuditContext,
+) -> AppResult<ActiveOrganizationResponse> {
+    let session_id = caller.session_id.as_deref().ok_or_else(|| {
+        ApiError::BadRequest("only sessions have an active organization".to_string())
+    })?;
+    let membership = membership_of(store, id, &caller.id).await?;
+    let organization = store.organization_find_by_id(id).await?;
+
+    store.session_set_organization(session_id, Some(id)).await?;
+    let account: AccountResponse = store.account_find_by_id(&caller.id).await?.into();
+    let audit = audit
+        .with_actor(&caller.id)
+        .in_session(Some(session_id.to_string()));
+    let id_token = session_token(store, &account, &audit).await?;
+
+    Ok(ActiveOrganizationResponse {
+        organization: OrganizationResponse::new(organization, &membership),
+        id_token,
+    })
+}
+
+/// The service handles listing the members of an organization of `caller`
+pub async fn list_members(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+) -> AppResult<MembersResponse> {
+    let _ = membership_of(store, id, &caller.id).await?;
+    let members = store.membership_find_by_organization(id).await?;
+
+    Ok(MembersResponse {
+        items: members.into_iter().map(|m| m.into()).collect(),
+    })
+}
+
+/// The service handles changing the role of the member `account_id` of the organization `id`
+pub async fn set_member_role(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    account_id: &str,
+    cmd: &UpdateMemberRequest,
+    audit: &AuditContext,
+) -> AppResult<MemberResponse> {
+    audit.ensure_not_impersonated()?;
+    let role: OrganizationRole = cmd.role.parse()?;
+    let manager = manager_of(store, id, caller).await?;
+    let member = membership_of(store, id, account_id).await?;
+    ensure_owner_change(manager.role, &[role, member.role])?;
+    if role != OrganizationRole::Owner {
+        ensure_not_last_owner(store, &member).await?;
+    }
+
+    store.membership_set_role(id, account_id, role).await?;
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = organization_properties(id, json!({ "role": role.as_str() }));
+    let kind = ActivityKind::MemberRoleChanged;
+    record_activity(store, &audit, kind, Some(account_id), properties).await;
+
+    Ok(membership_of(store, id, account_id).await?.into())
+}
+
+/// The service handles removing the member `account_id` from the organization `id`
+///
+/// Members can leave an organization, only admins and owners remove others
+pub async fn remove_member(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    account_id: &str,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    audit.ensure_not_impersonated()?;
+    let member = membership_of(store, id, account_id).await?;
+    if caller.id != account_id {
+        let manager = manager_of(store, id, caller).await?;
+        ensure_owner_change(manager.role, &[member.role])?;
+    }
+    ensure_not_last_owner(store, &member).await?;
+
+    if !store.membership_delete(id, account_id).await? {
+        return Err(ApiError::NotFound("membership not found".to_string()));
+    }
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = organization_properties(id, json!({}));
+    let kind = ActivityKind::MemberRemoved;
+    record_activity(store, &audit, kind, Some(account_id), properties).await;
+    Ok(true)
+}
+
+/// The service handles inviting `cmd.email` to the organization `id`
+///
+/// The token is only sent by email, an invitation that can't be sent is revoked
+pub async fn invite_member(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    cmd: &CreateInvitationRequest,
+    audit: &AuditContext,
+) -> AppResult<InvitationResponse> {
+    audit.ensure_not_impersonated()?;
+    let role: OrganizationRole = cmd.role.parse()?;
+    let manager = manager_of(store, id, caller).await?;
+    ensure_owner_change(manager.role, &[role])?;
+    let organization = store.organization_find_by_id(id).await?;
+
+    let config = &CONFIG.features.organizations;
+    let token = random_string(INVITATION_TOKEN_LEN);
+    let invitation = store
+        .invitation_create(CreateInvitationCommand {
+            organization_id: organization.id.clone(),
+            email: cmd.email.to_lowercase(),
+            role,
+            token_hash: hash_secret(&token),
+            invited_by: caller.id.clone(),
+            expires_at: Utc::now() + Duration::hours(config.invitation_hours),
+        })
+        .await?;
+
+    let (subject, body) = invitation_message(config, &organization.name, &token);
+    if let Err(e) = send_email(&invitation.email, &subject, &body).await {
+        let _ = store.invitation_revoke(id, &invitation.id).await;
+        return Err(e);
+    }
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = organization_properties(
+        id,
+        json!({ "invitation_id": invitation.id, "email": invitation.email, "role": role.as_str() }),
+    );
+    let kind = ActivityKind::MemberInvited;
+    record_activity(store, &audit, kind, Some(&caller.id), properties).await;
+
+    Ok(invitation.into())
+}
+
+/// The service handles listing the open invitations of the organization `id`
+pub async fn list_invitations(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+) -> AppResult<InvitationsResponse> {
+    let _ = manager_of(store, id, caller).await?;
+    let invitations = store.invitation_find_open(id).await?;
+
+    Ok(InvitationsResponse {
+        items: invitations.into_iter().map(|i| i.into()).collect(),
+    })
+}
+
+/// The service handles revoking the invitation `invitation_id` of the organization `id`
+pub async fn revoke_invitation(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    id: &str,
+    invitation_id: &str,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    audit.ensure_not_impersonated()?;
+    let _ = manager_of(store, id, caller).await?;
+    if !store.invitation_revoke(id, invitation_id).await? {
+        return Err(ApiError::NotFound("invitation not found".to_string()));
+    }
+
+    let audit = audit.with_actor(&caller.id);
+    let properties = organization_properties(id, json!({ "invitation_id": invitation_id }));
+    let kind = ActivityKind::InvitationRevoked;
+    record_activity(store, &audit, kind, Some(&caller.id), properties).await;
+    Ok(true)
+}
+
+/// Whether an invitation sent to `invited` may be accepted by an account with `email`
+fn is_invited(invited: &str, email: Option<&str>) -> bool {
+    email.map_or(false, |email| email.trim().eq_ignore_ascii_case(invited))
+}
+
+/// The invitation of `token` while it can still be accepted by an account with `email`
+///
+/// The token only works for the email it was sent to, a mismatch looks like any bad token
+pub async fn pending_invitation(
+    store: &BoxedStoreType,
+    token: &str,
+    email: Option<&str>,
+) -> AppResult<InvitationModel> {
+    let invitation = store
+        .invitation_find_by_token_hash(&hash_secret(token))
+        .await
+        .map_err(|_| invalid_invitation())?;
+
+    if invitation.is_pending(Utc::now()) && is_invited(&invitation.email, email) {
+        Ok(invitation)
+    } else {
+        Err(invalid_invitation())
+    }
+}
+
+/// Make `account_id` a member through `invitation`
+///
+/// Members keep their role unless the invitation grants a higher one
+pub async fn accept_invitation(
+    store: &BoxedStoreType,
+    invitation: &InvitationModel,
+    account_id: &str,
+    audit: &AuditContext,
+) -> AppResult<MembershipModel> {
+    if !store.invitation_accept(&invitation.id, account_id).await? {
+        return Err(invalid_invitation());
+    }
+
+    let organization_id = invitation.organization_id.as_str();
+    let membership = match store.membership_find(organization_id, account_id).await {
+        Ok(member) if member.role >= invitation.role => member,
+        Ok(_) => {
+            store
+                .membership_set_role(organization_id, account_id, invitation.role)
+                .await?;
+            store.membership_find(organization_id, account_id).await?
+        }
+        Err(_) => {
+            store
+                .membership_create(CreateMembershipCommand {
+                    organization_id: organization_id.to_string(),
+                    account_id: account_id.to_string(),
+                    role: invitation.role,
+                    invited_by: Some(invitation.invited_by.clone()),
+                })
+                .await?
+        }
+    };
+
+    let audit = audit.with_actor(account_id);
+    let properties = organization_properties(
+        organization_id,
+        json!({ "invitation_id": invitation.id, "role": membership.role.as_str() }),
+    );
+    let kind = ActivityKind::InvitationAccepted;
+    record_activity(store, &audit, kind, Some(account_id), properties).await;
+
+    Ok(membership)
+}
+
+/// The service handles `caller` accepting an invitation while logged in
+pub async fn accept_invitation_token(
+    store: &BoxedStoreType,
+    caller: &IdentityAccount,
+    cmd: &AcceptInvitationRequest,
+    audit: &AuditContext,
+) -> AppResult<OrganizationResponse> {
+    audit.ensure_not_impersonated()?;
+    let account = store.account_find_by_id(&caller.id).await?;
+    let invitation = pending_invitation(store, &cmd.token, account.email.as_deref()).await?;
+    let membership = accept_invitation(store, &invitation, &caller.id, audit).await?;
+    let organization = store
+        .organization_find_by_id(&invitation.organization_id)
+        .await?;
+
+    Ok(OrganizationResponse::new(organization, &membership))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_keeps_owners_to_owners() {
+        use OrganizationRole::*;
+
+        assert!(ensure_owner_change(Owner, &[Owner, Admin]).is_ok());
+        assert!(ensure_owner_change(Admin, &[Member, Admin]).is_ok());
+        assert!(ensure_owner_change(Admin, &[Owner]).is_err());
+        assert!(ensure_owner_change(Admin, &[Member, Owner]).is_err());
+    }
+
+    #[test]
+    fn it_links_the_invitation_token() {
+        let config = OrganizationFeaturesConfig {
+            invitation_hours: 48,
+            invitation_url: "https://app.example.com/join?token={token}".to_string(),
+        };
+
+        let (subject, body) = invitation_message(&config, "Acme", "t0k3n");
+        assert_eq!(subject, "You are invited to join Acme");
+        assert!(body.contains("https://app.example.com/join?token=t0k3n"));
+        assert!(body.contains("48 hours"));
+    }
+
+    #[test]
+    fn it_ties_invitations_to_the_invited_email() {
+        assert!(is_invited("ann@example.com", Some("ann@example.com")));
+        assert!(is_invited("ann@example.com", Some("Ann@Example.com")));
+        assert!(!is_invited("ann@example.com", Some("bob@example.com")));
+        assert!(!is_invited("ann@example.com", None));
+    }
+}
@@ -0,0 +1,102 @@
+use serde_json::{json, Map, Value};
+
+use crate::api::services::activity_service::record_activity;
+use crate::common::audit::AuditContext;
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::data::dtos::profile_dto::*;
+use crate::data::models::account_activity_model::ActivityKind;
+use crate::data::models::profile_schema::PROFILE_SCHEMA;
+use crate::data::stores::base_store_trait::BoxedStoreType;
+
+/// The service handles validating profile fields against the profile schema
+///
+/// Unique fields are checked against the profiles of every other account
+pub async fn validate_profile(
+    store: &BoxedStoreType,
+    fields: &[ProfileFieldInput],
+    account_id: Option<&str>,
+    partial: bool,
+) -> AppResult<Map<String, Value>> {
+    let values = PROFILE_SCHEMA.validate(
+        fields.iter().map(|f| (f.name.as_str(), f.value.as_str())),
+        partial,
+    )?;
+
+    let mut errors = vec![];
+    for (name, value) in values.iter().filter(|(_, v)| !v.is_null()) {
+        let unique = PROFILE_SCHEMA.field(name).map_or(false, |f| f.unique);
+        if unique && store.profile_field_taken(name, value, account_id).await? {
+            errors.push(format!("{} is already taken", name));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(ApiError::ValidationError(errors))
+    }
+}
+
+/// The service handles getting the profile of an account
+pub async fn get_profile(store: &BoxedStoreType, account_id: &str) -> AppResult<ProfileResponse> {
+    match store.profile_find_by_account(account_id).await {
+        Ok(profile) => Ok(profile.into()),
+        Err(ApiError::NotFound(_)) => Ok(ProfileResponse::empty(account_id)),
+        Err(e) => Err(e),
+    }
+}
+
+/// The service handles updating the profile fields of an account, other fields are kept
+pub async fn update_profile(
+    store: &BoxedStoreType,
+    account_id: &str,
+    cmd: &UpdateProfileRequest,
+    audit: &AuditContext,
+) -> AppResult<ProfileResponse> {
+    let fields = validate_profile(store, &cmd.fields, Some(account_id), true).await?;
+
+    // only the field names are logged, profile values can hold personal data
+    let properties = json!({ "fields": fields.keys().collect::<Vec<_>>() });
+
+    let profile = store
+        .profile_upsert(account_id, fields, audit.actor_or(account_id))
+        .await?;
+    record_activity(
+        store,
+        audit,
+        ActivityKind::ProfileUpdated,
+        Some(account_id),
+        Some(properties),
+    )
+    .await;
+
+    Ok(profile.into())
+}
+
+/// The service handles describing the custom profile fields
+pub fn get_profile_schema() -> ProfileSchemaResponse {
+    (&*PROFILE_SCHEMA).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::tests::helpers::tests::get_store;
+
+    #[actix_rt::test]
+    async fn it_rejects_undeclared_profile_fields() {
+        let fields = vec![ProfileFieldInput {
+            name: "nickname".to_string(),
+            value: "ada".to_string(),
+        }];
+
+        let err = validate_profile(&get_store(), &fields, None, false)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ApiError::ValidationError(vec!["nickname is not a profile field".to_string()])
+        );
+    }
+}
@@ -1,24 +1,266 @@
-use crate::common::auth::utils::hash;
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+use crate::api::services::account_service::{issue_token, replace_password, session_claim};
+use crate::api::services::activity_service::{recent_failed_logins, record_activity};
+use crate::api::services::organization_service::{
+    accept_invitation, default_organization, pending_invitation,
+};
+use crate::common::audit::AuditContext;
+use crate::common::auth::account::IdentityAccount;
+use crate::common::auth::challenge::{check_challenge, login_challenged, CHALLENGE_VERIFIER};
+use crate::common::auth::password_policy::{password_age, PasswordAge};
+use crate::common::auth::revocation::is_revoked;
+use crate::common::auth::utils::{
+    create_jwt, decode_scoped_jwt, hash, verify_password, PrivateClaim, PASSWORD_CHANGE_SCOPE,
+};
 use crate::common::errors::ApiError;
 use crate::common::helpers::AppResult;
-use crate::data::dtos::account_dto::AccountResponse;
-use crate::data::dtos::auth_dto::PostSessionRequest;
+use crate::common::middleware::rate_limit::RATE_LIMITER;
+use crate::common::tenant::tenant_features;
+use crate::config::{EnumerationProtectionConfig, CONFIG};
+use crate::data::dtos::account_dto::{AccountResponse, PostSessionResponse};
+use crate::data::dtos::auth_dto::{PostSessionRequest, RenewPasswordRequest};
+use crate::data::dtos::session_dto::{
+    ActiveSessionResponse, ActiveSessionsResponse, RevokedSessionsResponse,
+};
+use crate::data::models::account_activity_model::ActivityKind;
+use crate::data::models::accounts_model::AccountModel;
 use crate::data::stores::base_store_trait::BoxedStoreType;
-use actix_guardian_identity::Identity;
+use crate::data::stores::session_store_trait::CreateSessionCommand;
+use crate::events::{publish_event, AccountEventKind};
+
+lazy_static! {
+    /// Verified for unknown identities so their logins take as long as those of known ones
+    static ref DUMMY_PASSWORD_HASH: String = hash(&uuid::Uuid::new_v4().to_string());
+}
+
+/// The only login error of the enumeration safe mode
+fn login_failed() -> ApiError {
+    ApiError::Unauthorized("your email, username or password is incorrect".to_string())
+}
+
+/// Hide why a login failed when enumeration protection is on, the activity log keeps the reason
+fn uniform_error(protection: &EnumerationProtectionConfig, e: ApiError) -> ApiError {
+    if protection.enabled {
+        login_failed()
+    } else {
+        e
+    }
+}
+
+/// Record a new session of `account` seen from the caller in `audit` and issue its token
+///
+/// The session acts in the organization the account joined last
+pub async fn open_session(
+    store: &BoxedStoreType,
+    account: &AccountResponse,
+    audit: &AuditContext,
+) -> AppResult<String> {
+    let session_id = uuid::Uuid::new_v4();
+    let organization_id = default_organization(store, &account.id).await;
+    store
+        .session_create(CreateSessionCommand {
+            id: session_id.to_string(),
+            account_id: account.id.clone(),
+            ip: audit.ip.clone(),
+            user_agent: audit.user_agent.clone(),
+            expires_at: Utc::now() + Duration::hours(CONFIG.security.jwt_expiration),
+            organization_id: organization_id.clone(),
+            impersonated_by: None,
+        })
+        .await?;
+
+    let organization = organization_id.as_deref();
+    issue_token(account, session_id, store.tenant(), organization)
+}
+
+/// A fresh token of the caller's session in `audit`, a new session for callers without one
+///
+/// Impersonation sessions keep their expiry and the `act` claim naming the admin
+pub async fn session_token(
+    store: &BoxedStoreType,
+    account: &AccountResponse,
+    audit: &AuditContext,
+) -> AppResult<String> {
+    let session_id = audit
+        .session_id
+        .as_deref()
+        .and_then(|id| uuid::Uuid::parse_str(id).ok());
+
+    match session_id {
+        Some(session_id) => {
+            let id = session_id.to_string();
+            let session = store.session_find_by_id(&id).await?;
+            let organization = session.organization_id.as_deref();
+            if let Some(admin_id) = &session.impersonated_by {
+                let claim = session_claim(account, session_id, store.tenant(), organization);
+                return create_jwt(
+                    claim
+                        .impersonated_by(admin_id)
+                        .expiring_at(session.expires_at),
+                );
+            }
+
+            let expires_at = Utc::now() + Duration::hours(CONFIG.security.jwt_expiration);
+            store.session_touch(&id, Some(expires_at)).await?;
+            issue_token(account, session_id, store.tenant(), organization)
+        }
+        None => open_session(store, account, audit).await,
+    }
+}
 
 /// This service handles authenticating an auth
+///
+/// An expired password creates no session, the response carries a change password token instead.
+/// Logins are challenged when configured, or once the account piles up failed logins
 pub async fn create_session(
     store: &BoxedStoreType,
     cmd: &PostSessionRequest,
-) -> AppResult<AccountResponse> {
+    audit: &AuditContext,
+) -> AppResult<PostSessionResponse> {
+    if !tenant_features(&CONFIG, store.tenant()).enable_login {
+        return Err(ApiError::Forbidden(
+            "login is disabled for this tenant".to_string(),
+        ));
+    }
+
+    if let Some(retry_after) = RATE_LIMITER
+        .check_login(store.tenant(), &cmd.identity)
+        .await
+    {
+        let message = "too many login attempts, please retry later".to_string();
+        return Err(ApiError::TooManyRequests(message, retry_after));
+    }
+
+    let challenge = &CONFIG.features.challenge;
+    let challenge_token = cmd.challenge_token.as_deref();
+    if challenge.enable_login {
+        check_challenge(challenge_token, audit.ip.as_deref()).await?;
+    }
+
+    // identities under attack challenge every login until the failures age out, known or not
+    if CHALLENGE_VERIFIER.is_some() && !challenge.enable_login && challenge.failed_logins > 0 {
+        let since = Utc::now() - Duration::minutes(challenge.failed_login_minutes);
+        let failed_logins = recent_failed_logins(store, &cmd.identity, since).await;
+        if login_challenged(challenge, failed_logins) {
+            check_challenge(challenge_token, audit.ip.as_deref()).await?;
+        }
+    }
+
+    let protection = &CONFIG.security.enumeration_protection;
+    let account = match store.account_find_by_identity(cmd.identity.as_str()).await {
+        Ok(account) => account,
+        Err(e) => {
+            if protection.enabled {
+                verify_password(&cmd.password, &DUMMY_PASSWORD_HASH);
+            }
+            let properties = json!({ "identity": cmd.identity, "reason": "unknown identity" });
+            record_activity(
+                store,
+                audit,
+                ActivityKind::LoginFailed,
+                None,
+                Some(properties),
+            )
+            .await;
+            return Err(uniform_error(protection, e));
+        }
+    };
+
+    if let Err(e) = check_login(&account, cmd) {
+        let properties = json!({ "identity": cmd.identity, "reason": e.to_string() });
+        let account_id = Some(account.id.as_str());
+        record_activity(
+            store,
+            audit,
+            ActivityKind::LoginFailed,
+            account_id,
+            Some(properties),
+        )
+        .await;
+        return Err(uniform_error(protection, e));
+    }
+
+    let policy = &CONFIG.security.password_policy;
+    let changed_at = account.password_changed_at.unwrap_or(account.created_at);
+    let password_expires_at = match password_age(policy, changed_at, Utc::now()) {
+        PasswordAge::Current => None,
+        PasswordAge::ExpiresSoon(at) => Some(at),
+        PasswordAge::Expired => {
+            let properties = json!({ "reason": "password expired" });
+            let account_id = Some(account.id.as_str());
+            record_activity(
+                store,
+                audit,
+                ActivityKind::LoginFailed,
+                account_id,
+                Some(properties),
+            )
+            .await;
+            let token = create_jwt(PrivateClaim::password_change(account.id))?;
+            return Ok(PostSessionResponse::password_expired(token));
+        }
+    };
+
+    let invitation = match &cmd.invitation_token {
+        Some(token) => Some(pending_invitation(store, token, account.email.as_deref()).await?),
+        None => None,
+    };
+
+    // the session held before the login is ended, a planted or leaked one can't outlive it
+    if let (Some(previous_id), Some(session_id)) = (&audit.actor_id, &audit.session_id) {
+        store
+            .session_revoke(previous_id, Some(session_id), None)
+            .await?;
+    }
+
+    publish_event(store.tenant(), AccountEventKind::LoggedIn, &account.id);
+    let audit = audit.with_actor(&account.id).in_session(None);
+    record_activity(store, &audit, ActivityKind::Login, Some(&account.id), None).await;
+    if let Some(invitation) = &invitation {
+        accept_invitation(store, invitation, &account.id, &audit).await?;
+    }
+
+    let account: AccountResponse = account.into();
+    let mut rsp = PostSessionResponse::new(open_session(store, &account, &audit).await?);
+    rsp.password_expires_at = password_expires_at;
+    Ok(rsp)
+}
+
+/// This service handles replacing an expired password with the token handed out at login
+pub async fn renew_password(
+    store: &BoxedStoreType,
+    cmd: &RenewPasswordRequest,
+    audit: &AuditContext,
+) -> AppResult<PostSessionResponse> {
+    let invalid_token =
+        || ApiError::Unauthorized("the change password token is invalid or expired".to_string());
+
+    let claim = decode_scoped_jwt(&cmd.change_password_token, PASSWORD_CHANGE_SCOPE)
+        .map_err(|_| invalid_token())?;
     let account = store
-        .account_find_by_identity(cmd.identity.as_str())
-        .await?;
+        .account_find_by_id(&claim.sub)
+        .await
+        .map_err(|_| invalid_token())?;
 
-    let hashed_password = hash(&cmd.password);
-    if account.password != hashed_password {
-        let err_message = "your email, username or password is incorrect".to_string();
-        println!("error with password");
+    // a token is spent once the password changed after it was issued
+    if account.locked || is_revoked(account.password_changed_at, claim.issued_at()) {
+        return Err(invalid_token());
+    }
+
+    let audit = audit.with_actor(&account.id);
+    replace_password(store, &account, &cmd.password, &audit).await
+}
+
+/// Check the password and the state of the account logging in
+fn check_login(account: &AccountModel, cmd: &PostSessionRequest) -> AppResult<()> {
+    if !verify_password(&cmd.password, &account.password) {
+        return Err(login_failed());
+    }
+
+    if account.service_account {
+        let err_message = "service accounts authenticate with api keys".to_string();
         return Err(ApiError::Unauthorized(err_message));
     }
 
@@ -45,17 +287,128 @@ pub async fn create_session(
         },
     }
 
-    Ok(account.into())
+    Ok(())
 }
 
 /// This service handles deleting session or logging out.
-pub async fn delete_session(_store: &BoxedStoreType, identity: &Identity) -> AppResult<bool> {
-    identity.forget();
+///
+/// Logging out an impersonation token ends the impersonation, the account stays logged in
+pub async fn delete_session(
+    store: &BoxedStoreType,
+    current_account: &IdentityAccount,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    let id = current_account.id.as_str();
+    if let Some(session_id) = current_account.session_id.as_deref() {
+        store.session_revoke(id, Some(session_id), None).await?;
+    }
+
+    match current_account.impersonator_id.as_deref() {
+        Some(admin_id) => {
+            let audit = audit.with_actor(admin_id);
+            let properties = json!({ "session_id": current_account.session_id });
+            let kind = ActivityKind::ImpersonationEnded;
+            record_activity(store, &audit, kind, Some(id), Some(properties)).await;
+        }
+        None => {
+            publish_event(store.tenant(), AccountEventKind::LoggedOut, id);
+            record_activity(store, audit, ActivityKind::Logout, Some(id), None).await;
+        }
+    }
+
+    Ok(true)
+}
+
+/// This service handles ending the impersonations of the tenant of `store` that expired
+///
+/// Each one is recorded in the audit log as ended by its admin. Returns how many were ended
+pub async fn end_expired_impersonations(store: &BoxedStoreType) -> AppResult<usize> {
+    let sessions = store.session_end_expired_impersonations().await?;
+    for session in &sessions {
+        let audit = AuditContext::new(session.impersonated_by.clone(), None, None, None);
+        let properties = json!({ "session_id": session.id, "expired": true });
+        let id = session.account_id.as_str();
+        let kind = ActivityKind::ImpersonationEnded;
+        record_activity(store, &audit, kind, Some(id), Some(properties)).await;
+    }
+
+    Ok(sessions.len())
+}
+
+/// This service handles listing the active sessions of the logged in account
+pub async fn list_sessions(
+    store: &BoxedStoreType,
+    current_account: &IdentityAccount,
+) -> AppResult<ActiveSessionsResponse> {
+    let current_id = current_account.session_id.as_deref();
+    let sessions = store.session_find_active(&current_account.id).await?;
+
+    Ok(ActiveSessionsResponse {
+        items: sessions
+            .into_iter()
+            .map(|session| ActiveSessionResponse::new(session, current_id))
+            .collect(),
+    })
+}
+
+/// This service handles signing the logged in account out of one of its sessions
+pub async fn revoke_session(
+    store: &BoxedStoreType,
+    current_account: &IdentityAccount,
+    session_id: &str,
+    audit: &AuditContext,
+) -> AppResult<bool> {
+    audit.ensure_not_impersonated()?;
+    let id = current_account.id.as_str();
+    let revoked = store.session_revoke(id, Some(session_id), None).await?;
+    if revoked == 0 {
+        return Err(ApiError::NotFound("session not found".to_string()));
+    }
 
+    let properties = json!({ "session_id": session_id });
+    let kind = ActivityKind::SessionRevoked;
+    record_activity(store, audit, kind, Some(id), Some(properties)).await;
     Ok(true)
 }
 
+/// This service handles signing the logged in account out of every session but the current one
+pub async fn revoke_other_sessions(
+    store: &BoxedStoreType,
+    current_account: &IdentityAccount,
+    audit: &AuditContext,
+) -> AppResult<RevokedSessionsResponse> {
+    audit.ensure_not_impersonated()?;
+    let id = current_account.id.as_str();
+    let keep_id = current_account.session_id.as_deref();
+    let revoked = store.session_revoke(id, None, keep_id).await?;
+
+    if revoked > 0 {
+        let properties = json!({ "revoked": revoked, "kept_session_id": keep_id });
+        let kind = ActivityKind::SessionRevoked;
+        record_activity(store, audit, kind, Some(id), Some(properties)).await;
+    }
+    Ok(RevokedSessionsResponse {
+        revoked: revoked as i32,
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+
+    #[test]
+    fn it_hides_why_a_login_failed() {
+        let protection = EnumerationProtectionConfig {
+            enabled: true,
+            ..EnumerationProtectionConfig::default()
+        };
+        let locked = || ApiError::Unauthorized("your account has been locked out".to_string());
+        let not_found = ApiError::NotFound("account not found".to_string());
+
+        assert_eq!(uniform_error(&protection, locked()), login_failed());
+        assert_eq!(uniform_error(&protection, not_found), login_failed());
+
+        let disabled = EnumerationProtectionConfig::default();
+        assert_eq!(uniform_error(&disabled, locked()), locked());
+    }
 }
@@ -0,0 +1,155 @@
+//! Audit context crate
+use actix_web::{dev::Payload, http::header, web::HttpRequest, Error, FromRequest};
+use futures::future::{ok, Ready};
+
+use crate::common::auth::extractors::account_from_request;
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+
+/// Headers checked, in order, for a caller supplied correlation id
+pub const CORRELATION_HEADERS: [&str; 2] = ["x-correlation-id", "x-request-id"];
+
+/// Who performed an action and where it came from, recorded with every account activity
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AuditContext {
+    pub actor_id: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub correlation_id: String,
+    /// The server side session of the caller, tokens reissued to it keep the session
+    pub session_id: Option<String>,
+    /// The admin impersonating the actor, recorded with every activity of the impersonation
+    pub impersonator_id: Option<String>,
+}
+
+impl AuditContext {
+    pub fn new(
+        actor_id: Option<String>,
+        ip: Option<String>,
+        user_agent: Option<String>,
+        correlation_id: Option<String>,
+    ) -> Self {
+        AuditContext {
+            actor_id,
+            ip,
+            user_agent,
+            correlation_id: correlation_id
+                .filter(|id| !id.is_empty())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            session_id: None,
+            impersonator_id: None,
+        }
+    }
+
+    /// The same context in the session `session_id` of the caller
+    pub fn in_session(self, session_id: Option<String>) -> Self {
+        AuditContext { session_id, ..self }
+    }
+
+    /// The same context of a caller impersonated by the admin `impersonator_id`
+    pub fn impersonated_by(self, impersonator_id: Option<String>) -> Self {
+        AuditContext {
+            impersonator_id,
+            ..self
+        }
+    }
+
+    /// Fail when an admin is impersonating the caller, for changes only the account itself makes
+    pub fn ensure_not_impersonated(&self) -> AppResult<()> {
+        match self.impersonator_id {
+            Some(_) => Err(ApiError::Forbidden(
+                "not allowed while impersonating an account".to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The acting account id recorded in `updated_by`, `fallback` for anonymous callers
+    pub fn actor_or<'a>(&'a self, fallback: &'a str) -> &'a str {
+        self.actor_id.as_deref().unwrap_or(fallback)
+    }
+
+    /// The same context acting as `actor_id`, used once the caller is known e.g. after login
+    pub fn with_actor(&self, actor_id: &str) -> Self {
+        AuditContext {
+            actor_id: Some(actor_id.to_string()),
+            ..self.clone()
+        }
+    }
+}
+
+/// Build the audit context of a http request
+pub fn audit_from_request(req: &HttpRequest) -> AuditContext {
+    let header_value = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+
+    let correlation_id = CORRELATION_HEADERS
+        .iter()
+        .find_map(|name| header_value(name));
+
+    let account = account_from_request(req);
+    let session_id = account.as_ref().and_then(|a| a.session_id.clone());
+    let impersonator_id = account.as_ref().and_then(|a| a.impersonator_id.clone());
+    AuditContext::new(
+        account.map(|account| account.id),
+        req.connection_info()
+            .realip_remote_addr()
+            .map(|addr| addr.to_string()),
+        header_value(header::USER_AGENT.as_str()),
+        correlation_id,
+    )
+    .in_session(session_id)
+    .impersonated_by(impersonator_id)
+}
+
+/// Extractor for the audit context of a request.
+///
+/// Simply add "audit: AuditContext" to a handler to invoke this.
+impl FromRequest for AuditContext {
+    type Config = ();
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ok(audit_from_request(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn it_reads_the_request_context() {
+        let req = TestRequest::default()
+            .insert_header((header::USER_AGENT, "guardian-test"))
+            .insert_header(("x-request-id", "abc"))
+            .to_http_request();
+
+        let audit = audit_from_request(&req);
+        assert_eq!(audit.user_agent, Some("guardian-test".to_string()));
+        assert_eq!(audit.correlation_id, "abc".to_string());
+        assert_eq!(audit.actor_id, None);
+    }
+
+    #[test]
+    fn it_generates_a_correlation_id() {
+        let audit = AuditContext::new(None, None, None, None);
+        assert_eq!(audit.correlation_id.is_empty(), false);
+        assert_eq!(audit.with_actor("1").actor_id, Some("1".to_string()));
+    }
+
+    #[test]
+    fn it_denies_impersonated_callers() {
+        let audit = AuditContext::new(Some("1".to_string()), None, None, None);
+        assert!(audit.ensure_not_impersonated().is_ok());
+
+        let audit = audit.impersonated_by(Some("2".to_string()));
+        assert!(audit.ensure_not_impersonated().is_err());
+    }
+}
@@ -1,7 +1,64 @@
+use crate::common::auth::utils::PrivateClaim;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct IdentityAccount {
     pub id: String,
     pub email: Option<String>,
     pub username: Option<String>,
     pub mobile: Option<String>,
+    /// Unix timestamp the token was issued at
+    #[serde(default)]
+    pub issued_at: i64,
+    /// The server side session of the token
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// The api key a service account authenticated with, None for session tokens
+    #[serde(default)]
+    pub api_key: Option<ApiKeyGrant>,
+    /// The tenant the account was authenticated in
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// The organization the session claims to act in, checked against its memberships
+    #[serde(default)]
+    pub organization_id: Option<String>,
+    /// The admin impersonating the account, None when the account acts itself
+    #[serde(default)]
+    pub impersonator_id: Option<String>,
+}
+
+impl IdentityAccount {
+    /// Whether an admin is acting as the account
+    pub fn is_impersonated(&self) -> bool {
+        self.impersonator_id.is_some()
+    }
+}
+
+/// The key a request was authenticated with and the scopes it grants
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiKeyGrant {
+    pub id: String,
+    pub scopes: Vec<String>,
+}
+
+impl From<PrivateClaim> for IdentityAccount {
+    fn from(claim: PrivateClaim) -> Self {
+        let issued_at = claim.issued_at();
+        let session_id = Some(claim.session_id());
+        let tenant_id = Some(claim.tenant().to_string());
+        let organization_id = claim.organization().map(|id| id.to_string());
+        let impersonator_id = claim.impersonator().map(|id| id.to_string());
+
+        IdentityAccount {
+            id: claim.sub,
+            email: claim.email,
+            username: claim.username,
+            mobile: claim.mobile,
+            issued_at,
+            session_id,
+            api_key: None,
+            tenant_id,
+            organization_id,
+            impersonator_id,
+        }
+    }
 }
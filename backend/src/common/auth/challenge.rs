@@ -0,0 +1,91 @@
+use slog::warn;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::config::{ChallengeFeaturesConfig, ChallengeProvider, CONFIG};
+use crate::integrations::challenge::challenge_integration::{
+    ChallengeVerifier, MockChallengeVerifier,
+};
+#[cfg(feature = "challenge_integration")]
+use crate::integrations::challenge::http_challenge_integration::HttpChallengeVerifier;
+
+lazy_static! {
+    /// The verifier of challenge tokens, None when challenges are off
+    pub static ref CHALLENGE_VERIFIER: Option<Box<dyn ChallengeVerifier>> =
+        challenge_verifier(&CONFIG.features.challenge);
+}
+
+fn challenge_verifier(config: &ChallengeFeaturesConfig) -> Option<Box<dyn ChallengeVerifier>> {
+    match config.provider {
+        ChallengeProvider::Off => None,
+        ChallengeProvider::Mock => Some(Box::new(MockChallengeVerifier::new(&config.secret))),
+        #[cfg(feature = "challenge_integration")]
+        ChallengeProvider::Http => Some(Box::new(HttpChallengeVerifier::new(
+            &config.verify_url,
+            &config.secret,
+        ))),
+        #[cfg(not(feature = "challenge_integration"))]
+        ChallengeProvider::Http => {
+            warn!(
+                LOGGER,
+                "[challenge] the http provider needs the challenge_integration feature"
+            );
+            None
+        }
+    }
+}
+
+/// Whether a login to an account with `failed_logins` recent failures must solve a challenge
+pub fn login_challenged(config: &ChallengeFeaturesConfig, failed_logins: usize) -> bool {
+    config.enable_login || (config.failed_logins > 0 && failed_logins >= config.failed_logins)
+}
+
+/// Fail unless `token`, sent by the client at `remote_ip`, passes the configured challenge
+///
+/// Without a verifier every request passes, a verifier that can't be reached is logged and
+/// lets the request through rather than locking everyone out
+pub async fn check_challenge(token: Option<&str>, remote_ip: Option<&str>) -> AppResult<()> {
+    let verifier = match CHALLENGE_VERIFIER.as_ref() {
+        Some(verifier) => verifier,
+        None => return Ok(()),
+    };
+    let failed = || ApiError::Forbidden("a valid challenge token is required".to_string());
+
+    let token = token.filter(|t| !t.is_empty()).ok_or_else(failed)?;
+    match verifier.verify(token, remote_ip).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(failed()),
+        Err(e) => {
+            warn!(LOGGER, "[challenge] could not verify a token: {}", e);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_challenges_risky_logins() {
+        let mut config = ChallengeFeaturesConfig {
+            failed_logins: 3,
+            ..ChallengeFeaturesConfig::default()
+        };
+        assert!(!login_challenged(&config, 2));
+        assert!(login_challenged(&config, 3));
+
+        config.failed_logins = 0;
+        assert!(!login_challenged(&config, 100));
+
+        config.enable_login = true;
+        assert!(login_challenged(&config, 0));
+    }
+
+    #[actix_rt::test]
+    async fn it_passes_everyone_without_a_verifier() {
+        // config.yaml leaves the challenge provider off
+        assert!(check_challenge(None, None).await.is_ok());
+    }
+}
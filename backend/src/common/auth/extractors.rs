@@ -1,34 +1,53 @@
 use crate::common::auth::account::IdentityAccount;
-use crate::common::auth::utils::{decode_jwt, PrivateClaim};
+use crate::common::auth::revocation::ensure_current;
+use crate::common::auth::utils::decode_jwt;
+use crate::common::errors::ApiError;
+use crate::common::tenant::{store_from_request, tenant_from_request};
 use actix_guardian_identity::RequestIdentity;
-use actix_web::{
-    dev::Payload,
-    web::{HttpRequest, HttpResponse},
-    Error, FromRequest,
-};
-use futures::future::{err, ok, Ready};
+use actix_web::{dev::Payload, web::HttpRequest, Error, FromRequest, HttpMessage};
+use futures::future::LocalBoxFuture;
+
+/// Resolve the calling account from the request identity.
+///
+/// Service accounts authenticated by their api key come first, then the identity policy reads
+/// an `Authorization: Bearer` header before the identity cookie.
+/// Returns `None` for anonymous callers, when the token can't be decoded or when it was issued
+/// in another tenant than the request's.
+pub fn account_from_request(req: &HttpRequest) -> Option<IdentityAccount> {
+    if let Some(account) = req.extensions().get::<IdentityAccount>() {
+        return Some(account.clone());
+    }
+
+    let token = RequestIdentity::get_identity(req)?;
+    let claim = decode_jwt(&token).ok()?;
+    if claim.tenant() != tenant_from_request(req) {
+        return None;
+    }
+
+    Some(claim.into())
+}
 
 /// Extractor for pulling the auth out of a request.
 ///
 /// Simply add "user: IdentityAccount" to a handler to invoke this.
+/// Tokens revoked since they were issued are rejected.
 impl FromRequest for IdentityAccount {
     type Config = ();
     type Error = Error;
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        // req.headers().get()
-        let identity = RequestIdentity::get_identity(req);
+        let account = account_from_request(req);
+        let store = store_from_request(req);
+
+        Box::pin(async move {
+            let account = account
+                .ok_or_else(|| ApiError::Unauthorized("you must be logged in".to_string()))?;
 
-        if let Some(identity) = identity {
-            let private_claim: PrivateClaim = decode_jwt(&identity).unwrap();
-            return ok(IdentityAccount {
-                id: private_claim.sub.to_string(),
-                email: private_claim.email,
-                mobile: private_claim.mobile,
-                username: private_claim.username,
-            });
-        }
-        err(HttpResponse::Unauthorized().into())
+            if let Some(store) = store {
+                ensure_current(&store, &account).await?;
+            }
+            Ok(account)
+        })
     }
 }
@@ -0,0 +1,164 @@
+use crate::common::auth::account::IdentityAccount;
+use crate::common::auth::api_key::scoped_actor;
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::data::models::oso_init::{AccountResource, Actor, OSO};
+use crate::data::stores::base_store_trait::BoxedStoreType;
+
+pub const ACCOUNT_READ: &str = "account:read";
+pub const ACCOUNT_UPDATE: &str = "account:update";
+pub const ACCOUNT_LOCK: &str = "account:lock";
+pub const ACCOUNT_UNLOCK: &str = "account:unlock";
+pub const ACCOUNT_DELETE: &str = "account:delete";
+pub const ACCOUNT_MANAGE_ROLES: &str = "account:manage_roles";
+pub const ACCOUNT_REQUIRE_NEW_PASSWORD: &str = "account:require_new_password";
+pub const ACCOUNT_RESTORE: &str = "account:restore";
+pub const ACCOUNT_HARD_DELETE: &str = "account:hard_delete";
+pub const ACCOUNT_CONFIRM: &str = "account:confirm";
+pub const ACCOUNT_AUDIT: &str = "account:audit";
+pub const ACCOUNT_MANAGE_API_KEYS: &str = "account:manage_api_keys";
+
+/// Actions left to an admin impersonating an account, the others change it
+const IMPERSONATION_ACTIONS: [&str; 2] = [ACCOUNT_READ, ACCOUNT_AUDIT];
+
+/// Load the caller's current roles and permissions from the store
+///
+/// Callers authenticated with an api key only keep those granted by its scopes, impersonated
+/// callers keep none so an impersonated admin grants nothing
+pub async fn load_actor(store: &BoxedStoreType, account: &IdentityAccount) -> AppResult<Actor> {
+    let model = store
+        .account_find_by_id(account.id.as_str())
+        .await
+        .map_err(|_| ApiError::Unauthorized("account no longer exists".to_string()))?;
+
+    match &account.api_key {
+        Some(grant) => Ok(scoped_actor(model.into(), &grant.scopes)),
+        None if account.is_impersonated() => Ok(scoped_actor(model.into(), &[])),
+        None => Ok(model.into()),
+    }
+}
+
+/// Check the polar policy for `action` by `actor` on the account `resource_id`
+pub fn is_allowed(actor: &Actor, action: &str, resource_id: &str) -> AppResult<bool> {
+    let mut oso = OSO
+        .lock()
+        .map_err(|_| ApiError::InternalServerError("policy engine unavailable".to_string()))?;
+
+    oso.is_allowed(
+        actor.clone(),
+        action.to_string(),
+        AccountResource::new(resource_id),
+    )
+    .map_err(|e| ApiError::InternalServerError(e.to_string()))
+}
+
+/// Whether `account` may attempt `action` at all, impersonated callers only read
+fn is_impersonation_allowed(account: &IdentityAccount, action: &str) -> bool {
+    !account.is_impersonated() || IMPERSONATION_ACTIONS.contains(&action)
+}
+
+/// Authorize `account` to perform `action` on the account `resource_id`
+///
+/// Returns the resolved actor or a forbidden error
+pub async fn authorize(
+    store: &BoxedStoreType,
+    account: &IdentityAccount,
+    action: &str,
+    resource_id: &str,
+) -> AppResult<Actor> {
+    if !is_impersonation_allowed(account, action) {
+        let err_message = format!("{} is not allowed while impersonating", action);
+        return Err(ApiError::Forbidden(err_message));
+    }
+    let actor = load_actor(store, account).await?;
+
+    if is_allowed(&actor, action, resource_id)? {
+        Ok(actor)
+    } else {
+        Err(ApiError::Forbidden(format!("{} is not allowed", action)))
+    }
+}
+
+/// Require `account` to hold `role`
+pub async fn require_role(
+    store: &BoxedStoreType,
+    account: &IdentityAccount,
+    role: &str,
+) -> AppResult<Actor> {
+    let actor = load_actor(store, account).await?;
+
+    if actor.has_role(role.to_string()) {
+        Ok(actor)
+    } else {
+        Err(ApiError::Forbidden(format!("{} role is required", role)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::models::oso_init::ADMIN_ROLE;
+
+    #[test]
+    fn it_checks_the_policy() {
+        let actor = Actor {
+            id: "a".to_string(),
+            roles: vec![],
+            permissions: vec![ACCOUNT_LOCK.to_string()],
+        };
+        assert_eq!(is_allowed(&actor, ACCOUNT_LOCK, "b").unwrap(), true);
+        assert_eq!(is_allowed(&actor, ACCOUNT_DELETE, "b").unwrap(), false);
+        assert_eq!(is_allowed(&actor, ACCOUNT_DELETE, "a").unwrap(), true);
+
+        let admin = Actor {
+            id: "a".to_string(),
+            roles: vec![ADMIN_ROLE.to_string()],
+            permissions: vec![],
+        };
+        assert_eq!(is_allowed(&admin, ACCOUNT_MANAGE_ROLES, "b").unwrap(), true);
+    }
+
+    #[test]
+    fn it_keeps_roles_out_of_self_management() {
+        let actor = Actor {
+            id: "a".to_string(),
+            roles: vec![],
+            permissions: vec![ACCOUNT_MANAGE_ROLES.to_string()],
+        };
+        assert_eq!(is_allowed(&actor, ACCOUNT_MANAGE_ROLES, "b").unwrap(), true);
+        assert_eq!(
+            is_allowed(&actor, ACCOUNT_MANAGE_ROLES, "a").unwrap(),
+            false
+        );
+
+        let admin = Actor {
+            id: "a".to_string(),
+            roles: vec![ADMIN_ROLE.to_string()],
+            permissions: vec![],
+        };
+        assert_eq!(is_allowed(&admin, ACCOUNT_MANAGE_ROLES, "a").unwrap(), true);
+    }
+
+    #[test]
+    fn it_keeps_impersonation_read_only() {
+        let mut account = IdentityAccount {
+            id: "a".to_string(),
+            email: None,
+            username: None,
+            mobile: None,
+            issued_at: 0,
+            session_id: None,
+            api_key: None,
+            tenant_id: None,
+            organization_id: None,
+            impersonator_id: None,
+        };
+        assert!(is_impersonation_allowed(&account, ACCOUNT_DELETE));
+
+        account.impersonator_id = Some("admin".to_string());
+        assert!(is_impersonation_allowed(&account, ACCOUNT_READ));
+        assert!(is_impersonation_allowed(&account, ACCOUNT_AUDIT));
+        assert!(!is_impersonation_allowed(&account, ACCOUNT_DELETE));
+        assert!(!is_impersonation_allowed(&account, ACCOUNT_MANAGE_ROLES));
+    }
+}
@@ -1,6 +1,4 @@
-use crate::common::auth::utils::{decode_jwt, PrivateClaim};
-use crate::common::errors::ApiError;
-use actix_guardian_identity::RequestIdentity;
+use crate::common::auth::extractors::account_from_request;
 use actix_service::{Service, Transform};
 use actix_web::body::MessageBody;
 use actix_web::{
@@ -52,9 +50,7 @@ where
     }
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let identity = RequestIdentity::get_identity(&req).unwrap_or_else(|| "".into());
-        let private_claim: Result<PrivateClaim, ApiError> = decode_jwt(&identity);
-        let is_logged_in = private_claim.is_ok();
+        let is_logged_in = account_from_request(req.request()).is_some();
         let unauthorized = !is_logged_in && req.path() != "/api/v1/session/create";
 
         if unauthorized {
@@ -2,6 +2,11 @@ pub use self::middleware::AuthMiddleware;
 pub use self::middleware::Authorizer;
 
 pub mod account;
+pub mod api_key;
+pub mod challenge;
 pub mod extractors;
+pub mod guard;
 mod middleware;
+pub mod password_policy;
+pub mod revocation;
 pub mod utils;
@@ -0,0 +1,235 @@
+use chrono::{DateTime, Duration, Utc};
+use slog::warn;
+use zxcvbn::zxcvbn;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::config::{BreachEnforcement, BreachedPasswordConfig, PasswordPolicyConfig, CONFIG};
+use crate::integrations::breach::breach_integration::{
+    times_breached, LocalPasswordBreachIntegration, PasswordBreachIntegration,
+};
+#[cfg(feature = "hibp_integration")]
+use crate::integrations::breach::hibp_integration::HibpPasswordBreachIntegration;
+
+lazy_static! {
+    /// The breached password corpus new passwords are checked against, None when unchecked
+    pub static ref BREACH_INTEGRATION: Option<Box<dyn PasswordBreachIntegration>> =
+        breach_integration(&CONFIG.security.breached_passwords);
+}
+
+/// Where a password stands in its rotation
+#[derive(Debug, PartialEq)]
+pub enum PasswordAge {
+    Current,
+    /// The password expires at the given time, inside the warning window
+    ExpiresSoon(DateTime<Utc>),
+    Expired,
+}
+
+/// The rules of `policy` that `password` breaks
+pub fn policy_violations(policy: &PasswordPolicyConfig, password: &str) -> Vec<String> {
+    let has = |class: fn(char) -> bool| password.chars().any(class);
+    let mut errors = vec![];
+
+    if password.chars().count() < policy.min_length {
+        errors.push(format!(
+            "Password must be at least {} characters",
+            policy.min_length
+        ));
+    }
+    if policy.require_lowercase && !has(char::is_lowercase) {
+        errors.push("Password must contain a lowercase letter".to_string());
+    }
+    if policy.require_uppercase && !has(char::is_uppercase) {
+        errors.push("Password must contain an uppercase letter".to_string());
+    }
+    if policy.require_digit && !has(|c| c.is_ascii_digit()) {
+        errors.push("Password must contain a digit".to_string());
+    }
+    if policy.require_symbol && !has(|c| !c.is_alphanumeric()) {
+        errors.push("Password must contain a symbol".to_string());
+    }
+
+    errors
+}
+
+/// Check a new `password` against the configured policy
+///
+/// `user_inputs` are the username and email of the account, zxcvbn scores passwords built from
+/// them as weak when the policy enables it. `strength` is the minimum score of the tenant,
+/// checked here when it's above the one the request validators enforce
+pub fn check_password(
+    password: &str,
+    user_inputs: &[Option<&str>],
+    strength: i64,
+) -> AppResult<()> {
+    let policy = &CONFIG.security.password_policy;
+    let mut errors = policy_violations(policy, password);
+
+    if policy.user_inputs || strength > CONFIG.security.password_strength {
+        let inputs: Vec<&str> = if policy.user_inputs {
+            user_inputs.iter().flatten().copied().collect()
+        } else {
+            vec![]
+        };
+        let weak = zxcvbn(password, &inputs).map_or(true, |e| e.score() < strength as u8);
+        if weak {
+            errors.push("Password is not strong enough".to_string());
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::ValidationError(errors))
+    }
+}
+
+fn breach_integration(
+    config: &BreachedPasswordConfig,
+) -> Option<Box<dyn PasswordBreachIntegration>> {
+    if config.enforcement == BreachEnforcement::Off {
+        return None;
+    }
+
+    #[cfg(feature = "hibp_integration")]
+    {
+        if !config.api_url.is_empty() {
+            return Some(Box::new(HibpPasswordBreachIntegration::new(
+                &config.api_url,
+            )));
+        }
+    }
+
+    if config.range_path.is_empty() {
+        None
+    } else {
+        Some(Box::new(LocalPasswordBreachIntegration::new(
+            &config.range_path,
+        )))
+    }
+}
+
+/// Apply the enforcement of `config` to a password seen `count` times in the corpus
+fn breach_verdict(config: &BreachedPasswordConfig, count: u64) -> AppResult<()> {
+    if count < config.min_occurrences.max(1) {
+        return Ok(());
+    }
+
+    match config.enforcement {
+        BreachEnforcement::Reject => Err(ApiError::ValidationError(vec![
+            "Password has appeared in a data breach, please choose another".to_string(),
+        ])),
+        _ => {
+            warn!(LOGGER, "[breach] accepted a password seen {} times", count);
+            Ok(())
+        }
+    }
+}
+
+/// Check a new `password` against the breached password corpus
+///
+/// A corpus that can't be read is logged and lets the password through
+pub async fn check_not_breached(password: &str) -> AppResult<()> {
+    let integration = match BREACH_INTEGRATION.as_ref() {
+        Some(integration) => integration,
+        None => return Ok(()),
+    };
+
+    match times_breached(integration.as_ref(), password).await {
+        Ok(count) => breach_verdict(&CONFIG.security.breached_passwords, count),
+        Err(e) => {
+            warn!(LOGGER, "[breach] could not check a password: {}", e);
+            Ok(())
+        }
+    }
+}
+
+/// How old a password last changed at `changed_at` is at `now`
+pub fn password_age(
+    policy: &PasswordPolicyConfig,
+    changed_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> PasswordAge {
+    if policy.max_age_days <= 0 {
+        return PasswordAge::Current;
+    }
+
+    let expires_at = changed_at + Duration::days(policy.max_age_days);
+    if expires_at <= now {
+        PasswordAge::Expired
+    } else if expires_at - Duration::days(policy.warning_days) <= now {
+        PasswordAge::ExpiresSoon(expires_at)
+    } else {
+        PasswordAge::Current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_policy() -> PasswordPolicyConfig {
+        PasswordPolicyConfig {
+            max_age_days: 90,
+            warning_days: 14,
+            min_length: 8,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..PasswordPolicyConfig::default()
+        }
+    }
+
+    #[test]
+    fn it_lists_the_broken_rules() {
+        let policy = get_policy();
+        assert_eq!(
+            policy_violations(&policy, "abc"),
+            vec![
+                "Password must be at least 8 characters".to_string(),
+                "Password must contain an uppercase letter".to_string(),
+                "Password must contain a digit".to_string(),
+                "Password must contain a symbol".to_string(),
+            ]
+        );
+        assert!(policy_violations(&policy, "Abcdef1!").is_empty());
+        assert!(policy_violations(&PasswordPolicyConfig::default(), "abc").is_empty());
+    }
+
+    #[test]
+    fn it_ages_passwords() {
+        let policy = get_policy();
+        let now = Utc::now();
+        let age = |days| password_age(&policy, now - Duration::days(days), now);
+
+        assert_eq!(age(100), PasswordAge::Expired);
+        assert_eq!(age(80), PasswordAge::ExpiresSoon(now + Duration::days(10)));
+        assert_eq!(age(0), PasswordAge::Current);
+
+        let no_expiry = PasswordPolicyConfig::default();
+        assert_eq!(
+            password_age(&no_expiry, now - Duration::days(1000), now),
+            PasswordAge::Current
+        );
+    }
+
+    #[test]
+    fn it_enforces_breached_passwords() {
+        let mut config = BreachedPasswordConfig {
+            enforcement: BreachEnforcement::Reject,
+            min_occurrences: 10,
+            ..BreachedPasswordConfig::default()
+        };
+        assert!(breach_verdict(&config, 9).is_ok());
+        assert!(breach_verdict(&config, 10).is_err());
+
+        config.enforcement = BreachEnforcement::Warn;
+        assert!(breach_verdict(&config, 10).is_ok());
+
+        config.min_occurrences = 0;
+        assert!(breach_verdict(&config, 0).is_ok());
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::common::auth::account::IdentityAccount;
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::data::models::session_model::SessionModel;
+use crate::data::stores::base_store_trait::BoxedStoreType;
+
+/// Seconds between two updates of the last seen time of a session
+const SESSION_TOUCH_SECONDS: i64 = 60;
+
+/// Tokens issued before the last password change are revoked
+pub fn is_revoked(password_changed_at: Option<DateTime<Utc>>, issued_at: i64) -> bool {
+    password_changed_at.map_or(false, |changed| changed.timestamp() > issued_at)
+}
+
+fn session_ended() -> ApiError {
+    ApiError::Unauthorized("your session has ended, please login again".to_string())
+}
+
+/// Whether the session of a token of `account_id` still accepts it at `now`
+fn is_session_current(session: &SessionModel, account_id: &str, now: DateTime<Utc>) -> bool {
+    session.account_id == account_id && session.is_active(now)
+}
+
+/// Fail when the token of `account` has been revoked since it was issued
+///
+/// A token is revoked by a later password change or when its session is revoked or gone.
+/// Api key callers have no session, their key was checked when the request came in.
+pub async fn ensure_current(store: &BoxedStoreType, account: &IdentityAccount) -> AppResult<()> {
+    if account.api_key.is_some() {
+        return Ok(());
+    }
+
+    let model = store
+        .account_find_by_id(&account.id)
+        .await
+        .map_err(|_| ApiError::Unauthorized("account no longer exists".to_string()))?;
+
+    if is_revoked(model.password_changed_at, account.issued_at) {
+        return Err(session_ended());
+    }
+
+    let session_id = account.session_id.as_deref().ok_or_else(session_ended)?;
+    let session = store
+        .session_find_by_id(session_id)
+        .await
+        .map_err(|_| session_ended())?;
+    let now = Utc::now();
+    if !is_session_current(&session, &account.id, now) {
+        return Err(session_ended());
+    }
+
+    if now - session.last_seen_at > Duration::seconds(SESSION_TOUCH_SECONDS) {
+        let _ = store.session_touch(session_id, None).await;
+    }
+    Ok(())
+}
+
+/// Keep `account` only while its token is current
+pub async fn current_account(
+    store: &BoxedStoreType,
+    account: Option<IdentityAccount>,
+) -> Option<IdentityAccount> {
+    match account {
+        Some(a) if ensure_current(store, &a).await.is_ok() => Some(a),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_revokes_tokens_issued_before_a_password_change() {
+        let changed = Utc::now();
+        let before = (changed - Duration::minutes(5)).timestamp();
+        let after = changed.timestamp();
+
+        assert_eq!(is_revoked(Some(changed), before), true);
+        assert_eq!(is_revoked(Some(changed), after), false);
+        assert_eq!(is_revoked(None, before), false);
+    }
+
+    #[test]
+    fn it_only_accepts_active_sessions_of_the_account() {
+        let now = Utc::now();
+        let mut session = SessionModel {
+            id: "0d5f4e2a-8c1b-4f3e-a6d7-9b8c7d6e5f4a".to_string(),
+            account_id: "1234".to_string(),
+            ip: None,
+            user_agent: None,
+            created_at: now,
+            last_seen_at: now,
+            expires_at: now + Duration::hours(1),
+            revoked_at: None,
+            organization_id: None,
+            impersonated_by: None,
+        };
+        assert!(is_session_current(&session, "1234", now));
+        assert!(!is_session_current(&session, "5678", now));
+
+        session.revoked_at = Some(now);
+        assert!(!is_session_current(&session, "1234", now));
+    }
+}
@@ -1,8 +1,20 @@
 use crate::common::errors::ApiError;
 use crate::config::CONFIG;
 use argon2rs::argon2i_simple;
-use chrono::{Duration as ChronDur, Utc};
+use chrono::{DateTime, Duration as ChronDur, TimeZone, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use openssl::memcmp;
+
+/// Scope of the token handed out to change an expired password
+pub const PASSWORD_CHANGE_SCOPE: &str = "password_change";
+/// Scope of the token that allows checking whether an identity is taken
+pub const SIGNUP_SCOPE: &str = "signup";
+
+/// The account acting through a token issued for another one, the `act` claim of RFC 8693
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ActorClaim {
+    pub sub: String,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct PrivateClaim {
@@ -15,6 +27,18 @@ pub struct PrivateClaim {
     iat: i64,
     iss: String,
     jti: uuid::Uuid,
+    /// Restricts the token to one operation, scoped tokens are not sessions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    /// The tenant the token was issued in, tokens without one belong to the default tenant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tid: Option<String>,
+    /// The organization the session acts in, a hint rechecked against the memberships
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    org: Option<String>,
+    /// The admin impersonating `sub`, tokens carrying it can't make sensitive changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    act: Option<ActorClaim>,
 }
 
 impl PrivateClaim {
@@ -34,8 +58,101 @@ impl PrivateClaim {
             iat: Utc::now().timestamp(),
             iss: CONFIG.security.jwt_issuer.to_string(),
             jti: uuid::Uuid::new_v4(),
+            scope: None,
+            tid: None,
+            org: None,
+            act: None,
+        }
+    }
+
+    /// A claim restricted to `scope` that expires after `minutes`
+    pub fn scoped(sub: String, scope: &str, minutes: i64) -> Self {
+        Self {
+            exp: (Utc::now() + ChronDur::minutes(minutes)).timestamp(),
+            scope: Some(scope.to_string()),
+            ..Self::new(sub, None, None, None)
+        }
+    }
+
+    /// A short lived claim that only allows changing the expired password of `user_id`
+    pub fn password_change(user_id: String) -> Self {
+        let minutes = CONFIG.security.password_policy.change_token_minutes;
+        Self::scoped(user_id, PASSWORD_CHANGE_SCOPE, minutes)
+    }
+
+    /// The same claim tied to the server side session `session_id`, used as its `jti`
+    pub fn for_session(self, session_id: uuid::Uuid) -> Self {
+        Self {
+            jti: session_id,
+            ..self
+        }
+    }
+
+    /// The same claim issued in the tenant `tenant`
+    pub fn for_tenant(self, tenant: &str) -> Self {
+        Self {
+            tid: Some(tenant.to_string()),
+            ..self
         }
     }
+
+    /// The tenant the token was issued in
+    pub fn tenant(&self) -> &str {
+        self.tid
+            .as_deref()
+            .unwrap_or_else(|| CONFIG.tenancy.default_tenant.as_str())
+    }
+
+    /// The same claim acting in the organization `organization`
+    pub fn for_organization(self, organization: Option<&str>) -> Self {
+        Self {
+            org: organization.map(|id| id.to_string()),
+            ..self
+        }
+    }
+
+    /// The organization the session acts in
+    pub fn organization(&self) -> Option<&str> {
+        self.org.as_deref()
+    }
+
+    /// The same claim issued to `admin_id` impersonating `sub`
+    pub fn impersonated_by(self, admin_id: &str) -> Self {
+        Self {
+            act: Some(ActorClaim {
+                sub: admin_id.to_string(),
+            }),
+            ..self
+        }
+    }
+
+    /// The admin impersonating `sub`, None for tokens used by the account itself
+    pub fn impersonator(&self) -> Option<&str> {
+        self.act.as_ref().map(|act| act.sub.as_str())
+    }
+
+    /// The same claim expiring at `expires_at`
+    pub fn expiring_at(self, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            exp: expires_at.timestamp(),
+            ..self
+        }
+    }
+
+    /// When the token expires
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        Utc.timestamp(self.exp, 0)
+    }
+
+    /// Unix timestamp the token was issued at
+    pub fn issued_at(&self) -> i64 {
+        self.iat
+    }
+
+    /// Id of the session the token belongs to
+    pub fn session_id(&self) -> String {
+        self.jti.to_string()
+    }
 }
 
 /// Create a json appstate token (JWT)
@@ -45,14 +162,37 @@ pub fn create_jwt(private_claim: PrivateClaim) -> Result<String, ApiError> {
         .map_err(|e| ApiError::CannotEncodeJwtToken(e.to_string()))
 }
 
-/// Decode a json appstate token (JWT)
-pub fn decode_jwt(token: &str) -> Result<PrivateClaim, ApiError> {
+fn decode_claim(token: &str) -> Result<PrivateClaim, ApiError> {
     let decoding_key = DecodingKey::from_secret(&CONFIG.security.jwt_key.as_ref());
     decode::<PrivateClaim>(token, &decoding_key, &Validation::default())
         .map(|data| data.claims)
         .map_err(|e| ApiError::CannotDecodeJwtToken(e.to_string()))
 }
 
+/// Decode a json appstate token (JWT), scoped tokens are rejected
+pub fn decode_jwt(token: &str) -> Result<PrivateClaim, ApiError> {
+    let claim = decode_claim(token)?;
+    match claim.scope {
+        None => Ok(claim),
+        Some(_) => Err(ApiError::CannotDecodeJwtToken(
+            "token is not a session token".to_string(),
+        )),
+    }
+}
+
+/// Decode a token restricted to `scope`
+pub fn decode_scoped_jwt(token: &str, scope: &str) -> Result<PrivateClaim, ApiError> {
+    let claim = decode_claim(token)?;
+    if claim.scope.as_deref() == Some(scope) {
+        Ok(claim)
+    } else {
+        Err(ApiError::CannotDecodeJwtToken(format!(
+            "token is not scoped to {}",
+            scope
+        )))
+    }
+}
+
 /// Encrypt a password
 ///
 /// Uses the argon2i algorithm.
@@ -64,6 +204,16 @@ pub fn hash(password: &str) -> String {
         .collect()
 }
 
+/// Compare two secrets in constant time, only their length can leak
+pub fn secrets_match(secret: &str, candidate: &str) -> bool {
+    secret.len() == candidate.len() && memcmp::eq(secret.as_bytes(), candidate.as_bytes())
+}
+
+/// Compare `password` with a stored hash in constant time
+pub fn verify_password(password: &str, hashed: &str) -> bool {
+    secrets_match(hashed, &hash(password))
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -83,6 +233,14 @@ pub mod tests {
         assert_eq!(hashed, hashed_again);
     }
 
+    #[test]
+    fn it_verifies_a_password() {
+        let hashed = hash("password");
+        assert!(verify_password("password", &hashed));
+        assert!(!verify_password("passwore", &hashed));
+        assert!(!verify_password("password", ""));
+    }
+
     #[test]
     fn it_creates_a_jwt() {
         let email = Option::from("test@test.com".to_string());
@@ -99,4 +257,28 @@ pub mod tests {
         let decoded = decode_jwt(&jwt).unwrap();
         assert_eq!(private_claim, decoded);
     }
+
+    #[test]
+    fn it_keeps_scoped_jwts_out_of_sessions() {
+        let jwt = create_jwt(PrivateClaim::password_change("3467".to_string())).unwrap();
+        assert!(decode_jwt(&jwt).is_err());
+        assert_eq!(
+            decode_scoped_jwt(&jwt, PASSWORD_CHANGE_SCOPE).unwrap().sub,
+            "3467"
+        );
+
+        let session = create_jwt(PrivateClaim::new("3467".to_string(), None, None, None)).unwrap();
+        assert!(decode_scoped_jwt(&session, PASSWORD_CHANGE_SCOPE).is_err());
+    }
+
+    #[test]
+    fn it_names_the_impersonating_admin() {
+        let claim = PrivateClaim::new("3467".to_string(), None, None, None);
+        assert_eq!(claim.impersonator(), None);
+
+        let jwt = create_jwt(claim.impersonated_by("1234")).unwrap();
+        let decoded = decode_jwt(&jwt).unwrap();
+        assert_eq!(decoded.impersonator(), Some("1234"));
+        assert_eq!(decoded.sub, "3467");
+    }
 }
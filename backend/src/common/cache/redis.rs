@@ -28,6 +28,29 @@ pub async fn delete<'a>(redis: Cache, key: &'a str) -> Result<String, ApiError>
     send(redis, command).await
 }
 
+/// Run a lua `script` in redis and return its integer reply
+pub async fn eval(
+    redis: &Cache,
+    script: &str,
+    keys: &[&str],
+    args: &[String],
+) -> Result<i64, ApiError> {
+    let mut command: Vec<RespValue> =
+        vec!["EVAL".into(), script.into(), keys.len().to_string().into()];
+    command.extend(keys.iter().map(|&key| key.into()));
+    command.extend(args.iter().map(|arg| arg.as_str().into()));
+
+    let response = redis
+        .send(Command(RespValue::Array(command)))
+        .await
+        .map_err(|_| ApiError::CacheError("Could not send EVAL command to Redis".to_string()))?;
+    match response {
+        Ok(RespValue::Integer(value)) => Ok(value),
+        Ok(message) => Err(ApiError::CacheError(format!("{:?}", message))),
+        Err(message) => Err(ApiError::CacheError(format!("{:?}", message))),
+    }
+}
+
 /// Send a command to the redis actor
 async fn send<'a>(redis: Cache, command: RespValue) -> Result<String, ApiError> {
     let error_message = format!("Could not send {:?} command to Redis", command);
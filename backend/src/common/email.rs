@@ -0,0 +1,95 @@
+//! Outgoing email crate
+use slog::warn;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::config::{EmailIntegrationConfig, EmailProvider, IntegrationConfig, CONFIG};
+use crate::integrations::email::email_integration::{
+    EmailIntegration, LogEmailIntegration, MessageOption,
+};
+#[cfg(feature = "sendgrid_integration")]
+use crate::integrations::email::sendgrid_integration::SendgridEmailIntegration;
+
+lazy_static! {
+    /// The client delivering emails, None when email is off
+    pub static ref EMAIL_INTEGRATION: Option<Box<dyn EmailIntegration>> =
+        email_integration(&CONFIG.integration);
+}
+
+fn email_integration(config: &IntegrationConfig) -> Option<Box<dyn EmailIntegration>> {
+    match config.email.provider {
+        EmailProvider::Off => None,
+        EmailProvider::Log => Some(Box::new(LogEmailIntegration)),
+        #[cfg(feature = "sendgrid_integration")]
+        EmailProvider::Sendgrid => Some(Box::new(SendgridEmailIntegration::new(
+            &config.sendgrid.api_key,
+        ))),
+        #[cfg(not(feature = "sendgrid_integration"))]
+        EmailProvider::Sendgrid => {
+            warn!(
+                LOGGER,
+                "[email] the sendgrid provider needs the sendgrid_integration feature"
+            );
+            None
+        }
+    }
+}
+
+/// A plain text message from the configured sender
+fn message(config: &EmailIntegrationConfig, to: &str, subject: &str, body: &str) -> MessageOption {
+    MessageOption {
+        to: to.to_string(),
+        from: config.from.clone(),
+        from_name: config.from_name.clone(),
+        content: Some(body.to_string()),
+        content_type: "text/plain".to_string(),
+        subject: Some(subject.to_string()),
+        headers: None,
+    }
+}
+
+/// Send a plain text email to `to` with `integration`, failing when no email client is
+/// configured
+pub async fn send_email_with(
+    integration: Option<&dyn EmailIntegration>,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> AppResult<()> {
+    let integration = integration.ok_or_else(|| {
+        ApiError::InternalServerError("email delivery is not configured".to_string())
+    })?;
+
+    let opt = message(&CONFIG.integration.email, to, subject, body);
+    integration.send_message(opt).await.map_err(|e| {
+        warn!(LOGGER, "[email] could not send a message: {}", e);
+        e
+    })
+}
+
+/// Send a plain text email to `to` with the configured email client
+pub async fn send_email(to: &str, subject: &str, body: &str) -> AppResult<()> {
+    send_email_with(EMAIL_INTEGRATION.as_deref(), to, subject, body).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_a_plain_text_message() {
+        let config = EmailIntegrationConfig {
+            provider: EmailProvider::Log,
+            from: "no-reply@example.com".to_string(),
+            from_name: Some("Guardian".to_string()),
+        };
+
+        let opt = message(&config, "ann@example.com", "Hello", "Hi Ann");
+        assert_eq!(opt.from, "no-reply@example.com");
+        assert_eq!(opt.to, "ann@example.com");
+        assert_eq!(opt.subject.as_deref(), Some("Hello"));
+        assert_eq!(opt.content.as_deref(), Some("Hi Ann"));
+        assert_eq!(opt.content_type, "text/plain");
+    }
+}
@@ -1,7 +1,7 @@
 use actix_web::dev::HttpResponseBuilder;
 use actix_web::{
     error::{BlockingError, ResponseError},
-    http::StatusCode,
+    http::{header, StatusCode},
     HttpResponse,
 };
 use celery::error::CeleryError;
@@ -26,17 +26,24 @@ pub enum ApiError {
     Gone(String),
     PaymentRequired(String),
     PayloadTooLarge(String),
-    TooManyRequests(String),
+    /// The message and the seconds to wait before retrying, sent as `Retry-After`
+    #[display(fmt = "{}", _0)]
+    TooManyRequests(String, u64),
     DatabaseError(String),
     ParseError(String),
     PoolError(String),
     #[display(fmt = "")]
     ValidationError(Vec<String>),
     Unauthorized(String),
+    Forbidden(String),
+    /// A feature the configured datastore doesn't provide
+    NotImplemented(String),
 }
 
+impl std::error::Error for ApiError {}
+
 /// User-friendly error messages
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, paperclip::actix::Apiv2Schema)]
 pub struct ErrorResponse {
     errors: Vec<String>,
 }
@@ -49,11 +56,13 @@ impl ResponseError for ApiError {
             ApiError::NotFound(_message) => StatusCode::NOT_FOUND,
             ApiError::ValidationError(_errors) => StatusCode::UNPROCESSABLE_ENTITY,
             ApiError::Unauthorized(_error) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_error) => StatusCode::FORBIDDEN,
             ApiError::Conflict(_message) => StatusCode::CONFLICT,
             ApiError::Gone(_errors) => StatusCode::GONE,
             ApiError::PaymentRequired(_error) => StatusCode::PAYMENT_REQUIRED,
             ApiError::PayloadTooLarge(_error) => StatusCode::PAYLOAD_TOO_LARGE,
-            ApiError::TooManyRequests(_error) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::TooManyRequests(_error, _) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::NotImplemented(_error) => StatusCode::NOT_IMPLEMENTED,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -75,6 +84,9 @@ impl ResponseError for ApiError {
             ApiError::Unauthorized(error) => {
                 HttpResponseBuilder::new(self.status_code()).body::<String>(error.into())
             }
+            ApiError::Forbidden(error) => {
+                HttpResponseBuilder::new(self.status_code()).body::<String>(error.into())
+            }
             ApiError::Conflict(message) => {
                 HttpResponseBuilder::new(self.status_code()).body::<String>(message.into())
                 // HttpResponse::Conflict().json(message.into())
@@ -91,14 +103,18 @@ impl ResponseError for ApiError {
                 HttpResponseBuilder::new(self.status_code()).body::<String>(error.into())
                 // HttpResponse::PayloadTooLarge().json(error.into())
             }
-            ApiError::TooManyRequests(error) => {
-                HttpResponseBuilder::new(self.status_code()).body::<String>(error.into())
-                // HttpResponse::TooManyRequests().json(error.into())
+            ApiError::TooManyRequests(error, retry_after) => {
+                HttpResponseBuilder::new(self.status_code())
+                    .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                    .body::<String>(error.into())
             }
             ApiError::DatabaseError(error) => {
                 HttpResponseBuilder::new(self.status_code()).body::<String>(error.into())
                 // HttpResponse::TooManyRequests().json(error.into())
             }
+            ApiError::NotImplemented(error) => {
+                HttpResponseBuilder::new(self.status_code()).body::<String>(error.into())
+            }
             _ => HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR),
         }
     }
@@ -189,3 +205,70 @@ impl From<CeleryError> for ApiError {
         ApiError::InternalServerError(error.to_string())
     }
 }
+
+/// Convert tonic transport Error to ApiErrors
+#[cfg(feature = "server-grpc")]
+impl From<tonic::transport::Error> for ApiError {
+    fn from(error: tonic::transport::Error) -> ApiError {
+        ApiError::InternalServerError(error.to_string())
+    }
+}
+
+/// Automatically convert ApiErrors to gRPC status codes
+#[cfg(feature = "server-grpc")]
+impl From<ApiError> for tonic::Status {
+    fn from(error: ApiError) -> tonic::Status {
+        use tonic::{Code, Status};
+
+        match error {
+            ApiError::BadRequest(error) => Status::new(Code::InvalidArgument, error),
+            ApiError::NotFound(message) => Status::new(Code::NotFound, message),
+            ApiError::ValidationError(errors) => {
+                Status::new(Code::InvalidArgument, errors.join(", "))
+            }
+            ApiError::Unauthorized(error) => Status::new(Code::Unauthenticated, error),
+            ApiError::CannotDecodeJwtToken(error) => Status::new(Code::Unauthenticated, error),
+            ApiError::Forbidden(error) => Status::new(Code::PermissionDenied, error),
+            ApiError::Conflict(message) => Status::new(Code::AlreadyExists, message),
+            ApiError::Gone(error) => Status::new(Code::NotFound, error),
+            ApiError::RequestTimeout(error) => Status::new(Code::DeadlineExceeded, error),
+            ApiError::PaymentRequired(error) => Status::new(Code::FailedPrecondition, error),
+            ApiError::PayloadTooLarge(error) => Status::new(Code::OutOfRange, error),
+            ApiError::TooManyRequests(error, _) => Status::new(Code::ResourceExhausted, error),
+            ApiError::DatabaseError(error) => Status::new(Code::Unavailable, error),
+            ApiError::NotImplemented(error) => Status::new(Code::Unimplemented, error),
+            _ => Status::new(Code::Internal, "internal server error"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "server-grpc")]
+    #[test]
+    fn it_maps_api_errors_to_grpc_status() {
+        let status: tonic::Status = ApiError::NotFound("account not found".to_string()).into();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        assert_eq!(status.message(), "account not found");
+
+        let status: tonic::Status =
+            ApiError::ValidationError(vec!["a".to_string(), "b".to_string()]).into();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(status.message(), "a, b");
+
+        let status: tonic::Status = ApiError::Unauthorized("nope".to_string()).into();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let status: tonic::Status = ApiError::NotImplemented("sql".to_string()).into();
+        assert_eq!(status.code(), tonic::Code::Unimplemented);
+    }
+
+    #[test]
+    fn it_answers_not_implemented_with_501() {
+        let error = ApiError::NotImplemented("sql".to_string());
+        assert_eq!(error.status_code(), StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(error.error_response().status(), StatusCode::NOT_IMPLEMENTED);
+    }
+}
@@ -0,0 +1,126 @@
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use actix_service::{Service, Transform};
+use actix_web::body::MessageBody;
+use actix_web::http::header;
+use actix_web::{
+    dev::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage, ResponseError,
+};
+use futures::{
+    future::{ok, Ready},
+    Future,
+};
+
+use crate::common::auth::api_key::authenticate_api_key;
+use crate::common::errors::ApiError;
+use crate::common::tenant::store_from_request;
+
+/// Authorization scheme of the requests of service accounts
+pub const API_KEY_SCHEME: &str = "ApiKey";
+
+/// The key of an `Authorization: ApiKey <key>` header, the scheme is matched case insensitively
+pub fn api_key_from_header(value: &str) -> Option<&str> {
+    let scheme = value.get(..API_KEY_SCHEME.len())?;
+    let rest = value.get(API_KEY_SCHEME.len()..)?;
+
+    if scheme.eq_ignore_ascii_case(API_KEY_SCHEME) && rest.starts_with(' ') {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+/// Authenticates the requests of service accounts carrying an api key
+///
+/// The resolved `IdentityAccount` is stored in the request extensions where the extractors
+/// find it before any session identity. Keys are looked up in the tenant of the request.
+/// A request with an invalid key is rejected with a 401 rather than served anonymously.
+pub struct ApiKeyAuthentication;
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyAuthentication
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ApiKeyMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ApiKeyMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct ApiKeyMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyMiddleware<S>
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let key = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(api_key_from_header)
+            .map(|key| key.to_string());
+
+        Box::pin(async move {
+            let key = match key {
+                Some(key) => key,
+                None => return service.call(req).await,
+            };
+
+            let store = store_from_request(req.request());
+            let account = match store {
+                Some(store) => authenticate_api_key(&store, &key).await,
+                None => Err(ApiError::InternalServerError(
+                    "account store unavailable".to_string(),
+                )),
+            };
+
+            match account {
+                Ok(account) => {
+                    req.extensions_mut().insert(account);
+                    service.call(req).await
+                }
+                Err(e) => Ok(req.into_response(e.error_response().into_body())),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_the_api_key_scheme() {
+        assert_eq!(api_key_from_header("ApiKey gk_abc_def"), Some("gk_abc_def"));
+        assert_eq!(api_key_from_header("apikey gk_abc_def"), Some("gk_abc_def"));
+        assert_eq!(api_key_from_header("ApiKeygk_abc_def"), None);
+        assert_eq!(api_key_from_header("Bearer gk_abc_def"), None);
+        assert_eq!(api_key_from_header("Api"), None);
+    }
+}
@@ -0,0 +1,303 @@
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use actix_guardian_identity::{BearerIdentityPolicy, Identity};
+use actix_service::{Service, Transform};
+use actix_web::body::MessageBody;
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::http::Method;
+use actix_web::{
+    dev::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage, ResponseError,
+};
+use futures::{
+    future::{ok, Ready},
+    Future,
+};
+use openssl::memcmp;
+use rand::{distributions::Alphanumeric, Rng};
+
+use crate::common::auth::account::IdentityAccount;
+use crate::common::errors::ApiError;
+use crate::common::utils::cookie_utils::same_site;
+use crate::config::{CsrfConfig, SecurityConfig};
+
+/// Length of the random csrf tokens
+const CSRF_TOKEN_LEN: usize = 32;
+
+/// Double submit csrf protection of the requests authenticated by the session cookie
+///
+/// Every client gets a random token in a script readable cookie and has to echo it in the
+/// configured header on state changing requests carrying the session cookie. Requests without
+/// the session cookie, or identified by a bearer token or an api key, aren't affected, a cross
+/// site form can't send either. Any other `Authorization` header is ignored, the cookie still
+/// identifies those requests. Runs inside the identity and api key middleware, and the token
+/// is replaced whenever the session cookie changes.
+#[derive(Clone)]
+pub struct CsrfProtection {
+    config: Rc<CsrfConfig>,
+    session_name: Rc<String>,
+    secure: bool,
+    same_site: Option<SameSite>,
+}
+
+impl CsrfProtection {
+    pub fn new(security: &SecurityConfig) -> Self {
+        CsrfProtection {
+            config: Rc::new(security.csrf.clone()),
+            session_name: Rc::new(security.session_name.clone()),
+            secure: security.session_secure,
+            same_site: same_site(security.session_same_site),
+        }
+    }
+
+    /// Whether `req` changes state on behalf of the session cookie
+    fn requires_token(&self, req: &ServiceRequest) -> bool {
+        let safe = matches!(
+            *req.method(),
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+        );
+
+        !safe
+            && req.cookie(&self.session_name).is_some()
+            && !BearerIdentityPolicy::identified(req)
+            && req.extensions().get::<IdentityAccount>().is_none()
+    }
+
+    /// Whether the header of `req` echoes its csrf cookie
+    fn token_matches(&self, req: &ServiceRequest) -> bool {
+        let cookie = match req.cookie(&self.config.cookie_name) {
+            Some(cookie) => cookie,
+            None => return false,
+        };
+        let echoed = req
+            .headers()
+            .get(self.config.header_name.as_str())
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        !echoed.is_empty()
+            && echoed.len() == cookie.value().len()
+            && memcmp::eq(echoed.as_bytes(), cookie.value().as_bytes())
+    }
+
+    fn token_cookie(&self) -> Cookie<'static> {
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(CSRF_TOKEN_LEN)
+            .map(char::from)
+            .collect();
+
+        let mut cookie = Cookie::new(self.config.cookie_name.clone(), token);
+        cookie.set_path("/");
+        cookie.set_secure(self.secure);
+        cookie.set_http_only(false);
+        if let Some(same_site) = self.same_site {
+            cookie.set_same_site(same_site);
+        }
+        cookie
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CsrfProtection
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = CsrfMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CsrfMiddleware {
+            service: Rc::new(service),
+            protection: self.clone(),
+        })
+    }
+}
+
+pub struct CsrfMiddleware<S> {
+    service: Rc<S>,
+    protection: CsrfProtection,
+}
+
+impl<S, B> Service<ServiceRequest> for CsrfMiddleware<S>
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let protection = self.protection.clone();
+
+        Box::pin(async move {
+            if !protection.config.enabled {
+                return service.call(req).await;
+            }
+
+            if protection.requires_token(&req) && !protection.token_matches(&req) {
+                let error = ApiError::Forbidden("missing or invalid csrf token".to_string());
+                return Ok(req.into_response(error.error_response().into_body()));
+            }
+
+            let has_token = req.cookie(&protection.config.cookie_name).is_some();
+            let mut res = service.call(req).await?;
+
+            // a login or logout changes the session cookie, the token moves with it
+            if !has_token || Identity::is_changed(res.request()) {
+                res.response_mut().add_cookie(&protection.token_cookie())?;
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_guardian_identity::{ChainIdentityPolicy, CookieIdentityPolicy, IdentityService};
+    use actix_web::http::{header, StatusCode};
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+
+    fn get_protection() -> CsrfProtection {
+        CsrfProtection::new(&SecurityConfig {
+            session_name: "auth".to_string(),
+            ..SecurityConfig::default()
+        })
+    }
+
+    fn cookie_request(method: Method) -> TestRequest {
+        TestRequest::default()
+            .method(method)
+            .cookie(Cookie::new("auth", "session"))
+    }
+
+    #[test]
+    fn it_requires_tokens_of_state_changing_cookie_requests() {
+        let protection = get_protection();
+
+        assert!(protection.requires_token(&cookie_request(Method::POST).to_srv_request()));
+        assert!(protection.requires_token(&cookie_request(Method::DELETE).to_srv_request()));
+        assert!(!protection.requires_token(&cookie_request(Method::GET).to_srv_request()));
+
+        let anonymous = TestRequest::default().method(Method::POST).to_srv_request();
+        assert!(!protection.requires_token(&anonymous));
+    }
+
+    #[actix_rt::test]
+    async fn it_only_skips_requests_identified_by_a_bearer_token() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(get_protection())
+                .wrap(IdentityService::new(ChainIdentityPolicy::new(
+                    BearerIdentityPolicy::new(),
+                    CookieIdentityPolicy::new(&[0; 32]).name("auth"),
+                )))
+                .route("/", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+        let status = |authorization: &'static str| {
+            let req = cookie_request(Method::POST)
+                .insert_header((header::AUTHORIZATION, authorization))
+                .to_request();
+            let srv = &srv;
+            async move { test::call_service(srv, req).await.status() }
+        };
+
+        assert_eq!(status("Bearer token").await, StatusCode::OK);
+        assert_eq!(status("Basic dGVzdA==").await, StatusCode::FORBIDDEN);
+        assert_eq!(status("Bearer ").await, StatusCode::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn it_replaces_the_token_when_the_identity_changes() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(get_protection())
+                .wrap(IdentityService::new(
+                    CookieIdentityPolicy::new(&[0; 32]).name("auth"),
+                ))
+                .route("/", web::get().to(HttpResponse::Ok))
+                .route(
+                    "/login",
+                    web::get().to(|id: Identity| {
+                        id.remember("account".to_string());
+                        HttpResponse::Ok()
+                    }),
+                ),
+        )
+        .await;
+        let sets_token = |uri: &'static str| {
+            let req = TestRequest::with_uri(uri)
+                .cookie(Cookie::new("csrf_token", "abc123"))
+                .to_request();
+            let srv = &srv;
+            async move {
+                let res = test::call_service(srv, req).await;
+                let cookies: Vec<_> = res.response().cookies().collect();
+                cookies.iter().any(|cookie| cookie.name() == "csrf_token")
+            }
+        };
+
+        assert!(!sets_token("/").await);
+        assert!(sets_token("/login").await);
+    }
+
+    #[test]
+    fn it_skips_api_key_requests() {
+        let req = cookie_request(Method::POST).to_srv_request();
+        req.extensions_mut().insert(IdentityAccount {
+            id: "service".to_string(),
+            email: None,
+            username: None,
+            mobile: None,
+            issued_at: 0,
+            session_id: None,
+            api_key: None,
+            tenant_id: None,
+            organization_id: None,
+            impersonator_id: None,
+        });
+        assert!(!get_protection().requires_token(&req));
+    }
+
+    #[test]
+    fn it_matches_the_echoed_token() {
+        let protection = get_protection();
+        let request = |echoed: &str| {
+            cookie_request(Method::POST)
+                .cookie(Cookie::new("csrf_token", "abc123"))
+                .insert_header(("x-csrf-token", echoed))
+                .to_srv_request()
+        };
+
+        assert!(protection.token_matches(&request("abc123")));
+        assert!(!protection.token_matches(&request("abc124")));
+        assert!(!protection.token_matches(&request("")));
+        assert!(!protection.token_matches(&cookie_request(Method::POST).to_srv_request()));
+    }
+
+    #[test]
+    fn it_issues_script_readable_tokens() {
+        let cookie = get_protection().token_cookie();
+        assert_eq!(cookie.name(), "csrf_token");
+        assert_eq!(cookie.value().len(), CSRF_TOKEN_LEN);
+        assert_eq!(cookie.http_only(), Some(false));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+    }
+}
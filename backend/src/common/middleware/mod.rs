@@ -1 +1,4 @@
-
+pub mod api_key;
+pub mod csrf;
+pub mod rate_limit;
+pub mod tenant;
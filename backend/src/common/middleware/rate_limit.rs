@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use actix_redis::RedisActor;
+use actix_service::{Service, Transform};
+use actix_web::body::MessageBody;
+use actix_web::web::Data;
+use actix_web::{
+    dev::{ServiceRequest, ServiceResponse},
+    Error, ResponseError,
+};
+use futures::{
+    future::{ok, Ready},
+    Future,
+};
+use once_cell::sync::OnceCell;
+use slog::warn;
+
+use crate::common::auth::extractors::account_from_request;
+use crate::common::cache::redis::{eval, Cache};
+use crate::common::errors::ApiError;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::config::{RateLimitBackend, RateLimitConfig, RateLimitKey, RouteLimitConfig, CONFIG};
+
+/// Most buckets the memory backend holds, the least recently used are evicted past it
+const MAX_MEMORY_BUCKETS: usize = 10_000;
+/// Buckets idle this long are full again and pruned first
+const IDLE_BUCKET_SECS: u64 = 3600;
+
+/// Refill the bucket in `KEYS[1]` and take a token from it atomically
+///
+/// `ARGV` holds the burst, the refill rate per second and the current unix time. The reply is
+/// the seconds to wait for the next token, 0 when a token was taken
+const REDIS_TOKEN_BUCKET: &str = r#"
+local burst = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated_at')
+local tokens = tonumber(bucket[1]) or burst
+local updated_at = tonumber(bucket[2]) or now
+tokens = math.min(burst, tokens + math.max(0, now - updated_at) * rate)
+local wait = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+else
+    wait = math.ceil((1 - tokens) / rate)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated_at', tostring(now))
+redis.call('EXPIRE', KEYS[1], math.ceil(burst / rate) + 1)
+return wait
+"#;
+
+lazy_static! {
+    /// The limiter of the app, the middleware and the logins of every transport share its buckets
+    pub static ref RATE_LIMITER: RateLimiter = RateLimiter::new(&CONFIG.rate_limit);
+}
+
+/// A token bucket of the memory backend
+#[derive(Clone, Debug, PartialEq)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Tokens added back to a bucket of `limit` every second
+fn refill_rate(limit: &RouteLimitConfig) -> f64 {
+    f64::from(limit.per_minute.max(1)) / 60.0
+}
+
+/// Refill `bucket` up to `now` and take a token, the seconds to wait when it is empty
+fn take_token(bucket: &mut Bucket, limit: &RouteLimitConfig, now: Instant) -> Option<u64> {
+    let rate = refill_rate(limit);
+    let elapsed = now
+        .saturating_duration_since(bucket.updated_at)
+        .as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * rate).min(f64::from(limit.burst));
+    bucket.updated_at = now;
+
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        None
+    } else {
+        Some(((1.0 - bucket.tokens) / rate).ceil() as u64)
+    }
+}
+
+/// Whether `limit` covers a request to `path` with `method`
+fn applies(limit: &RouteLimitConfig, path: &str, method: &str) -> bool {
+    limit.path.trim_end_matches('/') == path.trim_end_matches('/')
+        && (limit.methods.is_empty()
+            || limit.methods.iter().any(|m| m.eq_ignore_ascii_case(method)))
+}
+
+/// The client ip of `req`, forwarded headers are only read from `trusted_proxies`
+fn client_ip(req: &ServiceRequest, trusted_proxies: &[String]) -> String {
+    let peer = req.peer_addr().map(|addr| addr.ip().to_string());
+    match peer {
+        Some(ip) if trusted_proxies.contains(&ip) => req
+            .connection_info()
+            .realip_remote_addr()
+            .map_or(ip, |forwarded| forwarded.to_string()),
+        Some(ip) => ip,
+        None => "unknown".to_string(),
+    }
+}
+
+/// The bucket of `limit` a request falls into
+fn bucket_key(
+    limit: &RouteLimitConfig,
+    req: &ServiceRequest,
+    trusted_proxies: &[String],
+) -> String {
+    let ip = || format!("ip:{}", client_ip(req, trusted_proxies));
+    let subject = match limit.key {
+        RateLimitKey::Ip => ip(),
+        RateLimitKey::Identity => account_from_request(req.request())
+            .map_or_else(ip, |account| format!("account:{}", account.id)),
+        RateLimitKey::Route => "all".to_string(),
+        RateLimitKey::Login => unreachable!("login limits are charged by the login"),
+    };
+    format!(
+        "rate_limit:{}:{}:{}",
+        limit.path,
+        limit.methods.join(","),
+        subject
+    )
+}
+
+/// The bucket of the login `limit` an `identity` submitted to the login of `tenant` falls into
+fn login_bucket_key(limit: &RouteLimitConfig, tenant: &str, identity: &str) -> String {
+    format!(
+        "rate_limit:{}:login:{}:{}",
+        limit.path,
+        tenant,
+        identity.trim().to_lowercase()
+    )
+}
+
+/// Make room for a new bucket, idle buckets go first and then the least recently used
+fn evict_buckets(buckets: &mut HashMap<String, Bucket>, now: Instant) {
+    let idle =
+        |b: &Bucket| now.saturating_duration_since(b.updated_at).as_secs() > IDLE_BUCKET_SECS;
+    buckets.retain(|_, bucket| !idle(bucket));
+    if buckets.len() < MAX_MEMORY_BUCKETS {
+        return;
+    }
+
+    // a tenth at once so a flood of new clients doesn't sort the map on every request
+    let mut used: Vec<(Instant, String)> = buckets
+        .iter()
+        .map(|(key, bucket)| (bucket.updated_at, key.clone()))
+        .collect();
+    used.sort();
+    let count = buckets.len() + 1 - MAX_MEMORY_BUCKETS + MAX_MEMORY_BUCKETS / 10;
+    for (_, key) in used.into_iter().take(count) {
+        buckets.remove(&key);
+    }
+}
+
+/// Token bucket rate limiting of the routes listed in `rate_limit.routes`
+///
+/// Create it once and clone it into every worker so they share the memory buckets
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+    /// Redis connection of the login limits, the middleware uses the one of the app
+    cache: Arc<OnceCell<Cache>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        RateLimiter {
+            config: Arc::new(config.clone()),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+            cache: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Take a token from every bucket the request falls into, the seconds to wait when one is
+    /// empty
+    async fn check(&self, req: &ServiceRequest) -> Option<u64> {
+        let method = req.method().as_str();
+        for limit in self.config.routes.iter() {
+            if limit.key == RateLimitKey::Login || !applies(limit, req.path(), method) {
+                continue;
+            }
+
+            let key = bucket_key(limit, req, &self.config.trusted_proxies);
+            let wait = match (self.config.backend, req.app_data::<Cache>()) {
+                (RateLimitBackend::Redis, Some(cache)) => take_redis(cache, &key, limit).await,
+                _ => self.take_memory(key, limit),
+            };
+            if wait.is_some() {
+                return wait;
+            }
+        }
+
+        None
+    }
+
+    /// Take a token from every `login` bucket of `identity`, the seconds to wait when one is
+    /// empty
+    ///
+    /// The login routes don't expose the identity to the middleware, so every login transport
+    /// charges these buckets itself before checking the credentials
+    pub async fn check_login(&self, tenant: &str, identity: &str) -> Option<u64> {
+        for limit in self.config.routes.iter() {
+            if limit.key != RateLimitKey::Login {
+                continue;
+            }
+
+            let key = login_bucket_key(limit, tenant, identity);
+            let wait = match self.login_cache() {
+                Some(cache) => take_redis(cache, &key, limit).await,
+                None => self.take_memory(key, limit),
+            };
+            if wait.is_some() {
+                return wait;
+            }
+        }
+
+        None
+    }
+
+    /// The redis connection of the login limits, started on first use with the redis backend
+    fn login_cache(&self) -> Option<&Cache> {
+        if self.config.backend != RateLimitBackend::Redis || CONFIG.datastore.redis_url.is_empty() {
+            return None;
+        }
+        Some(
+            self.cache
+                .get_or_init(|| Data::new(RedisActor::start(&CONFIG.datastore.redis_url))),
+        )
+    }
+
+    fn take_memory(&self, key: String, limit: &RouteLimitConfig) -> Option<u64> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_MEMORY_BUCKETS && !buckets.contains_key(&key) {
+            evict_buckets(&mut buckets, now);
+        }
+
+        let bucket = buckets.entry(key).or_insert_with(|| Bucket {
+            tokens: f64::from(limit.burst),
+            updated_at: now,
+        });
+        take_token(bucket, limit, now)
+    }
+}
+
+/// Take a token from the shared bucket in redis, an unreachable redis lets the request through
+async fn take_redis(cache: &Cache, key: &str, limit: &RouteLimitConfig) -> Option<u64> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default();
+    let args = vec![
+        limit.burst.to_string(),
+        refill_rate(limit).to_string(),
+        format!("{:.3}", now),
+    ];
+
+    match eval(cache, REDIS_TOKEN_BUCKET, &[key], &args).await {
+        Ok(wait) if wait > 0 => Some(wait as u64),
+        Ok(_) => None,
+        Err(e) => {
+            warn!(LOGGER, "[rate_limit] redis bucket unavailable: {}", e);
+            None
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimiter
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            limiter: self.clone(),
+        })
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    limiter: RateLimiter,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            if let Some(retry_after) = limiter.check(&req).await {
+                let message = "too many requests, please retry later".to_string();
+                let error = ApiError::TooManyRequests(message, retry_after);
+                return Ok(req.into_response(error.error_response().into_body()));
+            }
+
+            service.call(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::Method;
+    use actix_web::test::TestRequest;
+    use std::time::Duration;
+
+    fn get_limit() -> RouteLimitConfig {
+        RouteLimitConfig {
+            path: "/api/v1/session".to_string(),
+            methods: vec!["POST".to_string()],
+            key: RateLimitKey::Ip,
+            burst: 2,
+            per_minute: 6,
+        }
+    }
+
+    #[test]
+    fn it_refills_a_token_bucket() {
+        let limit = get_limit();
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 2.0,
+            updated_at: start,
+        };
+
+        assert_eq!(take_token(&mut bucket, &limit, start), None);
+        assert_eq!(take_token(&mut bucket, &limit, start), None);
+        assert_eq!(take_token(&mut bucket, &limit, start), Some(10));
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(take_token(&mut bucket, &limit, later), None);
+    }
+
+    #[test]
+    fn it_matches_routes_and_methods() {
+        let limit = get_limit();
+        assert!(applies(&limit, "/api/v1/session/", "post"));
+        assert!(!applies(&limit, "/api/v1/session", "DELETE"));
+        assert!(!applies(&limit, "/api/v1/accounts", "POST"));
+    }
+
+    #[actix_rt::test]
+    async fn it_limits_requests_per_ip() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            backend: RateLimitBackend::Memory,
+            routes: vec![get_limit()],
+            trusted_proxies: vec![],
+        });
+        let request = |ip: &str| {
+            TestRequest::default()
+                .method(Method::POST)
+                .uri("/api/v1/session")
+                .peer_addr(format!("{}:4000", ip).parse().unwrap())
+                .to_srv_request()
+        };
+
+        assert_eq!(limiter.check(&request("10.0.0.1")).await, None);
+        assert_eq!(limiter.check(&request("10.0.0.1")).await, None);
+        assert_eq!(limiter.check(&request("10.0.0.1")).await, Some(10));
+        assert_eq!(limiter.check(&request("10.0.0.2")).await, None);
+    }
+
+    #[actix_rt::test]
+    async fn it_limits_logins_per_submitted_identity() {
+        let limit = RouteLimitConfig {
+            key: RateLimitKey::Login,
+            methods: vec![],
+            ..get_limit()
+        };
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            backend: RateLimitBackend::Memory,
+            routes: vec![limit],
+            trusted_proxies: vec![],
+        });
+
+        assert_eq!(limiter.check_login("acme", "jane@example.com").await, None);
+        assert_eq!(limiter.check_login("acme", " Jane@Example.com").await, None);
+        assert_eq!(
+            limiter.check_login("acme", "jane@example.com").await,
+            Some(10)
+        );
+        assert_eq!(limiter.check_login("acme", "john@example.com").await, None);
+        assert_eq!(limiter.check_login("other", "jane@example.com").await, None);
+
+        // the middleware leaves login limits to the login, whatever ip the attempts come from
+        let request = TestRequest::default()
+            .method(Method::POST)
+            .uri("/api/v1/session")
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .to_srv_request();
+        assert_eq!(limiter.check(&request).await, None);
+    }
+
+    #[test]
+    fn it_reads_forwarded_ips_from_trusted_proxies_only() {
+        let request = |peer: &str| {
+            TestRequest::default()
+                .uri("/api/v1/session")
+                .insert_header(("x-forwarded-for", "203.0.113.7"))
+                .peer_addr(format!("{}:4000", peer).parse().unwrap())
+                .to_srv_request()
+        };
+        let proxies = vec!["10.0.0.9".to_string()];
+
+        assert_eq!(client_ip(&request("10.0.0.1"), &proxies), "10.0.0.1");
+        assert_eq!(client_ip(&request("10.0.0.9"), &proxies), "203.0.113.7");
+        assert_eq!(client_ip(&request("10.0.0.9"), &[]), "10.0.0.9");
+    }
+
+    #[test]
+    fn it_caps_the_memory_buckets() {
+        let start = Instant::now();
+        let mut buckets = HashMap::new();
+        for i in 0..MAX_MEMORY_BUCKETS {
+            let bucket = Bucket {
+                tokens: 1.0,
+                updated_at: start + Duration::from_millis(i as u64),
+            };
+            buckets.insert(format!("ip:{}", i), bucket);
+        }
+
+        evict_buckets(&mut buckets, start + Duration::from_secs(1));
+        assert!(buckets.len() < MAX_MEMORY_BUCKETS);
+        assert!(!buckets.contains_key("ip:0"));
+        assert!(buckets.contains_key(&format!("ip:{}", MAX_MEMORY_BUCKETS - 1)));
+    }
+}
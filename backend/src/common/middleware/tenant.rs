@@ -0,0 +1,134 @@
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use actix_service::{Service, Transform};
+use actix_web::body::MessageBody;
+use actix_web::http::uri::{PathAndQuery, Uri};
+use actix_web::{
+    dev::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage, ResponseError,
+};
+use futures::{
+    future::{ok, Ready},
+    Future,
+};
+
+use crate::common::errors::ApiError;
+use crate::common::tenant::{resolve_tenant, Tenant};
+use crate::config::TenancyConfig;
+
+/// Point `req` at `path`, keeping its query
+fn rewrite_path(req: &mut ServiceRequest, path: &str) -> Result<(), ApiError> {
+    let invalid = |_| ApiError::BadRequest("invalid tenant path".to_string());
+
+    let mut parts = req.head().uri.clone().into_parts();
+    let path_and_query = match parts.path_and_query.as_ref().and_then(|pq| pq.query()) {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.to_string(),
+    };
+    parts.path_and_query = Some(path_and_query.parse::<PathAndQuery>().map_err(invalid)?);
+    let uri = Uri::from_parts(parts).map_err(invalid)?;
+
+    req.match_info_mut().get_mut().update(&uri);
+    req.head_mut().uri = uri;
+    Ok(())
+}
+
+/// Resolves the tenant of every request and stores it in the request extensions
+///
+/// Wrap it outside the other middlewares, they read the tenant and the path it strips.
+/// A header or path naming an unknown tenant is answered with a 404.
+#[derive(Clone)]
+pub struct TenantResolution {
+    config: Rc<TenancyConfig>,
+}
+
+impl TenantResolution {
+    pub fn new(config: &TenancyConfig) -> Self {
+        TenantResolution {
+            config: Rc::new(config.clone()),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for TenantResolution
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TenantMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(TenantMiddleware {
+            service,
+            config: Rc::clone(&self.config),
+        })
+    }
+}
+
+pub struct TenantMiddleware<S> {
+    service: S,
+    config: Rc<TenancyConfig>,
+}
+
+impl<S, B> Service<ServiceRequest> for TenantMiddleware<S>
+where
+    B: MessageBody,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let resolved = {
+            let info = req.connection_info();
+            let header = req
+                .headers()
+                .get(self.config.header_name.as_str())
+                .and_then(|value| value.to_str().ok());
+            resolve_tenant(&self.config, Some(info.host()), header, req.path())
+        };
+
+        let resolved = resolved.and_then(|resolved| match &resolved.path {
+            Some(path) => rewrite_path(&mut req, path).map(|_| resolved),
+            None => Ok(resolved),
+        });
+
+        match resolved {
+            Ok(resolved) => {
+                req.extensions_mut().insert(Tenant(resolved.id));
+                Box::pin(self.service.call(req))
+            }
+            Err(e) => {
+                Box::pin(async move { Ok(req.into_response(e.error_response().into_body())) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn it_strips_the_tenant_path() {
+        let mut req = TestRequest::with_uri("/t/acme/api/v1/session?next=a").to_srv_request();
+        rewrite_path(&mut req, "/api/v1/session").unwrap();
+
+        assert_eq!(req.path(), "/api/v1/session");
+        assert_eq!(req.query_string(), "next=a");
+    }
+}
@@ -1,10 +1,14 @@
 pub mod appstate;
+pub mod audit;
 pub mod auth;
 pub mod cache;
+pub mod email;
 pub mod errors;
 pub mod helpers;
 pub mod logger;
 pub mod middleware;
+pub mod sms;
+pub mod tenant;
 pub mod tests;
 pub mod utils;
 pub mod validate;
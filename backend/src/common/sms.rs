@@ -0,0 +1,42 @@
+//! Outgoing text message crate
+use slog::warn;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::config::{SmsProvider, CONFIG};
+use crate::integrations::sms::sms_integration::{LogSmsIntegration, SmsIntegration};
+
+lazy_static! {
+    /// The client delivering text messages, None when sms is off
+    pub static ref SMS_INTEGRATION: Option<Box<dyn SmsIntegration>> =
+        sms_integration(CONFIG.integration.sms.provider);
+}
+
+fn sms_integration(provider: SmsProvider) -> Option<Box<dyn SmsIntegration>> {
+    match provider {
+        SmsProvider::Off => None,
+        SmsProvider::Log => Some(Box::new(LogSmsIntegration)),
+    }
+}
+
+/// Send `body` to the mobile `to` with `integration`, failing when no sms client is configured
+pub async fn send_sms_with(
+    integration: Option<&dyn SmsIntegration>,
+    to: &str,
+    body: &str,
+) -> AppResult<()> {
+    let integration = integration.ok_or_else(|| {
+        ApiError::InternalServerError("sms delivery is not configured".to_string())
+    })?;
+
+    integration.send_sms(to, body).await.map_err(|e| {
+        warn!(LOGGER, "[sms] could not send a message: {}", e);
+        e
+    })
+}
+
+/// Send `body` to the mobile `to` with the configured sms client
+pub async fn send_sms(to: &str, body: &str) -> AppResult<()> {
+    send_sms_with(SMS_INTEGRATION.as_deref(), to, body).await
+}
@@ -0,0 +1,282 @@
+//! Tenant resolution crate
+use std::io::ErrorKind;
+use std::ops::Deref;
+
+use actix_web::{
+    dev::Payload,
+    web::{Data, HttpRequest},
+    Error, FromRequest, HttpMessage,
+};
+use futures::future::{ready, Ready};
+
+use crate::common::errors::ApiError;
+use crate::config::{Config, TenancyConfig, TenantSource, CONFIG};
+use crate::data::stores::base_store_trait::BoxedStoreType;
+
+/// Prefix of the paths naming their tenant, `/t/{tenant}/api/v1/...`
+const TENANT_PATH_PREFIX: &str = "/t/";
+/// Whether the datastore scopes its data by tenant, only the MongoDB store does
+const STORE_SCOPES_TENANTS: bool = cfg!(feature = "mongo");
+
+/// The tenant a request was resolved to, kept in the request extensions
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tenant(pub String);
+
+/// The outcome of resolving the tenant of a request
+#[derive(Debug, PartialEq)]
+pub struct ResolvedTenant {
+    pub id: String,
+    /// The path left once the `/t/{tenant}` prefix is stripped, set by the path source only
+    pub path: Option<String>,
+}
+
+/// The tenant named by the host, tenant header or path of a request
+///
+/// Hosts no tenant lists are ignored, a header or path naming an unknown tenant is an error.
+/// Requests no source resolves belong to the default tenant.
+pub fn resolve_tenant(
+    config: &TenancyConfig,
+    host: Option<&str>,
+    header: Option<&str>,
+    path: &str,
+) -> Result<ResolvedTenant, ApiError> {
+    let default = || ResolvedTenant {
+        id: config.default_tenant.clone(),
+        path: None,
+    };
+    if !config.enabled {
+        return Ok(default());
+    }
+
+    for source in config.sources.iter() {
+        let resolved = match source {
+            TenantSource::Header => header.filter(|h| !h.is_empty()).map(|h| ResolvedTenant {
+                id: h.to_string(),
+                path: None,
+            }),
+            TenantSource::Host => host.and_then(|host| {
+                let name = host.split(':').next().unwrap_or(host);
+                config
+                    .tenants
+                    .iter()
+                    .find(|t| t.hosts.iter().any(|h| h == host || h == name))
+                    .map(|t| ResolvedTenant {
+                        id: t.id.clone(),
+                        path: None,
+                    })
+            }),
+            TenantSource::Path => path.strip_prefix(TENANT_PATH_PREFIX).map(|rest| {
+                let (id, rest) = match rest.find('/') {
+                    Some(i) => rest.split_at(i),
+                    None => (rest, "/"),
+                };
+                ResolvedTenant {
+                    id: id.to_string(),
+                    path: Some(rest.to_string()),
+                }
+            }),
+        };
+
+        if let Some(resolved) = resolved {
+            if !config.is_known(&resolved.id) {
+                return Err(ApiError::NotFound("unknown tenant".to_string()));
+            }
+            return Ok(resolved);
+        }
+    }
+
+    Ok(default())
+}
+
+/// The tenant of a request, the default tenant when it wasn't resolved
+pub fn tenant_from_request(req: &HttpRequest) -> String {
+    req.extensions()
+        .get::<Tenant>()
+        .map(|tenant| tenant.0.clone())
+        .unwrap_or_else(|| CONFIG.tenancy.default_tenant.clone())
+}
+
+/// The application store scoped to the tenant of a request
+pub fn store_from_request(req: &HttpRequest) -> Option<Data<BoxedStoreType>> {
+    let store = req.app_data::<Data<BoxedStoreType>>()?;
+    let tenant = tenant_from_request(req);
+
+    if store.tenant() == tenant {
+        Some(store.clone())
+    } else {
+        Some(Data::new(store.for_tenant(&tenant)))
+    }
+}
+
+/// The switches of a tenant, its overrides applied on top of the global config
+#[derive(Debug, PartialEq)]
+pub struct TenantFeatures {
+    pub enable_signup: bool,
+    pub enable_login: bool,
+    pub password_strength: i64,
+}
+
+/// The switches of the tenant `id` in `config`
+///
+/// Overrides only tighten the global config, a tenant can't enable what is globally disabled.
+pub fn tenant_features(config: &Config, id: &str) -> TenantFeatures {
+    let auth = &config.features.auth;
+    let overrides = config.tenancy.tenant(id).map(|t| &t.features);
+
+    TenantFeatures {
+        enable_signup: auth.enable_signup
+            && overrides.and_then(|o| o.enable_signup).unwrap_or(true),
+        enable_login: auth.enable_login && overrides.and_then(|o| o.enable_login).unwrap_or(true),
+        password_strength: overrides
+            .and_then(|o| o.password_strength)
+            .map_or(config.security.password_strength, |strength| {
+                strength.max(config.security.password_strength)
+            }),
+    }
+}
+
+/// Check the tenancy config once at startup, tenancy needs a datastore scoping its data by tenant
+pub fn validate_tenancy(config: &TenancyConfig) -> std::io::Result<()> {
+    check_tenancy(config, STORE_SCOPES_TENANTS)
+}
+
+fn check_tenancy(config: &TenancyConfig, store_scopes_tenants: bool) -> std::io::Result<()> {
+    if config.enabled && !store_scopes_tenants {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "tenancy.enabled needs the MongoDB datastore, the SQL stores don't scope by tenant",
+        ));
+    }
+    Ok(())
+}
+
+/// Extractor for the account store of the tenant of a request.
+///
+/// Simply add "store: TenantStore" to a handler to invoke this.
+pub struct TenantStore(Data<BoxedStoreType>);
+
+impl TenantStore {
+    pub fn into_data(self) -> Data<BoxedStoreType> {
+        self.0
+    }
+}
+
+impl Deref for TenantStore {
+    type Target = BoxedStoreType;
+
+    fn deref(&self) -> &BoxedStoreType {
+        &self.0
+    }
+}
+
+impl FromRequest for TenantStore {
+    type Config = ();
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(store_from_request(req).map(TenantStore).ok_or_else(|| {
+            ApiError::InternalServerError("account store unavailable".to_string()).into()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{TenantConfig, TenantFeaturesConfig};
+
+    fn get_config() -> TenancyConfig {
+        TenancyConfig {
+            enabled: true,
+            tenants: vec![TenantConfig {
+                id: "acme".to_string(),
+                hosts: vec!["acme.example.com".to_string()],
+                features: TenantFeaturesConfig::default(),
+            }],
+            ..TenancyConfig::default()
+        }
+    }
+
+    fn resolved(id: &str, path: Option<&str>) -> ResolvedTenant {
+        ResolvedTenant {
+            id: id.to_string(),
+            path: path.map(|p| p.to_string()),
+        }
+    }
+
+    #[test]
+    fn it_resolves_the_tenant_of_a_request() {
+        let config = get_config();
+        let resolve = |host, header, path| resolve_tenant(&config, host, header, path);
+
+        assert_eq!(
+            resolve(None, Some("acme"), "/api/v1/session").unwrap(),
+            resolved("acme", None)
+        );
+        assert_eq!(
+            resolve(Some("acme.example.com:4443"), None, "/api/v1/session").unwrap(),
+            resolved("acme", None)
+        );
+        assert_eq!(
+            resolve(None, None, "/t/acme/api/v1/session").unwrap(),
+            resolved("acme", Some("/api/v1/session"))
+        );
+        assert_eq!(
+            resolve(Some("other.example.com"), None, "/api/v1/session").unwrap(),
+            resolved("default", None)
+        );
+        assert!(resolve(None, Some("globex"), "/api/v1/session").is_err());
+        assert!(resolve(None, None, "/t/globex/api/v1/session").is_err());
+    }
+
+    #[test]
+    fn it_ignores_the_request_when_tenancy_is_off() {
+        let config = TenancyConfig {
+            enabled: false,
+            ..get_config()
+        };
+        assert_eq!(
+            resolve_tenant(&config, None, Some("acme"), "/t/acme/api").unwrap(),
+            resolved("default", None)
+        );
+    }
+
+    #[test]
+    fn it_applies_the_tenant_overrides() {
+        let mut config = CONFIG.clone();
+        config.features.auth.enable_signup = true;
+        config.security.password_strength = 2;
+        config.tenancy = get_config();
+        config.tenancy.tenants[0].features = TenantFeaturesConfig {
+            enable_signup: Some(false),
+            enable_login: None,
+            password_strength: Some(4),
+        };
+
+        let acme = tenant_features(&config, "acme");
+        assert_eq!(acme.enable_signup, false);
+        assert_eq!(acme.enable_login, config.features.auth.enable_login);
+        assert_eq!(acme.password_strength, 4);
+
+        let default = tenant_features(&config, "default");
+        assert_eq!(default.enable_signup, true);
+        assert_eq!(default.password_strength, 2);
+
+        config.tenancy.tenants[0].features.password_strength = Some(1);
+        assert_eq!(tenant_features(&config, "acme").password_strength, 2);
+    }
+
+    #[test]
+    fn it_needs_a_tenant_scoped_store_for_tenancy() {
+        let config = get_config();
+        assert!(check_tenancy(&config, true).is_ok());
+        assert!(check_tenancy(&config, false).is_err());
+
+        let disabled = TenancyConfig {
+            enabled: false,
+            ..get_config()
+        };
+        assert!(check_tenancy(&disabled, false).is_ok());
+    }
+}
@@ -1,12 +1,93 @@
-use crate::config::CONFIG;
-use actix_guardian_identity::CookieIdentityPolicy;
+use std::io::{Error, ErrorKind};
+
+use crate::config::{CookieSameSite, SecurityConfig, CONFIG};
+use actix_guardian_identity::{BearerIdentityPolicy, ChainIdentityPolicy, CookieIdentityPolicy};
+use actix_web::cookie::SameSite;
 use time::Duration;
 
+/// Identity of a request, an `Authorization: Bearer` token first, then the session cookie
+pub type IdentityPolicyType = ChainIdentityPolicy<BearerIdentityPolicy, CookieIdentityPolicy>;
+
+/// Bytes a session key needs at least, shorter cookie keys are rejected
+const MIN_SESSION_KEY_BYTES: usize = 32;
+
+/// Check `security.session_key` and `security.session_fallback_keys` once at startup, before
+/// the workers build their cookie policies from them
+pub fn validate_session_keys(security: &SecurityConfig) -> std::io::Result<()> {
+    let mut keys = std::iter::once(&security.session_key).chain(&security.session_fallback_keys);
+    if keys.any(|key| key.len() < MIN_SESSION_KEY_BYTES) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "session keys must be at least {} bytes long",
+                MIN_SESSION_KEY_BYTES
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Key of the redis session cookie, kept apart from the identity cookie keys
+pub fn redis_session_key() -> Vec<u8> {
+    [CONFIG.security.session_key.as_bytes(), b"redis-session"].concat()
+}
+
+/// The `SameSite` attribute of a configured value, None leaves it out
+pub fn same_site(value: CookieSameSite) -> Option<SameSite> {
+    match value {
+        CookieSameSite::Unset => None,
+        CookieSameSite::Lax => Some(SameSite::Lax),
+        CookieSameSite::Strict => Some(SameSite::Strict),
+        CookieSameSite::None => Some(SameSite::None),
+    }
+}
+
+/// Cookie policy encrypting with `security.session_key` and still reading the fallback keys
+///
+/// The keys are checked by `validate_session_keys` when the server starts
 pub fn get_cookie_policy() -> CookieIdentityPolicy {
-    CookieIdentityPolicy::new(&[0; 32]) // <- create cookie auth policy
-        .name(&CONFIG.security.session_name)
-        .secure(CONFIG.security.session_secure)
-        .max_age(Duration::seconds(CONFIG.security.session_timeout))
-        .max_age_secs(CONFIG.security.session_timeout)
-        .path(&CONFIG.security.session_path)
+    let security = &CONFIG.security;
+    let policy = CookieIdentityPolicy::new(security.session_key.as_bytes());
+
+    let policy = security
+        .session_fallback_keys
+        .iter()
+        .fold(policy, |policy, key| policy.fallback_key(key.as_bytes()))
+        .name(&security.session_name)
+        .secure(security.session_secure)
+        .max_age(Duration::seconds(security.session_timeout))
+        .max_age_secs(security.session_timeout)
+        .path(&security.session_path);
+
+    match same_site(security.session_same_site) {
+        Some(same_site) => policy.same_site(same_site),
+        None => policy,
+    }
+}
+
+pub fn get_identity_policy() -> IdentityPolicyType {
+    ChainIdentityPolicy::new(BearerIdentityPolicy::new(), get_cookie_policy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_rejects_short_session_keys() {
+        let mut security = SecurityConfig {
+            session_key: "k".repeat(32),
+            session_fallback_keys: vec![],
+            ..CONFIG.security.clone()
+        };
+        assert!(validate_session_keys(&security).is_ok());
+
+        security.session_fallback_keys = vec!["short".to_string()];
+        let error = validate_session_keys(&security).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        security.session_fallback_keys = vec![];
+        security.session_key = "short".to_string();
+        assert!(validate_session_keys(&security).is_err());
+    }
 }
@@ -1,5 +1,6 @@
 pub mod cookie_utils;
 pub mod logger_utils;
+pub mod protobuf_utils;
 pub mod serde_utils;
 pub mod validators;
 pub mod ver_code_gen;
@@ -0,0 +1,200 @@
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::web::{Bytes, Json};
+use actix_web::{Error, FromRequest, HttpRequest, HttpResponse};
+use futures::future::{FutureExt, LocalBoxFuture};
+use prost::Message;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+const PROTOBUF_CONTENT_TYPE_ALT: &str = "application/protobuf";
+
+fn is_protobuf_mime(value: &str) -> bool {
+    value.split(',').any(|part| {
+        let mime = part.split(';').next().unwrap_or("").trim();
+        mime.eq_ignore_ascii_case(PROTOBUF_CONTENT_TYPE)
+            || mime.eq_ignore_ascii_case(PROTOBUF_CONTENT_TYPE_ALT)
+    })
+}
+
+/// Returns true when the request body is protobuf encoded
+pub fn is_protobuf(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(is_protobuf_mime)
+        .unwrap_or(false)
+}
+
+/// Returns true when the client asked for a protobuf encoded response
+pub fn accepts_protobuf(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(is_protobuf_mime)
+        .unwrap_or(false)
+}
+
+/// Extractor for bodies that may be sent either as json or as protobuf.
+///
+/// `T` is the dto the handler works with and `M` the protobuf message it is decoded from
+/// when the request's content type is `application/x-protobuf`.
+pub struct ProtoOrJson<T, M> {
+    inner: T,
+    message: PhantomData<M>,
+}
+
+impl<T, M> ProtoOrJson<T, M> {
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T, M> Deref for ProtoOrJson<T, M> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T, M> FromRequest for ProtoOrJson<T, M>
+where
+    T: DeserializeOwned + 'static,
+    M: Message + Default + Into<T> + 'static,
+{
+    type Config = ();
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if !is_protobuf(req) {
+            let json = Json::<T>::from_request(req, payload);
+            return async move {
+                let body = json.await?;
+                Ok(ProtoOrJson {
+                    inner: body.into_inner(),
+                    message: PhantomData,
+                })
+            }
+            .boxed_local();
+        }
+
+        let bytes = Bytes::from_request(req, payload);
+        async move {
+            let body = bytes.await?;
+            let msg = M::decode(body).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+            Ok(ProtoOrJson {
+                inner: msg.into(),
+                message: PhantomData,
+            })
+        }
+        .boxed_local()
+    }
+}
+
+/// Helper function to respond with protobuf when the client accepts it, json otherwise
+pub fn respond_negotiated<T, M>(req: &HttpRequest, data: T) -> AppResult<HttpResponse>
+where
+    T: Serialize + Into<M>,
+    M: Message,
+{
+    if !accepts_protobuf(req) {
+        return Ok(HttpResponse::Ok().json(data));
+    }
+
+    let msg: M = data.into();
+    let mut buf = Vec::with_capacity(msg.encoded_len());
+    msg.encode(&mut buf)
+        .map_err(|e| ApiError::InternalServerError(e.to_string()))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(PROTOBUF_CONTENT_TYPE)
+        .body(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+    use crate::data::dtos::auth_dto::IdentifierRequest;
+    use crate::proto::gen;
+
+    #[test]
+    fn it_detects_protobuf_accept_header() {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "application/json, application/x-protobuf"))
+            .to_http_request();
+        assert!(accepts_protobuf(&req));
+
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "application/json"))
+            .to_http_request();
+        assert!(!accepts_protobuf(&req));
+    }
+
+    #[test]
+    fn it_responds_with_protobuf() {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, PROTOBUF_CONTENT_TYPE))
+            .to_http_request();
+        let rsp = respond_negotiated::<bool, gen::StatusResponse>(&req, true).unwrap();
+        assert_eq!(
+            rsp.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROTOBUF_CONTENT_TYPE
+        );
+    }
+
+    #[test]
+    fn it_responds_with_json_by_default() {
+        let req = TestRequest::default().to_http_request();
+        let rsp = respond_negotiated::<bool, gen::StatusResponse>(&req, true).unwrap();
+        assert_eq!(
+            rsp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn it_extracts_a_protobuf_body() {
+        let msg = gen::IdentifierRequest {
+            identity: "user_a".to_string(),
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        msg.encode(&mut buf).unwrap();
+
+        let (req, mut payload) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, PROTOBUF_CONTENT_TYPE))
+            .set_payload(buf)
+            .to_http_parts();
+
+        let body =
+            ProtoOrJson::<IdentifierRequest, gen::IdentifierRequest>::from_request(&req, &mut payload)
+                .await
+                .unwrap();
+        assert_eq!(body.identity, "user_a".to_string());
+    }
+
+    #[actix_rt::test]
+    async fn it_extracts_a_json_body() {
+        let (req, mut payload) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload(r#"{"identity":"user_a"}"#)
+            .to_http_parts();
+
+        let body =
+            ProtoOrJson::<IdentifierRequest, gen::IdentifierRequest>::from_request(&req, &mut payload)
+                .await
+                .unwrap();
+        assert_eq!(body.identity, "user_a".to_string());
+    }
+}
@@ -4,6 +4,14 @@ use validator::{Validate, ValidationErrors};
 
 /// Validate a struct and collect and return the errors
 pub fn validate<T>(params: &Json<T>) -> Result<(), ApiError>
+where
+    T: Validate,
+{
+    validate_input(&params.0)
+}
+
+/// Validate a plain input struct that does not come from a json body
+pub fn validate_input<T>(params: &T) -> Result<(), ApiError>
 where
     T: Validate,
 {
@@ -44,12 +44,18 @@ pub struct Config {
     pub environment: AppEnvironment,
     #[serde(rename = "ssl_address")]
     pub ssl_address: String,
+    #[serde(default)]
+    pub grpc_address: String,
     pub datastore: DatastoreConfig,
     pub security: SecurityConfig,
     pub features: FeaturesConfig,
     pub logging: LoggingConfig,
     pub integration: IntegrationConfig,
     pub files: FilesConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub tenancy: TenancyConfig,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -62,6 +68,95 @@ pub struct DatastoreConfig {
     pub redis_url: String,
 }
 
+/// Where the rate limit buckets are kept
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackend {
+    /// Buckets of this process only
+    Memory,
+    /// Buckets shared by every instance, needs `datastore.redis_url`
+    Redis,
+}
+
+impl Default for RateLimitBackend {
+    fn default() -> Self {
+        RateLimitBackend::Memory
+    }
+}
+
+/// What a rate limit bucket is kept for, on top of the route
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitKey {
+    /// One bucket per client ip
+    Ip,
+    /// One bucket per signed in account, anonymous callers fall back to their ip
+    Identity,
+    /// One bucket shared by every caller
+    Route,
+    /// One bucket per identity submitted to the login, whatever ip the attempts come from. The
+    /// login charges it on every transport, `path` and `methods` only name the bucket
+    Login,
+}
+
+impl Default for RateLimitKey {
+    fn default() -> Self {
+        RateLimitKey::Ip
+    }
+}
+
+/// A token bucket limiting the requests to one route
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteLimitConfig {
+    /// Request path the limit applies to, e.g. `/api/v1/session`
+    pub path: String,
+    /// Methods limited, empty limits every method
+    #[serde(default)]
+    pub methods: Vec<String>,
+    #[serde(default)]
+    pub key: RateLimitKey,
+    /// Requests allowed in a burst, the capacity of the bucket
+    pub burst: u32,
+    /// Requests added back to the bucket every minute
+    pub per_minute: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub backend: RateLimitBackend,
+    pub routes: Vec<RouteLimitConfig>,
+    /// Proxy ips whose `Forwarded` and `X-Forwarded-For` headers name the client, the headers
+    /// of other peers are ignored
+    pub trusted_proxies: Vec<String>,
+}
+
+/// Without a `rate_limit` section the enumeration routes are still limited per ip and the logins
+/// per submitted identity
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        let per_ip = |path: &str, burst, per_minute| RouteLimitConfig {
+            path: path.to_string(),
+            methods: vec![],
+            key: RateLimitKey::Ip,
+            burst,
+            per_minute,
+        };
+        RateLimitConfig {
+            backend: RateLimitBackend::Memory,
+            routes: vec![
+                per_ip("/api/v1/accounts/available", 20, 10),
+                per_ip("/api/v1/accounts/signup-token", 5, 2),
+                RouteLimitConfig {
+                    key: RateLimitKey::Login,
+                    ..per_ip("/api/v1/session", 10, 2)
+                },
+            ],
+            trusted_proxies: vec![],
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FilesConfig {
     #[serde(rename = "security_cert")]
@@ -85,18 +180,186 @@ pub struct SecurityConfig {
 
     #[serde(rename = "session_key")]
     pub session_key: String,
+    /// Retired session keys, cookies they encrypted are still read and re-issued with `session_key`
+    #[serde(default)]
+    pub session_fallback_keys: Vec<String>,
     #[serde(rename = "session_name")]
     pub session_name: String,
     #[serde(rename = "session_secure")]
     pub session_secure: bool,
     #[serde(rename = "session_max_age_secs")]
     pub session_timeout: i64,
+    /// `SameSite` attribute of the session and csrf cookies
+    #[serde(default)]
+    pub session_same_site: CookieSameSite,
+    #[serde(default)]
+    pub csrf: CsrfConfig,
     #[serde(rename = "password_strength")]
     pub password_strength: i64,
+    /// Number of previous passwords that can't be reused, 0 disables the check
+    #[serde(default)]
+    pub password_history: usize,
     #[serde(rename = "onetime_code_duration")]
     pub onetime_code_duration: i32,
     #[serde(rename = "onetime_code_length")]
     pub onetime_code_length: u8,
+    #[serde(default)]
+    pub admin_accounts: Vec<String>,
+    #[serde(default)]
+    pub policy_path: String,
+    #[serde(default)]
+    pub password_policy: PasswordPolicyConfig,
+    #[serde(default)]
+    pub breached_passwords: BreachedPasswordConfig,
+    #[serde(default)]
+    pub enumeration_protection: EnumerationProtectionConfig,
+    #[serde(default)]
+    pub impersonation: ImpersonationConfig,
+}
+
+/// The `SameSite` attribute of a cookie
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CookieSameSite {
+    /// Leave the attribute out, browsers pick their own default
+    Unset,
+    Lax,
+    Strict,
+    /// Send the cookie on cross site requests too, needs `session_secure`
+    None,
+}
+
+impl Default for CookieSameSite {
+    fn default() -> Self {
+        CookieSameSite::Lax
+    }
+}
+
+/// Double submit csrf tokens for state changing requests authenticated by the session cookie
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsrfConfig {
+    /// Off unless turned on, cookie clients then have to echo the token
+    pub enabled: bool,
+    /// Cookie holding the token, readable by scripts so they can echo it
+    pub cookie_name: String,
+    /// Header the token has to be echoed in
+    pub header_name: String,
+}
+
+impl Default for CsrfConfig {
+    fn default() -> Self {
+        CsrfConfig {
+            enabled: false,
+            cookie_name: "csrf_token".to_string(),
+            header_name: "x-csrf-token".to_string(),
+        }
+    }
+}
+
+/// Hide from callers whether an identity has an account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnumerationProtectionConfig {
+    /// Uniform login errors and availability checks gated by a signup token
+    pub enabled: bool,
+    /// Minutes a signup token stays valid
+    pub signup_token_minutes: i64,
+    /// Availability checks one signup token allows
+    pub signup_token_checks: i64,
+}
+
+impl Default for EnumerationProtectionConfig {
+    fn default() -> Self {
+        EnumerationProtectionConfig {
+            enabled: false,
+            signup_token_minutes: 10,
+            signup_token_checks: 5,
+        }
+    }
+}
+
+/// Admins acting as another account for support
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImpersonationConfig {
+    /// Off unless turned on, admins can read any account while impersonating
+    pub enabled: bool,
+    /// Minutes an impersonation token and its session stay valid
+    pub minutes: i64,
+}
+
+impl Default for ImpersonationConfig {
+    fn default() -> Self {
+        ImpersonationConfig {
+            enabled: false,
+            minutes: 15,
+        }
+    }
+}
+
+/// Password rules applied on top of the zxcvbn score, and the rotation of passwords
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicyConfig {
+    /// Days a password stays valid, 0 disables expiry
+    pub max_age_days: i64,
+    /// Days before the expiry a login starts reporting it
+    pub warning_days: i64,
+    pub min_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Pass the username and email to zxcvbn so passwords built from them score low
+    pub user_inputs: bool,
+    /// Minutes the change token handed out for an expired password stays valid
+    pub change_token_minutes: i64,
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        PasswordPolicyConfig {
+            max_age_days: 0,
+            warning_days: 0,
+            min_length: 0,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            user_inputs: false,
+            change_token_minutes: 15,
+        }
+    }
+}
+
+/// How a new password found in the breached password corpus is treated
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BreachEnforcement {
+    Off,
+    /// Log the breached password and accept it
+    Warn,
+    Reject,
+}
+
+impl Default for BreachEnforcement {
+    fn default() -> Self {
+        BreachEnforcement::Off
+    }
+}
+
+/// The k-anonymity breached password corpus new passwords are checked against
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BreachedPasswordConfig {
+    pub enforcement: BreachEnforcement,
+    /// Directory of HIBP style range files named after the 5 character SHA-1 prefix
+    pub range_path: String,
+    /// Range api queried instead of the local files, needs the `hibp_integration` feature
+    pub api_url: String,
+    /// Occurrences in the corpus before a password counts as breached, at least 1
+    pub min_occurrences: u64,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -111,11 +374,25 @@ pub struct AuthFeaturesConfig {
     pub login_with_signup: bool,
     #[serde(rename = "security_level")]
     pub security_level: AuthSecurityLevel,
+    /// Link sent to confirm a new email, `{token}` is replaced by the token, empty sends the
+    /// token alone
+    #[serde(default)]
+    pub email_confirmation_url: String,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ApiFeaturesConfig {
     pub enable_graphql: bool,
+    #[serde(default)]
+    pub enable_openapi: bool,
+    #[serde(default)]
+    pub enable_swagger_ui: bool,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileFeaturesConfig {
+    #[serde(default)]
+    pub schema_path: String,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -123,6 +400,151 @@ pub struct FeaturesConfig {
     pub auth: AuthFeaturesConfig,
     #[serde(default = "Default::default")]
     pub api: ApiFeaturesConfig,
+    #[serde(default = "Default::default")]
+    pub profile: ProfileFeaturesConfig,
+    #[serde(default)]
+    pub challenge: ChallengeFeaturesConfig,
+    #[serde(default)]
+    pub organizations: OrganizationFeaturesConfig,
+}
+
+/// Organizations grouping accounts, and the email invitations to join them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrganizationFeaturesConfig {
+    /// Hours an invitation can be accepted
+    pub invitation_hours: i64,
+    /// Link sent in invitation emails, `{token}` is replaced by the invitation token
+    pub invitation_url: String,
+}
+
+impl Default for OrganizationFeaturesConfig {
+    fn default() -> Self {
+        OrganizationFeaturesConfig {
+            invitation_hours: 72,
+            invitation_url: "http://localhost:8080/invitations/accept?token={token}".to_string(),
+        }
+    }
+}
+
+/// Where the tenant of a request is read from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TenantSource {
+    /// The `Host` header, matched against the `hosts` of the tenants
+    Host,
+    /// The `header_name` header holding the tenant id
+    Header,
+    /// A `/t/{tenant}` path prefix, stripped before routing
+    Path,
+}
+
+/// Features a tenant overrides, unset ones follow the global config
+///
+/// Signup and login can only be switched off, routes of globally disabled features don't exist
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TenantFeaturesConfig {
+    pub enable_signup: Option<bool>,
+    pub enable_login: Option<bool>,
+    /// Minimum zxcvbn score of new passwords, only raises `security.password_strength`
+    pub password_strength: Option<i64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TenantConfig {
+    pub id: String,
+    /// Hosts of the tenant, with or without the port
+    pub hosts: Vec<String>,
+    pub features: TenantFeaturesConfig,
+}
+
+/// Isolation of the accounts of several products hosted by one deployment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TenancyConfig {
+    /// When off every request belongs to `default_tenant`
+    pub enabled: bool,
+    /// Tenant of the requests no source resolves and of the accounts created before tenancy
+    pub default_tenant: String,
+    /// Sources tried in order, the first one naming a tenant wins
+    pub sources: Vec<TenantSource>,
+    pub header_name: String,
+    pub tenants: Vec<TenantConfig>,
+}
+
+impl Default for TenancyConfig {
+    fn default() -> Self {
+        TenancyConfig {
+            enabled: false,
+            default_tenant: "default".to_string(),
+            sources: vec![TenantSource::Header, TenantSource::Host, TenantSource::Path],
+            header_name: "x-tenant-id".to_string(),
+            tenants: vec![],
+        }
+    }
+}
+
+impl TenancyConfig {
+    /// The configuration of the tenant `id`
+    pub fn tenant(&self, id: &str) -> Option<&TenantConfig> {
+        self.tenants.iter().find(|t| t.id == id)
+    }
+
+    /// Whether `id` is the default tenant or a configured one
+    pub fn is_known(&self, id: &str) -> bool {
+        id == self.default_tenant || self.tenant(id).is_some()
+    }
+}
+
+/// Who checks the challenge tokens sent with signups and logins
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChallengeProvider {
+    Off,
+    /// A hCaptcha, reCAPTCHA or Turnstile compatible siteverify endpoint
+    Http,
+    /// Accept the `secret` itself as the only valid token, never use it in production
+    Mock,
+}
+
+impl Default for ChallengeProvider {
+    fn default() -> Self {
+        ChallengeProvider::Off
+    }
+}
+
+/// CAPTCHA style challenges guarding signup and login against bots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChallengeFeaturesConfig {
+    pub provider: ChallengeProvider,
+    /// Siteverify url of the http provider, needs the `challenge_integration` feature
+    pub verify_url: String,
+    pub secret: String,
+    /// Challenge every signup
+    pub enable_signup: bool,
+    /// Challenge every login
+    pub enable_login: bool,
+    /// Challenge the logins of an identity with this many recent failed logins, 0 never does
+    pub failed_logins: usize,
+    /// Minutes a failed login counts towards `failed_logins`
+    pub failed_login_minutes: i64,
+}
+
+impl Default for ChallengeFeaturesConfig {
+    fn default() -> Self {
+        ChallengeFeaturesConfig {
+            provider: ChallengeProvider::Off,
+            verify_url: String::new(),
+            secret: String::new(),
+            enable_signup: false,
+            enable_login: false,
+            failed_logins: 0,
+            failed_login_minutes: 15,
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -139,6 +561,57 @@ pub struct SentryLoggingConfig {
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IntegrationConfig {
     pub sendgrid: SendgridIntegrationConfig,
+    #[serde(default)]
+    pub email: EmailIntegrationConfig,
+    #[serde(default)]
+    pub sms: SmsIntegrationConfig,
+}
+
+/// Who delivers the emails sent by the server
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailProvider {
+    Off,
+    /// Write the messages to the log instead of sending them, for local development
+    Log,
+    /// Sendgrid with `sendgrid.api_key`, needs the `sendgrid_integration` feature
+    Sendgrid,
+}
+
+impl Default for EmailProvider {
+    fn default() -> Self {
+        EmailProvider::Off
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailIntegrationConfig {
+    pub provider: EmailProvider,
+    /// Sender address of the emails
+    pub from: String,
+    pub from_name: Option<String>,
+}
+
+/// Who delivers the text messages sent by the server
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmsProvider {
+    Off,
+    /// Write the messages to the log instead of sending them, for local development
+    Log,
+}
+
+impl Default for SmsProvider {
+    fn default() -> Self {
+        SmsProvider::Off
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmsIntegrationConfig {
+    pub provider: SmsProvider,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
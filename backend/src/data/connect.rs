@@ -8,7 +8,7 @@ use crate::data::stores::base_store_trait::{BaseStoreTrait, BoxedStoreType};
 use std::sync::Mutex;
 
 pub fn init_store(config: Config) -> AppResult<BoxedStoreType> {
-    let store = new_account_store(config.datastore).expect("Failed to create connection pool");
+    let store = new_account_store(config.datastore)?;
     let _ = store.index_db(); //.and_then(|_| println!("db indexed successfully"));
     Result::Ok(Box::from(store))
 }
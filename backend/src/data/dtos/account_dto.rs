@@ -1,9 +1,11 @@
 use crate::data::models::accounts_model::AccountModel;
+use crate::events::{AccountEvent, AccountEventKind};
 use chrono::{DateTime, Utc};
+use paperclip::actix::Apiv2Schema;
 use rayon::prelude::*;
 
 #[graphql(description = "A humanoid creature in the Star Wars universe")]
-#[derive(juniper::GraphQLObject, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
 pub struct AccountResponse {
     pub id: String,
     pub username: Option<String>,
@@ -14,17 +16,73 @@ pub struct AccountResponse {
 }
 
 #[graphql(description = "A humanoid creature in the Star Wars universe")]
-#[derive(juniper::GraphQLObject, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
 pub struct PostSessionResponse {
     pub id_token: Option<String>,
+    /// Set when the password has expired, no session is created then
+    #[serde(default)]
+    pub password_expired: bool,
+    /// Short lived token that only allows changing the expired password
+    #[serde(default)]
+    pub change_password_token: Option<String>,
+    /// Set when the password expires within the warning window
+    #[serde(default)]
+    pub password_expires_at: Option<DateTime<Utc>>,
+}
+
+impl PostSessionResponse {
+    pub fn new(id_token: String) -> Self {
+        PostSessionResponse {
+            id_token: Some(id_token),
+            password_expired: false,
+            change_password_token: None,
+            password_expires_at: None,
+        }
+    }
+
+    /// The response to a login with an expired password
+    pub fn password_expired(change_password_token: String) -> Self {
+        PostSessionResponse {
+            id_token: None,
+            password_expired: true,
+            change_password_token: Some(change_password_token),
+            password_expires_at: None,
+        }
+    }
+}
+
+#[graphql(description = "Short lived token required by the availability check")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SignupTokenResponse {
+    pub signup_token: String,
 }
 
 #[graphql(description = "A humanoid creature in the Star Wars universe")]
-#[derive(juniper::GraphQLObject, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
 pub struct PostAccountResponse {
     pub id_token: Option<String>,
 }
 
+#[graphql(description = "The account after a self service update")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AccountUpdateResponse {
+    pub account: AccountResponse,
+    /// A fresh token whose claims match the updated account
+    pub id_token: Option<String>,
+    /// The new email is applied once the token sent to it is confirmed
+    pub email_confirmation_required: bool,
+    /// The new mobile is applied once the one time code sent to it is verified
+    pub mobile_verification_required: bool,
+}
+
+#[graphql(description = "An account or session event")]
+#[derive(juniper::GraphQLObject, Debug, PartialEq)]
+pub struct AccountEventResponse {
+    pub kind: AccountEventKind,
+    pub account_id: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct AccountsResponse(pub Vec<AccountResponse>);
 
@@ -41,6 +99,16 @@ impl From<AccountModel> for AccountResponse {
     }
 }
 
+impl From<AccountEvent> for AccountEventResponse {
+    fn from(event: AccountEvent) -> Self {
+        AccountEventResponse {
+            kind: event.kind,
+            account_id: event.account_id,
+            occurred_at: event.occurred_at,
+        }
+    }
+}
+
 impl From<Vec<AccountModel>> for AccountsResponse {
     fn from(acct: Vec<AccountModel>) -> Self {
         AccountsResponse(acct.into_par_iter().map(|a| a.into()).collect())
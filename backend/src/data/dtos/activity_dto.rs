@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use paperclip::actix::Apiv2Schema;
+
+use crate::data::models::account_activity_model::AccountActivityModel;
+
+/// Activities returned per page when no `per_page` is requested
+pub const DEFAULT_PER_PAGE: i64 = 20;
+/// Upper bound of `per_page`
+pub const MAX_PER_PAGE: i64 = 100;
+/// Upper bound of `page`, keeps the offset of the page and the returned page number in range
+pub const MAX_PAGE: i64 = 1_000_000;
+
+#[derive(Apiv2Schema, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PageRequest {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+impl PageRequest {
+    /// The requested page clamped to a valid 1 based page number and page size
+    pub fn bounds(&self) -> (i64, i64) {
+        let page = self.page.unwrap_or(1).max(1).min(MAX_PAGE);
+        let per_page = self
+            .per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .max(1)
+            .min(MAX_PER_PAGE);
+        (page, per_page)
+    }
+}
+
+#[derive(Apiv2Schema, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ActivitiesQuery {
+    pub account_id: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+impl From<&ActivitiesQuery> for PageRequest {
+    fn from(query: &ActivitiesQuery) -> Self {
+        PageRequest {
+            page: query.page,
+            per_page: query.per_page,
+        }
+    }
+}
+
+#[graphql(description = "A security relevant action recorded in the account activity log")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ActivityResponse {
+    pub id: String,
+    pub account_id: Option<String>,
+    pub log_name: String,
+    pub description: String,
+    pub causer_id: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub correlation_id: Option<String>,
+    /// Extra details of the activity encoded as json
+    pub properties: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[graphql(description = "A page of account activities, newest first")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ActivitiesResponse {
+    pub items: Vec<ActivityResponse>,
+    pub page: i32,
+    pub per_page: i32,
+    pub total: i32,
+}
+
+impl From<AccountActivityModel> for ActivityResponse {
+    fn from(activity: AccountActivityModel) -> Self {
+        ActivityResponse {
+            id: activity.id,
+            account_id: activity.account_id,
+            log_name: activity.log_name,
+            description: activity.description,
+            causer_id: activity.causer_id,
+            ip: activity.ip,
+            user_agent: activity.user_agent,
+            correlation_id: activity.correlation_id,
+            properties: activity.properties.map(|p| p.to_string()),
+            created_at: activity.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_clamps_page_bounds() {
+        assert_eq!(PageRequest::default().bounds(), (1, DEFAULT_PER_PAGE));
+
+        let req = PageRequest {
+            page: Some(-3),
+            per_page: Some(1000),
+        };
+        assert_eq!(req.bounds(), (1, MAX_PER_PAGE));
+
+        let req = PageRequest {
+            page: Some(i64::MAX),
+            per_page: Some(i64::MAX),
+        };
+        assert_eq!(req.bounds(), (MAX_PAGE, MAX_PER_PAGE));
+    }
+}
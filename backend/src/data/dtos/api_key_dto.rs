@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use paperclip::actix::Apiv2Schema;
+use validator::Validate;
+
+use crate::data::models::api_key_model::ApiKeyModel;
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct CreateServiceAccountRequest {
+    /// Username of the service account
+    #[validate(length(min = 3, max = 64, message = "name must be 3 to 64 characters"))]
+    pub name: String,
+
+    #[serde(default)]
+    pub roles: Vec<String>,
+
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 64, message = "name must be 1 to 64 characters"))]
+    pub name: String,
+
+    /// Roles and permissions of the account the key may use, `*` for all of them
+    #[validate(length(min = 1, message = "at least one scope is required"))]
+    pub scopes: Vec<String>,
+
+    /// The key never expires when left out
+    #[validate(range(min = 1, max = 3650))]
+    #[serde(default)]
+    pub expires_in_days: Option<i32>,
+}
+
+#[graphql(description = "An api key of a service account, its secret is never shown again")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ApiKeyResponse {
+    pub id: String,
+    pub account_id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKeyModel> for ApiKeyResponse {
+    fn from(key: ApiKeyModel) -> Self {
+        ApiKeyResponse {
+            id: key.id,
+            account_id: key.account_id,
+            name: key.name,
+            scopes: key.scopes,
+            created_at: key.created_at,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            revoked_at: key.revoked_at,
+        }
+    }
+}
+
+#[graphql(description = "The api keys of a service account, newest first")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ApiKeysResponse {
+    pub items: Vec<ApiKeyResponse>,
+}
+
+#[graphql(description = "A new api key together with the only copy of its secret")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct CreatedApiKeyResponse {
+    /// Sent as `Authorization: ApiKey <key>`, store it now as it can't be retrieved later
+    pub key: String,
+    pub api_key: ApiKeyResponse,
+}
@@ -1,14 +1,19 @@
 use lazy_static::lazy_static;
+use paperclip::actix::Apiv2Schema;
 use regex::Regex;
 use validator::Validate;
 
+use crate::data::dtos::profile_dto::ProfileFieldInput;
+
 lazy_static! {
     static ref EMAIL_REGEX: Regex = Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap();
     static ref PHONE_NUMBER_REGEX: Regex =
         Regex::new(r"^[+]*[(]{0,1}[0-9]{1,4}[)]{0,1}[-\s\./0-9]*$").unwrap();
 }
 
-#[derive(juniper::GraphQLInputObject, Clone, Deserialize, Serialize, Validate, Debug)]
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Deserialize, Serialize, Validate, Debug,
+)]
 pub struct MobileInput {
     #[validate(
         length(min = 1, message = "mobile prefix too short"),
@@ -20,7 +25,9 @@ pub struct MobileInput {
     pub digit: String,
 }
 
-#[derive(juniper::GraphQLInputObject, Clone, Deserialize, Serialize, Validate, Debug)]
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Deserialize, Serialize, Validate, Debug,
+)]
 pub struct PostSessionRequest {
     #[validate(length(
         min = 2,
@@ -33,9 +40,19 @@ pub struct PostSessionRequest {
         custom = "crate::common::utils::validators::validate_strong_password"
     )]
     pub password: String,
+
+    /// Token of a solved challenge, required when the login is challenged
+    #[serde(default)]
+    pub challenge_token: Option<String>,
+
+    /// Token of an organization invitation the account accepts by logging in
+    #[serde(default)]
+    pub invitation_token: Option<String>,
 }
 
-#[derive(juniper::GraphQLInputObject, Clone, Debug, Deserialize, Serialize, Validate)]
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
 pub struct PostAccountRequest {
     #[validate(
         email,
@@ -57,13 +74,118 @@ pub struct PostAccountRequest {
     pub username: Option<String>,
 
     pub mobile: Option<MobileInput>,
+
+    /// Custom profile fields, validated against the profile schema
+    #[serde(default)]
+    pub profile: Option<Vec<ProfileFieldInput>>,
+
+    /// Token of a solved challenge, required when signups are challenged
+    #[serde(default)]
+    pub challenge_token: Option<String>,
+
+    /// Token of an organization invitation the new account accepts
+    #[serde(default)]
+    pub invitation_token: Option<String>,
+}
+
+#[derive(Apiv2Schema, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SignupTokenRequest {
+    /// Token of a solved challenge, required when a challenge provider is configured
+    #[serde(default)]
+    pub challenge_token: Option<String>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+#[derive(Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate)]
 pub struct IdentifierRequest {
     #[validate(length(
         min = 3,
         message = "auth is required which is your username, mobile or email and must be at least 3 characters "
     ))]
     pub identity: String,
+
+    /// Required by the availability check when enumeration protection is on
+    #[serde(default)]
+    pub signup_token: Option<String>,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct UpdateAccountRequest {
+    #[validate(
+        email,
+        length(min = 1, message = "Email is missing"),
+        regex(path = "EMAIL_REGEX", message = "Invalid email address")
+    )]
+    pub email: Option<String>,
+
+    #[validate(length(min = 3, message = "Username must be at least 3 characters"))]
+    pub username: Option<String>,
+
+    pub mobile: Option<MobileInput>,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "current_password is required"))]
+    pub current_password: String,
+
+    #[validate(
+        length(min = 1),
+        custom = "crate::common::utils::validators::validate_strong_password"
+    )]
+    pub password: String,
+
+    #[validate(must_match(other = "password", message = "Passwords does not match"))]
+    pub confirm_password: String,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct RenewPasswordRequest {
+    /// The change password token of a login with an expired password
+    #[validate(length(min = 1, message = "change_password_token is required"))]
+    pub change_password_token: String,
+
+    #[validate(
+        length(min = 1),
+        custom = "crate::common::utils::validators::validate_strong_password"
+    )]
+    pub password: String,
+
+    #[validate(must_match(other = "password", message = "Passwords does not match"))]
+    pub confirm_password: String,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct ConfirmChangeRequest {
+    /// The email confirmation token or the mobile one time code
+    #[validate(length(min = 1, message = "code is required"))]
+    pub code: String,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct SetAccountRolesRequest {
+    pub roles: Vec<String>,
+
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Apiv2Schema, Clone, Debug, Deserialize, Serialize)]
+pub struct SetConfirmedRequest {
+    pub confirmed: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DeleteAccountQuery {
+    #[serde(default)]
+    pub hard: bool,
 }
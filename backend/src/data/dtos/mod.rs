@@ -1,2 +1,7 @@
 pub mod account_dto;
+pub mod activity_dto;
+pub mod api_key_dto;
 pub mod auth_dto;
+pub mod organization_dto;
+pub mod profile_dto;
+pub mod session_dto;
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use paperclip::actix::Apiv2Schema;
+use validator::Validate;
+
+use crate::data::models::invitation_model::InvitationModel;
+use crate::data::models::organization_model::{MembershipModel, OrganizationModel};
+
+fn default_role() -> String {
+    "member".to_string()
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct CreateOrganizationRequest {
+    #[validate(length(min = 1, max = 100, message = "name must be 1 to 100 characters"))]
+    pub name: String,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct UpdateMemberRequest {
+    /// owner, admin or member
+    pub role: String,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct CreateInvitationRequest {
+    #[validate(email(message = "Invalid email address"))]
+    pub email: String,
+
+    /// owner, admin or member, only owners invite owners
+    #[serde(default = "default_role")]
+    pub role: String,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct AcceptInvitationRequest {
+    #[validate(length(min = 1, message = "token is required"))]
+    pub token: String,
+}
+
+#[graphql(description = "An organization and the role of the caller in it")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct OrganizationResponse {
+    pub id: String,
+    pub name: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub role: String,
+}
+
+impl OrganizationResponse {
+    pub fn new(organization: OrganizationModel, membership: &MembershipModel) -> Self {
+        OrganizationResponse {
+            id: organization.id,
+            name: organization.name,
+            created_by: organization.created_by,
+            created_at: organization.created_at,
+            role: membership.role.as_str().to_string(),
+        }
+    }
+}
+
+#[graphql(description = "The organizations of the caller, the most recently joined first")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct OrganizationsResponse {
+    pub items: Vec<OrganizationResponse>,
+}
+
+#[graphql(description = "The organization a session acts in and the token claiming it")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ActiveOrganizationResponse {
+    pub organization: OrganizationResponse,
+    pub id_token: String,
+}
+
+#[graphql(description = "A member of an organization")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MemberResponse {
+    pub account_id: String,
+    pub role: String,
+    pub invited_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<MembershipModel> for MemberResponse {
+    fn from(membership: MembershipModel) -> Self {
+        MemberResponse {
+            account_id: membership.account_id,
+            role: membership.role.as_str().to_string(),
+            invited_by: membership.invited_by,
+            created_at: membership.created_at,
+        }
+    }
+}
+
+#[graphql(description = "The members of an organization, oldest first")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MembersResponse {
+    pub items: Vec<MemberResponse>,
+}
+
+#[graphql(description = "An invitation to join an organization, its token is only emailed")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct InvitationResponse {
+    pub id: String,
+    pub organization_id: String,
+    pub email: String,
+    pub role: String,
+    pub invited_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<InvitationModel> for InvitationResponse {
+    fn from(invitation: InvitationModel) -> Self {
+        InvitationResponse {
+            id: invitation.id,
+            organization_id: invitation.organization_id,
+            email: invitation.email,
+            role: invitation.role.as_str().to_string(),
+            invited_by: invitation.invited_by,
+            created_at: invitation.created_at,
+            expires_at: invitation.expires_at,
+        }
+    }
+}
+
+#[graphql(description = "The open invitations of an organization, newest first")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct InvitationsResponse {
+    pub items: Vec<InvitationResponse>,
+}
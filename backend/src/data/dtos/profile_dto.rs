@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use paperclip::actix::Apiv2Schema;
+use serde_json::Value;
+use validator::Validate;
+
+use crate::data::models::profile_model::ProfileModel;
+use crate::data::models::profile_schema::{ProfileFieldSchema, ProfileSchema};
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, PartialEq,
+)]
+pub struct ProfileFieldInput {
+    pub name: String,
+    /// The raw value, converted to the type declared in the profile schema
+    /// An empty value clears an optional field
+    pub value: String,
+}
+
+#[derive(
+    juniper::GraphQLInputObject, Apiv2Schema, Clone, Debug, Deserialize, Serialize, Validate,
+)]
+pub struct UpdateProfileRequest {
+    pub fields: Vec<ProfileFieldInput>,
+}
+
+#[graphql(description = "A custom profile field of an account")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ProfileFieldResponse {
+    pub name: String,
+    pub value: String,
+}
+
+#[graphql(description = "The custom profile fields of an account")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ProfileResponse {
+    pub account_id: String,
+    pub fields: Vec<ProfileFieldResponse>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[graphql(description = "A custom profile field declared by the profile schema")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ProfileFieldSchemaResponse {
+    pub name: String,
+    /// One of string, integer, number, boolean or date
+    pub field_type: String,
+    pub required: bool,
+    pub unique: bool,
+    pub pattern: Option<String>,
+}
+
+#[graphql(description = "The custom profile fields every account can or must provide")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ProfileSchemaResponse {
+    pub fields: Vec<ProfileFieldSchemaResponse>,
+}
+
+impl ProfileResponse {
+    /// The response of an account without a stored profile
+    pub fn empty(account_id: &str) -> Self {
+        ProfileResponse {
+            account_id: account_id.to_string(),
+            fields: vec![],
+            updated_at: None,
+        }
+    }
+}
+
+impl From<ProfileModel> for ProfileResponse {
+    fn from(profile: ProfileModel) -> Self {
+        let fields = profile
+            .fields
+            .into_iter()
+            .filter(|(_, v)| !v.is_null())
+            .map(|(name, value)| ProfileFieldResponse {
+                name,
+                value: match value {
+                    Value::String(s) => s,
+                    v => v.to_string(),
+                },
+            })
+            .collect();
+
+        ProfileResponse {
+            account_id: profile.account_id,
+            fields,
+            updated_at: Some(profile.updated_at),
+        }
+    }
+}
+
+impl From<&ProfileFieldSchema> for ProfileFieldSchemaResponse {
+    fn from(field: &ProfileFieldSchema) -> Self {
+        let field_type = serde_json::to_value(field.field_type)
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default();
+
+        ProfileFieldSchemaResponse {
+            name: field.name.clone(),
+            field_type,
+            required: field.required,
+            unique: field.unique,
+            pattern: field.pattern.clone(),
+        }
+    }
+}
+
+impl From<&ProfileSchema> for ProfileSchemaResponse {
+    fn from(schema: &ProfileSchema) -> Self {
+        ProfileSchemaResponse {
+            fields: schema.fields.iter().map(|f| f.into()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Map;
+
+    #[test]
+    fn it_converts_field_values_to_strings() {
+        let mut fields = Map::new();
+        fields.insert("age".to_string(), Value::from(36));
+        fields.insert("first_name".to_string(), Value::from("Ada"));
+        fields.insert("nickname".to_string(), Value::Null);
+
+        let now = Utc::now();
+        let rsp: ProfileResponse = ProfileModel {
+            id: "p".to_string(),
+            account_id: "a".to_string(),
+            fields,
+            created_by: None,
+            created_at: now,
+            updated_by: None,
+            updated_at: now,
+        }
+        .into();
+
+        assert_eq!(
+            rsp.fields,
+            vec![
+                ProfileFieldResponse {
+                    name: "age".to_string(),
+                    value: "36".to_string()
+                },
+                ProfileFieldResponse {
+                    name: "first_name".to_string(),
+                    value: "Ada".to_string()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use paperclip::actix::Apiv2Schema;
+
+use crate::data::models::session_model::SessionModel;
+
+#[graphql(description = "A device or client the account is logged in from")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ActiveSessionResponse {
+    pub id: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// Set for the session making the request
+    pub current: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// The admin impersonating the account through the session
+    pub impersonated_by: Option<String>,
+}
+
+impl ActiveSessionResponse {
+    /// The response of `session`, flagged as current when it is `current_id`
+    pub fn new(session: SessionModel, current_id: Option<&str>) -> Self {
+        ActiveSessionResponse {
+            current: current_id == Some(session.id.as_str()),
+            id: session.id,
+            ip: session.ip,
+            user_agent: session.user_agent,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            expires_at: session.expires_at,
+            impersonated_by: session.impersonated_by,
+        }
+    }
+}
+
+#[graphql(description = "The active sessions of the logged in account, last used first")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ActiveSessionsResponse {
+    pub items: Vec<ActiveSessionResponse>,
+}
+
+#[graphql(description = "The number of sessions ended by a revocation")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct RevokedSessionsResponse {
+    pub revoked: i32,
+}
+
+#[graphql(description = "A short lived token of an admin acting as another account")]
+#[derive(juniper::GraphQLObject, Apiv2Schema, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ImpersonationResponse {
+    pub id_token: String,
+    /// The session of the impersonation, ending it ends the impersonation
+    pub session_id: String,
+    pub expires_at: DateTime<Utc>,
+}
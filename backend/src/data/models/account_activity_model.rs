@@ -0,0 +1,137 @@
+//! Account activity model crate
+use chrono::{DateTime, Utc};
+use mongodb::bson::serde_helpers::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+use crate::data::models::accounts_model::string_from_object_id;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountActivityModel {
+    #[serde(
+        rename = "_id",
+        serialize_with = "serialize_hex_string_as_object_id",
+        deserialize_with = "string_from_object_id"
+    )]
+    pub id: String,
+    pub account_id: Option<String>,
+    pub log_name: String,
+    pub description: String,
+    pub subject_id: Option<String>,
+    pub subject_type: Option<String>,
+    pub causer_id: Option<String>,
+    pub causer_type: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub correlation_id: Option<String>,
+    pub properties: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The security relevant actions recorded in the activity log, serialized as the `log_name`
+#[derive(strum_macros::ToString, Clone, Copy, Debug, PartialEq)]
+pub enum ActivityKind {
+    #[strum(serialize = "signup")]
+    Signup,
+    #[strum(serialize = "login")]
+    Login,
+    #[strum(serialize = "login_failed")]
+    LoginFailed,
+    #[strum(serialize = "logout")]
+    Logout,
+    #[strum(serialize = "account_updated")]
+    AccountUpdated,
+    #[strum(serialize = "account_locked")]
+    AccountLocked,
+    #[strum(serialize = "account_unlocked")]
+    AccountUnlocked,
+    #[strum(serialize = "account_deleted")]
+    AccountDeleted,
+    #[strum(serialize = "account_restored")]
+    AccountRestored,
+    #[strum(serialize = "account_confirmed")]
+    AccountConfirmed,
+    #[strum(serialize = "password_change_required")]
+    PasswordChangeRequired,
+    #[strum(serialize = "roles_changed")]
+    RolesChanged,
+    #[strum(serialize = "profile_updated")]
+    ProfileUpdated,
+    #[strum(serialize = "email_change_requested")]
+    EmailChangeRequested,
+    #[strum(serialize = "email_changed")]
+    EmailChanged,
+    #[strum(serialize = "mobile_change_requested")]
+    MobileChangeRequested,
+    #[strum(serialize = "mobile_changed")]
+    MobileChanged,
+    #[strum(serialize = "mobile_verification_failed")]
+    MobileVerificationFailed,
+    #[strum(serialize = "password_changed")]
+    PasswordChanged,
+    #[strum(serialize = "password_change_failed")]
+    PasswordChangeFailed,
+    #[strum(serialize = "session_revoked")]
+    SessionRevoked,
+    #[strum(serialize = "service_account_created")]
+    ServiceAccountCreated,
+    #[strum(serialize = "api_key_created")]
+    ApiKeyCreated,
+    #[strum(serialize = "api_key_revoked")]
+    ApiKeyRevoked,
+    #[strum(serialize = "organization_created")]
+    OrganizationCreated,
+    #[strum(serialize = "member_invited")]
+    MemberInvited,
+    #[strum(serialize = "invitation_accepted")]
+    InvitationAccepted,
+    #[strum(serialize = "invitation_revoked")]
+    InvitationRevoked,
+    #[strum(serialize = "member_role_changed")]
+    MemberRoleChanged,
+    #[strum(serialize = "member_removed")]
+    MemberRemoved,
+    #[strum(serialize = "impersonation_started")]
+    ImpersonationStarted,
+    #[strum(serialize = "impersonation_ended")]
+    ImpersonationEnded,
+}
+
+impl ActivityKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ActivityKind::Signup => "account created",
+            ActivityKind::Login => "logged in",
+            ActivityKind::LoginFailed => "failed login attempt",
+            ActivityKind::Logout => "logged out",
+            ActivityKind::AccountUpdated => "account details updated",
+            ActivityKind::AccountLocked => "account locked",
+            ActivityKind::AccountUnlocked => "account unlocked",
+            ActivityKind::AccountDeleted => "account deleted",
+            ActivityKind::AccountRestored => "account restored",
+            ActivityKind::AccountConfirmed => "account confirmation changed",
+            ActivityKind::PasswordChangeRequired => "password change required",
+            ActivityKind::RolesChanged => "roles and permissions changed",
+            ActivityKind::ProfileUpdated => "profile fields updated",
+            ActivityKind::EmailChangeRequested => "email change requested",
+            ActivityKind::EmailChanged => "email change confirmed",
+            ActivityKind::MobileChangeRequested => "mobile change requested",
+            ActivityKind::MobileChanged => "mobile change verified",
+            ActivityKind::MobileVerificationFailed => "mobile verification with a wrong code",
+            ActivityKind::PasswordChanged => "password changed, other sessions revoked",
+            ActivityKind::PasswordChangeFailed => "password change with a wrong current password",
+            ActivityKind::SessionRevoked => "session revoked",
+            ActivityKind::ServiceAccountCreated => "service account created",
+            ActivityKind::ApiKeyCreated => "api key created",
+            ActivityKind::ApiKeyRevoked => "api key revoked",
+            ActivityKind::OrganizationCreated => "organization created",
+            ActivityKind::MemberInvited => "organization invitation sent",
+            ActivityKind::InvitationAccepted => "organization invitation accepted",
+            ActivityKind::InvitationRevoked => "organization invitation revoked",
+            ActivityKind::MemberRoleChanged => "organization role changed",
+            ActivityKind::MemberRemoved => "removed from an organization",
+            ActivityKind::ImpersonationStarted => "admin started impersonating the account",
+            ActivityKind::ImpersonationEnded => "admin stopped impersonating the account",
+        }
+    }
+}
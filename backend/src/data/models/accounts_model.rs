@@ -4,7 +4,7 @@ use mongodb::bson::serde_helpers::*;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt::Debug;
 
-fn string_from_object_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+pub fn string_from_object_id<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -25,6 +25,9 @@ pub struct AccountModel {
     pub email: Option<String>,
     pub mobile: Option<String>,
     pub password: String,
+    /// Hashes of the previous passwords, newest first
+    #[serde(default)]
+    pub password_history: Vec<String>,
     pub last_login_at: Option<DateTime<Utc>>,
     pub current_login_at: Option<DateTime<Utc>>,
     pub confirmed_at: Option<DateTime<Utc>>,
@@ -41,8 +44,17 @@ pub struct AccountModel {
     pub last_login_ip: Option<String>,
     pub current_login_ip: Option<String>,
     pub unconfirmed_email: Option<String>,
+    #[serde(default)]
+    pub unconfirmed_mobile: Option<String>,
     pub locked: bool,
     pub require_new_password: bool,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Machine account that authenticates with api keys only
+    #[serde(default)]
+    pub service_account: bool,
     pub created_by: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_by: Option<String>,
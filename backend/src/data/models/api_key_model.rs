@@ -0,0 +1,61 @@
+//! Api key model crate
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// A named key a service account authenticates with, only the hash of its secret is kept
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiKeyModel {
+    /// Public part of the key, the lookup id
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub account_id: String,
+    pub name: String,
+    /// Roles and permissions of the account the key may use, `*` for all of them
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    pub secret_hash: String,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKeyModel {
+    /// Whether the key is accepted at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.map_or(true, |at| at > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn it_ends_revoked_and_expired_keys() {
+        let now = Utc::now();
+        let mut key = ApiKeyModel {
+            id: "k3Yd9aQx2LmP".to_string(),
+            account_id: "1234".to_string(),
+            name: "billing".to_string(),
+            scopes: vec!["account:read".to_string()],
+            secret_hash: String::new(),
+            created_by: None,
+            created_at: now,
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+        };
+        assert!(key.is_active(now));
+
+        key.expires_at = Some(now + Duration::days(1));
+        assert!(key.is_active(now));
+        assert!(!key.is_active(now + Duration::days(2)));
+
+        key.revoked_at = Some(now);
+        assert!(!key.is_active(now));
+    }
+}
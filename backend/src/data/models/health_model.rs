@@ -1,4 +1,4 @@
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, paperclip::actix::Apiv2Schema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
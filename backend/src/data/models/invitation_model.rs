@@ -0,0 +1,64 @@
+//! Organization invitation model crate
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+use crate::data::models::organization_model::OrganizationRole;
+
+/// An email invitation to join an organization, only the hash of its token is kept
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InvitationModel {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub organization_id: String,
+    pub email: String,
+    /// The role the invited account joins with
+    pub role: OrganizationRole,
+    pub token_hash: String,
+    pub invited_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub accepted_by: Option<String>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl InvitationModel {
+    /// Whether the invitation can still be accepted at `now`
+    pub fn is_pending(&self, now: DateTime<Utc>) -> bool {
+        self.accepted_at.is_none() && self.revoked_at.is_none() && self.expires_at > now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn it_ends_accepted_revoked_and_expired_invitations() {
+        let now = Utc::now();
+        let mut invitation = InvitationModel {
+            id: "2f0c6a1e-8b3d-4c5e-9a7f-0d1e2c3b4a59".to_string(),
+            organization_id: "acme".to_string(),
+            email: "ann@example.com".to_string(),
+            role: OrganizationRole::Member,
+            token_hash: String::new(),
+            invited_by: "1234".to_string(),
+            created_at: now,
+            expires_at: now + Duration::hours(1),
+            accepted_at: None,
+            accepted_by: None,
+            revoked_at: None,
+        };
+        assert!(invitation.is_pending(now));
+        assert!(!invitation.is_pending(now + Duration::hours(2)));
+
+        invitation.accepted_at = Some(now);
+        assert!(!invitation.is_pending(now));
+
+        invitation.accepted_at = None;
+        invitation.revoked_at = Some(now);
+        assert!(!invitation.is_pending(now));
+    }
+}
@@ -1,4 +1,11 @@
+pub mod account_activity_model;
+pub mod api_key_model;
 pub mod accounts_model;
 pub mod health_model;
+pub mod invitation_model;
 pub mod onetime_code_model;
+pub mod organization_model;
 pub mod oso_init;
+pub mod profile_model;
+pub mod profile_schema;
+pub mod session_model;
@@ -8,6 +8,9 @@ pub struct OneTimeCodeModel {
     #[serde(serialize_with = "serialize_hex_string_as_object_id")]
     pub id: String,
     pub code: String,
+    /// Wrong codes sent so far, the code is dropped once they reach the limit
+    #[serde(default)]
+    pub failed_attempts: i64,
     pub expire_at: Option<NaiveDateTime>,
     pub created_by: Option<String>,
     pub created_at: NaiveDateTime,
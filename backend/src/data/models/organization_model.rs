@@ -0,0 +1,89 @@
+//! Organization model crate
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use crate::common::errors::ApiError;
+
+/// A group of accounts, e.g. the customer of a B2B product
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrganizationModel {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub name: String,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The role of a member in an organization, owners outrank admins who outrank members
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrganizationRole {
+    Member,
+    Admin,
+    Owner,
+}
+
+impl OrganizationRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrganizationRole::Member => "member",
+            OrganizationRole::Admin => "admin",
+            OrganizationRole::Owner => "owner",
+        }
+    }
+
+    /// Whether the role manages the members and invitations of its organization
+    pub fn can_manage(&self) -> bool {
+        *self >= OrganizationRole::Admin
+    }
+}
+
+impl FromStr for OrganizationRole {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "member" => Ok(OrganizationRole::Member),
+            "admin" => Ok(OrganizationRole::Admin),
+            "owner" => Ok(OrganizationRole::Owner),
+            _ => Err(ApiError::BadRequest(format!(
+                "{} is not an organization role",
+                s
+            ))),
+        }
+    }
+}
+
+/// An account belonging to an organization with a role
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MembershipModel {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub organization_id: String,
+    pub account_id: String,
+    pub role: OrganizationRole,
+    /// The account whose invitation the member accepted, None for the creator
+    pub invited_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_ranks_the_organization_roles() {
+        assert!(OrganizationRole::Owner > OrganizationRole::Admin);
+        assert!(OrganizationRole::Admin.can_manage());
+        assert!(!OrganizationRole::Member.can_manage());
+
+        let role: OrganizationRole = "admin".parse().unwrap();
+        assert_eq!(role, OrganizationRole::Admin);
+        assert_eq!(role.as_str(), "admin");
+        assert!("root".parse::<OrganizationRole>().is_err());
+    }
+}
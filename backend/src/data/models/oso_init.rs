@@ -1 +1,159 @@
+//! Oso policy model crate
+use std::sync::Mutex;
 
+use oso::{Oso, PolarClass};
+
+use crate::config::CONFIG;
+use crate::data::models::accounts_model::AccountModel;
+
+/// Role granting every action on every account
+pub const ADMIN_ROLE: &str = "admin";
+
+lazy_static! {
+    /// The policy engine, loaded once with the configured polar policy
+    pub static ref OSO: Mutex<Oso> = Mutex::new(init_oso(&policy_path()).unwrap());
+}
+
+/// The authenticated caller as seen by the polar policy
+#[derive(Clone, Debug, Default, PartialEq, oso_derive::PolarClass)]
+pub struct Actor {
+    #[polar(attribute)]
+    pub id: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+impl Actor {
+    pub fn has_role(&self, role: String) -> bool {
+        self.roles.contains(&role)
+    }
+
+    pub fn has_permission(&self, permission: String) -> bool {
+        self.permissions.contains(&permission)
+    }
+}
+
+impl From<AccountModel> for Actor {
+    fn from(account: AccountModel) -> Self {
+        let mut roles = account.roles;
+
+        // accounts listed in the config are admins regardless of stored roles
+        if CONFIG.security.admin_accounts.contains(&account.id)
+            && !roles.iter().any(|r| r == ADMIN_ROLE)
+        {
+            roles.push(ADMIN_ROLE.to_string());
+        }
+
+        Actor {
+            id: account.id,
+            roles,
+            permissions: account.permissions,
+        }
+    }
+}
+
+/// The account an action is performed on
+#[derive(Clone, Debug, Default, PartialEq, oso_derive::PolarClass)]
+pub struct AccountResource {
+    #[polar(attribute)]
+    pub id: String,
+}
+
+impl AccountResource {
+    pub fn new(id: &str) -> Self {
+        AccountResource { id: id.to_string() }
+    }
+}
+
+fn policy_path() -> String {
+    if !CONFIG.security.policy_path.is_empty() {
+        return CONFIG.security.policy_path.clone();
+    }
+
+    #[cfg(not(test))]
+    let path = "./backend/config/policy.polar".to_string();
+
+    #[cfg(test)]
+    let path = "./config/policy.polar".to_string();
+
+    path
+}
+
+/// Register the policy classes and load the polar policy at `path`
+pub fn init_oso(path: &str) -> oso::Result<Oso> {
+    let mut oso = Oso::new();
+
+    oso.register_class(
+        Actor::get_polar_class_builder()
+            .add_method("has_role", Actor::has_role)
+            .add_method("has_permission", Actor::has_permission)
+            .build(),
+    )?;
+    oso.register_class(AccountResource::get_polar_class())?;
+    oso.load_file(path)?;
+
+    Ok(oso)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actor(id: &str, roles: Vec<&str>, permissions: Vec<&str>) -> Actor {
+        Actor {
+            id: id.to_string(),
+            roles: roles.into_iter().map(String::from).collect(),
+            permissions: permissions.into_iter().map(String::from).collect(),
+        }
+    }
+
+    fn is_allowed(actor: Actor, action: &str, id: &str) -> bool {
+        let mut oso = OSO.lock().unwrap();
+        oso.is_allowed(actor, action.to_string(), AccountResource::new(id))
+            .unwrap()
+    }
+
+    #[test]
+    fn it_allows_accounts_to_manage_themselves() {
+        assert_eq!(
+            is_allowed(actor("a", vec![], vec![]), "account:lock", "a"),
+            true
+        );
+        assert_eq!(
+            is_allowed(actor("a", vec![], vec![]), "account:manage_roles", "a"),
+            false
+        );
+    }
+
+    #[test]
+    fn it_denies_other_accounts() {
+        assert_eq!(
+            is_allowed(actor("a", vec![], vec![]), "account:lock", "b"),
+            false
+        );
+    }
+
+    #[test]
+    fn it_allows_admins_and_permissions() {
+        assert_eq!(
+            is_allowed(actor("a", vec![ADMIN_ROLE], vec![]), "account:delete", "b"),
+            true
+        );
+        assert_eq!(
+            is_allowed(
+                actor("a", vec![], vec!["account:unlock"]),
+                "account:unlock",
+                "b"
+            ),
+            true
+        );
+        assert_eq!(
+            is_allowed(
+                actor("a", vec![], vec!["account:unlock"]),
+                "account:delete",
+                "b"
+            ),
+            false
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! User profile model crate
+use chrono::{DateTime, Utc};
+use mongodb::bson::serde_helpers::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt::Debug;
+
+use crate::data::models::accounts_model::string_from_object_id;
+
+/// The custom profile fields of an account, as declared by the profile schema
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProfileModel {
+    #[serde(
+        rename = "_id",
+        serialize_with = "serialize_hex_string_as_object_id",
+        deserialize_with = "string_from_object_id"
+    )]
+    pub id: String,
+    pub account_id: String,
+    #[serde(default)]
+    pub fields: Map<String, Value>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,270 @@
+//! Profile schema model crate
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::config::CONFIG;
+
+lazy_static! {
+    /// The custom profile fields declared by the operator, loaded once at startup
+    pub static ref PROFILE_SCHEMA: ProfileSchema =
+        load_profile_schema(&CONFIG.features.profile.schema_path).unwrap();
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileFieldType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// A `YYYY-MM-DD` date
+    Date,
+}
+
+impl Default for ProfileFieldType {
+    fn default() -> Self {
+        ProfileFieldType::String
+    }
+}
+
+/// A custom profile field declared in the schema file
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ProfileFieldSchema {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub field_type: ProfileFieldType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub unique: bool,
+    /// Regular expression the raw value must match
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(rename = "requireConfirmation", default)]
+    pub require_confirmation: bool,
+    #[serde(skip)]
+    regex: Option<Regex>,
+}
+
+impl ProfileFieldSchema {
+    /// Convert the raw input `value` to the declared type
+    fn parse(&self, value: &str) -> Result<Value, String> {
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(value) {
+                return Err(format!("{} has an invalid format", self.name));
+            }
+        }
+
+        let invalid = |kind: &str| format!("{} must be {}", self.name, kind);
+        match self.field_type {
+            ProfileFieldType::String => Ok(Value::from(value)),
+            ProfileFieldType::Integer => value
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| invalid("an integer")),
+            ProfileFieldType::Number => value
+                .parse::<f64>()
+                .map(Value::from)
+                .map_err(|_| invalid("a number")),
+            ProfileFieldType::Boolean => value
+                .parse::<bool>()
+                .map(Value::from)
+                .map_err(|_| invalid("true or false")),
+            ProfileFieldType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|_| Value::from(value))
+                .map_err(|_| invalid("a YYYY-MM-DD date")),
+        }
+    }
+}
+
+/// The `AuthSchema` describing the custom profile fields of every account
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ProfileSchema {
+    #[serde(default)]
+    pub schema: String,
+    #[serde(default)]
+    pub fields: Vec<ProfileFieldSchema>,
+}
+
+impl ProfileSchema {
+    /// Parse a schema from json and compile the field patterns
+    pub fn from_json(content: &str) -> AppResult<Self> {
+        let mut schema = serde_json::from_str::<ProfileSchema>(content)
+            .map_err(|e| ApiError::InternalServerError(format!("invalid profile schema: {}", e)))?;
+
+        let mut names = HashSet::new();
+        for field in schema.fields.iter_mut() {
+            if !names.insert(field.name.clone()) {
+                return Err(ApiError::InternalServerError(format!(
+                    "profile field {} is declared twice",
+                    field.name
+                )));
+            }
+
+            if let Some(pattern) = &field.pattern {
+                let regex = Regex::new(pattern).map_err(|e| {
+                    ApiError::InternalServerError(format!(
+                        "invalid pattern for profile field {}: {}",
+                        field.name, e
+                    ))
+                })?;
+                field.regex = Some(regex);
+            }
+        }
+
+        Ok(schema)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&ProfileFieldSchema> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Validate raw profile `values` and convert them to their declared types
+    ///
+    /// Missing required fields are only reported when `partial` is false, empty values
+    /// clear a field and are returned as null
+    pub fn validate<'a, I>(&self, values: I, partial: bool) -> AppResult<Map<String, Value>>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut errors = vec![];
+        let mut seen = HashSet::new();
+        let mut fields = Map::new();
+
+        for (name, value) in values {
+            seen.insert(name);
+            let field = match self.field(name) {
+                None => {
+                    errors.push(format!("{} is not a profile field", name));
+                    continue;
+                }
+                Some(f) => f,
+            };
+
+            if value.is_empty() {
+                if field.required {
+                    errors.push(format!("{} is required", name));
+                } else {
+                    fields.insert(name.to_string(), Value::Null);
+                }
+                continue;
+            }
+
+            match field.parse(value) {
+                Ok(v) => {
+                    fields.insert(name.to_string(), v);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if !partial {
+            for field in self.fields.iter().filter(|f| f.required) {
+                if !seen.contains(field.name.as_str()) {
+                    errors.push(format!("{} is required", field.name));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(fields)
+        } else {
+            Err(ApiError::ValidationError(errors))
+        }
+    }
+}
+
+/// Load the schema file at `path`, an empty path declares no custom fields
+pub fn load_profile_schema(path: &str) -> AppResult<ProfileSchema> {
+    if path.is_empty() {
+        return Ok(ProfileSchema::default());
+    }
+
+    let mut content = String::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut content))
+        .map_err(|e| {
+            ApiError::InternalServerError(format!("can't read profile schema {}: {}", path, e))
+        })?;
+
+    ProfileSchema::from_json(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_schema() -> ProfileSchema {
+        ProfileSchema::from_json(
+            r#"{
+                "schema": "AuthSchema",
+                "fields": [
+                    { "name": "first_name", "type": "string", "required": true },
+                    { "name": "age", "type": "integer" },
+                    { "name": "born_on", "type": "date" },
+                    { "name": "employee_id", "unique": true, "pattern": "^E[0-9]{4}$" }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn it_loads_the_template_schema() {
+        let schema = load_profile_schema("./template/form.json").unwrap();
+        assert_eq!(schema.schema, "AuthSchema");
+        assert!(schema.field("employee_id").unwrap().unique);
+    }
+
+    #[test]
+    fn it_converts_values_to_their_types() {
+        let fields = get_schema()
+            .validate(
+                vec![
+                    ("first_name", "Ada"),
+                    ("age", "36"),
+                    ("born_on", "1815-12-10"),
+                    ("employee_id", "E0001"),
+                ],
+                false,
+            )
+            .unwrap();
+        assert_eq!(fields["age"], Value::from(36));
+        assert_eq!(fields["born_on"], Value::from("1815-12-10"));
+    }
+
+    #[test]
+    fn it_rejects_invalid_values() {
+        let err = get_schema()
+            .validate(
+                vec![("age", "old"), ("employee_id", "X1"), ("nickname", "a")],
+                false,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ApiError::ValidationError(vec![
+                "age must be an integer".to_string(),
+                "employee_id has an invalid format".to_string(),
+                "nickname is not a profile field".to_string(),
+                "first_name is required".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_allows_partial_updates() {
+        let fields = get_schema().validate(vec![("age", "")], true).unwrap();
+        assert_eq!(fields["age"], Value::Null);
+        assert!(get_schema()
+            .validate(vec![("first_name", "")], true)
+            .is_err());
+    }
+}
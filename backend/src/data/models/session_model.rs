@@ -0,0 +1,59 @@
+//! Account session model crate
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// A login of an account, its id is the `jti` of the tokens issued for it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionModel {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub account_id: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// The organization the tokens of the session act in
+    #[serde(default)]
+    pub organization_id: Option<String>,
+    /// The admin impersonating the account through the session
+    #[serde(default)]
+    pub impersonated_by: Option<String>,
+}
+
+impl SessionModel {
+    /// Whether tokens of the session are still accepted at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn it_ends_revoked_and_expired_sessions() {
+        let now = Utc::now();
+        let mut session = SessionModel {
+            id: "7c2b3a56-4f1d-4a8e-9d5b-1c0e2f3a4b5c".to_string(),
+            account_id: "1234".to_string(),
+            ip: None,
+            user_agent: None,
+            created_at: now,
+            last_seen_at: now,
+            expires_at: now + Duration::hours(1),
+            revoked_at: None,
+            organization_id: None,
+            impersonated_by: None,
+        };
+        assert!(session.is_active(now));
+        assert!(!session.is_active(now + Duration::hours(2)));
+
+        session.revoked_at = Some(now);
+        assert!(!session.is_active(now));
+    }
+}
@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::common::helpers::AppResult;
+use crate::data::models::account_activity_model::AccountActivityModel;
+
+#[derive(Debug, Default)]
+pub struct CreateActivityCommand {
+    pub account_id: Option<String>,
+    pub log_name: String,
+    pub description: String,
+    pub subject_id: Option<String>,
+    pub subject_type: Option<String>,
+    pub causer_id: Option<String>,
+    pub causer_type: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub correlation_id: Option<String>,
+    pub properties: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FindActivitiesCommand {
+    /// Only return activities of this account, all activities otherwise
+    pub account_id: Option<String>,
+    /// 1 based page number
+    pub page: i64,
+    pub per_page: i64,
+}
+
+#[async_trait]
+/// Store trait for the account activity audit log, implemented by all store type
+pub trait ActivityStoreTrait {
+    /// Record an account activity
+    ///
+    /// Returns the recorded activity
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The activity payload CreateActivityCommand
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// let opts = CreateActivityCommand {
+    ///     account_id: Option::from("id".to_string()),
+    ///     log_name: "login".to_string(),
+    ///     description: "logged in".to_string(),
+    ///     ..Default::default()
+    /// }
+    /// let activity = BoxedStoreType::activity_create(opts).await?;
+    async fn activity_create(&self, cmd: CreateActivityCommand) -> AppResult<AccountActivityModel>;
+
+    /// Find a page of activities, newest first
+    ///
+    /// Returns the activities of the page and the total number of matching activities
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The account filter and page FindActivitiesCommand
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// let opts = FindActivitiesCommand {
+    ///     account_id: None,
+    ///     page: 1,
+    ///     per_page: 20,
+    /// }
+    /// let (activities, total) = BoxedStoreType::activity_find(opts).await?;
+    async fn activity_find(
+        &self,
+        cmd: FindActivitiesCommand,
+    ) -> AppResult<(Vec<AccountActivityModel>, i64)>;
+
+    /// Count the activities named `log_name` attempted with `identity` since `since`
+    ///
+    /// Activities keep the identity in their `identity` property, e.g. failed logins of known
+    /// and unknown identities alike
+    ///
+    /// # Arguments
+    ///
+    /// * `log_name` - The activity kind, e.g. `login_failed`
+    /// * `identity` - The username, email or mobile the activity was attempted with
+    /// * `since` - Only count activities recorded at or after this time
+    async fn activity_count_by_identity(
+        &self,
+        log_name: &str,
+        identity: &str,
+        since: DateTime<Utc>,
+    ) -> AppResult<i64>;
+}
@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::common::helpers::AppResult;
+use crate::data::models::api_key_model::ApiKeyModel;
+
+#[derive(Debug)]
+pub struct CreateApiKeyCommand {
+    /// The public lookup id of the key
+    pub id: String,
+    pub account_id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub secret_hash: String,
+    pub created_by: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+/// Store trait for the api keys of service accounts, implemented by all store type
+pub trait ApiKeyStoreTrait {
+    /// Record a new api key
+    ///
+    /// Returns the recorded key
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The key payload CreateApiKeyCommand
+    async fn api_key_create(&self, cmd: CreateApiKeyCommand) -> AppResult<ApiKeyModel>;
+
+    /// Find a key by its id, revoked and expired keys included
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The public id of the key
+    async fn api_key_find_by_id(&self, id: &str) -> AppResult<ApiKeyModel>;
+
+    /// Find the keys of an account, newest first
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - An account ID
+    async fn api_key_find_by_account(&self, account_id: &str) -> AppResult<Vec<ApiKeyModel>>;
+
+    /// Record that a key was used just now
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The public id of the key
+    async fn api_key_touch(&self, id: &str) -> AppResult<()>;
+
+    /// Revoke a key of an account
+    ///
+    /// Returns whether an active key was revoked
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - An account ID
+    /// * `id` - The public id of the key
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// let revoked = BoxedStoreType::api_key_revoke(account_id, key_id).await?;
+    async fn api_key_revoke(&self, account_id: &str, id: &str) -> AppResult<bool>;
+}
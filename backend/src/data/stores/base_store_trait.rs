@@ -1,10 +1,16 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
 use crate::common::errors::ApiError;
 use crate::common::helpers::AppResult;
 use crate::config::DatastoreConfig;
 use crate::data::models::accounts_model::AccountModel;
 use crate::data::models::onetime_code_model::OneTimeCodeModel;
+use crate::data::stores::activity_store_trait::ActivityStoreTrait;
+use crate::data::stores::api_key_store_trait::ApiKeyStoreTrait;
+use crate::data::stores::organization_store_trait::OrganizationStoreTrait;
+use crate::data::stores::profile_store_trait::ProfileStoreTrait;
+use crate::data::stores::session_store_trait::SessionStoreTrait;
 
 #[derive(strum_macros::ToString, Debug)]
 pub enum TableNames {
@@ -12,6 +18,22 @@ pub enum TableNames {
     Accounts,
     #[strum(serialize = "one_time_codes")]
     OneTimeCodes,
+    #[strum(serialize = "account_activities")]
+    AccountActivities,
+    #[strum(serialize = "user_profiles")]
+    UserProfiles,
+    #[strum(serialize = "account_sessions")]
+    AccountSessions,
+    #[strum(serialize = "api_keys")]
+    ApiKeys,
+    #[strum(serialize = "organizations")]
+    Organizations,
+    #[strum(serialize = "organization_memberships")]
+    OrganizationMemberships,
+    #[strum(serialize = "organization_invitations")]
+    OrganizationInvitations,
+    #[strum(serialize = "signup_tokens")]
+    SignupTokens,
 }
 
 #[derive(Debug)]
@@ -20,18 +42,31 @@ pub struct CreateAccountCommand {
     pub email: Option<String>,
     pub username: Option<String>,
     pub mobile: Option<String>,
+    /// Machine account that authenticates with api keys only
+    pub service_account: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateAccountCommand {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mobile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
 }
 
 #[async_trait]
 /// A generic store trait implemented by all store type
-pub trait BaseStoreTrait {
+pub trait BaseStoreTrait:
+    ActivityStoreTrait
+    + ApiKeyStoreTrait
+    + OrganizationStoreTrait
+    + ProfileStoreTrait
+    + SessionStoreTrait
+{
     /// Create a new account
     ///
     /// Returns Self or BoxedStoreType
@@ -52,6 +87,22 @@ pub trait BaseStoreTrait {
 
     async fn index_db(&self) -> AppResult<()>;
 
+    /// The tenant whose accounts the store reads and writes
+    fn tenant(&self) -> &str;
+
+    /// The same store scoped to the accounts of `tenant`
+    ///
+    /// Returns a BoxedStoreType sharing the connection
+    ///
+    /// # Arguments
+    ///
+    /// * `tenant` - A tenant id
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// let store = BoxedStoreType::for_tenant("acme");
+    fn for_tenant(&self, tenant: &str) -> BoxedStoreType;
+
     /// Create a new account
     ///
     /// Returns the account if found or throws an error
@@ -167,12 +218,13 @@ pub trait BaseStoreTrait {
     /// # Arguments
     ///
     /// * `id` - An account ID
+    /// * `updated_by` - The ID of the account performing the action
     ///
     /// # Examples
     /// use crate::data::stores::base_store_trait::BoxedStoreType;
     ///
-    /// let account = BoxedStoreType::account_lock("username").await?;
-    async fn account_lock(&self, id: &str) -> AppResult<bool>;
+    /// let account = BoxedStoreType::account_lock("id", "admin_id").await?;
+    async fn account_lock(&self, id: &str, updated_by: &str) -> AppResult<bool>;
 
     /// Un-Lock an account.
     ///
@@ -181,12 +233,13 @@ pub trait BaseStoreTrait {
     /// # Arguments
     ///
     /// * `id` - An account ID
+    /// * `updated_by` - The ID of the account performing the action
     ///
     /// # Examples
     /// use crate::data::stores::base_store_trait::BoxedStoreType;
     ///
-    /// let state = BoxedStoreType::account_unlock("username").await?;
-    async fn account_unlock(&self, id: &str) -> AppResult<bool>;
+    /// let state = BoxedStoreType::account_unlock("id", "admin_id").await?;
+    async fn account_unlock(&self, id: &str, updated_by: &str) -> AppResult<bool>;
 
     /// Marks a account for requiring password change
     ///
@@ -195,18 +248,150 @@ pub trait BaseStoreTrait {
     /// # Arguments
     ///
     /// * `id` - An account ID
+    /// * `updated_by` - The ID of the account performing the action
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    ///
+    /// let state = BoxedStoreType::account_require_new_password("id", "admin_id").await?;
+    async fn account_require_new_password(&self, id: &str, updated_by: &str) -> AppResult<bool>;
+
+    /// Replace the roles and permissions granted to an account
+    ///
+    /// Returns boolean
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An account ID
+    /// * `roles` - The roles the account holds, e.g. `admin`
+    /// * `permissions` - Individual permissions, e.g. `account:lock`
+    /// * `updated_by` - The ID of the account performing the action
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    ///
+    /// let state = BoxedStoreType::account_set_roles("id", vec!["admin".to_string()], vec![], "admin_id").await?;
+    async fn account_set_roles(
+        &self,
+        id: &str,
+        roles: Vec<String>,
+        permissions: Vec<String>,
+        updated_by: &str,
+    ) -> AppResult<bool>;
+
+    /// Restore a soft deleted account
+    ///
+    /// Returns boolean
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An account ID
+    /// * `updated_by` - The ID of the account performing the action
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    ///
+    /// let state = BoxedStoreType::account_restore("id", "admin_id").await?;
+    async fn account_restore(&self, id: &str, updated_by: &str) -> AppResult<bool>;
+
+    /// Mark an account as confirmed or unconfirmed
+    ///
+    /// Returns boolean
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An account ID
+    /// * `confirmed` - Whether the account is confirmed
+    /// * `updated_by` - The ID of the account performing the action
     ///
     /// # Examples
     /// use crate::data::stores::base_store_trait::BoxedStoreType;
     ///
-    /// let state = BoxedStoreType::account_require_new_password("username").await?;
-    async fn account_require_new_password(&self, id: &str) -> AppResult<bool>;
+    /// let state = BoxedStoreType::account_set_confirmed("id", true, "admin_id").await?;
+    async fn account_set_confirmed(
+        &self,
+        id: &str,
+        confirmed: bool,
+        updated_by: &str,
+    ) -> AppResult<bool>;
 
-    async fn account_set_password(&self, id: &str, password: &str) -> AppResult<bool>;
+    /// Store an email change waiting for confirmation, `None` clears it
+    ///
+    /// Returns boolean
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An account ID
+    /// * `email` - The new email, kept in `unconfirmed_email` until confirmed
+    /// * `token` - The confirmation token sent to the new email
+    /// * `updated_by` - The ID of the account performing the action
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    ///
+    /// let state = BoxedStoreType::account_set_unconfirmed_email("id", Some("new@example.com"), Some("token"), "id").await?;
+    async fn account_set_unconfirmed_email(
+        &self,
+        id: &str,
+        email: Option<&str>,
+        token: Option<&str>,
+        updated_by: &str,
+    ) -> AppResult<bool>;
+
+    /// Store a mobile change waiting for one time code verification, `None` clears it
+    ///
+    /// Returns boolean
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An account ID
+    /// * `mobile` - The new mobile, kept in `unconfirmed_mobile` until verified
+    /// * `updated_by` - The ID of the account performing the action
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    ///
+    /// let state = BoxedStoreType::account_set_unconfirmed_mobile("id", Some("+44-7700900000"), "id").await?;
+    async fn account_set_unconfirmed_mobile(
+        &self,
+        id: &str,
+        mobile: Option<&str>,
+        updated_by: &str,
+    ) -> AppResult<bool>;
+
+    /// Replace the password hash of an account and mark the change time
+    ///
+    /// The previous hash is kept in `password_history`, trimmed to `history_size` entries
+    ///
+    /// Returns boolean
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An account ID
+    /// * `password` - The new password hash
+    /// * `history_size` - Number of previous hashes to keep
+    /// * `updated_by` - The ID of the account performing the action
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    ///
+    /// let state = BoxedStoreType::account_set_password("id", &hash("new password"), 5, "id").await?;
+    async fn account_set_password(
+        &self,
+        id: &str,
+        password: &str,
+        history_size: usize,
+        updated_by: &str,
+    ) -> AppResult<bool>;
 
     async fn account_set_last_login(&self, id: &str) -> AppResult<bool>;
 
-    async fn account_delete(&self, id: &str, hard_delete: bool) -> AppResult<bool>;
+    async fn account_delete(
+        &self,
+        id: &str,
+        hard_delete: bool,
+        updated_by: &str,
+    ) -> AppResult<bool>;
 
     async fn onetime_code_create(&self, id: &str) -> AppResult<OneTimeCodeModel>;
 
@@ -215,5 +400,18 @@ pub trait BaseStoreTrait {
         account_id: &str,
         code: Option<&str>,
     ) -> AppResult<OneTimeCodeModel>;
+
+    /// Count a wrong code sent for the one time code of `account_id`
+    ///
+    /// Returns the wrong codes so far, this one included
+    async fn onetime_code_fail(&self, account_id: &str) -> AppResult<i64>;
+
+    /// Remove the one time codes of `account_id` so they can't be used again
+    async fn onetime_code_delete(&self, account_id: &str) -> AppResult<()>;
+
+    /// Count a use of the signup token `id`, kept until the token expires at `expires_at`
+    ///
+    /// Returns the uses of the token so far, this one included
+    async fn signup_token_use(&self, id: &str, expires_at: DateTime<Utc>) -> AppResult<i64>;
 }
 pub type BoxedStoreType = Box<dyn BaseStoreTrait + Send + Sync>;
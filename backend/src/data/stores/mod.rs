@@ -14,4 +14,9 @@ pub mod postgres;
 pub mod cockroach;
 
 pub mod account_store;
+pub mod activity_store_trait;
+pub mod api_key_store_trait;
 pub mod base_store_trait;
+pub mod organization_store_trait;
+pub mod profile_store_trait;
+pub mod session_store_trait;
@@ -1,12 +1,12 @@
 use std::time::Duration as StdDuration;
 
 use async_trait::async_trait;
-use chrono::{Duration, SecondsFormat, Utc};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
 use futures::executor::block_on;
 use mongodb::{
     bson,
     bson::{doc, Document},
-    options::ClientOptions,
+    options::{ClientOptions, FindOneAndUpdateOptions, ReturnDocument},
     Client, Collection, Database,
 };
 use riker::actors::{ActorRefFactory, Timer};
@@ -18,20 +18,24 @@ use crate::config::{DatastoreConfig, CONFIG};
 use crate::data::models::accounts_model::AccountModel;
 use crate::data::models::onetime_code_model::OneTimeCodeModel;
 use crate::data::stores::base_store_trait::{
-    BaseStoreTrait, CreateAccountCommand, TableNames, UpdateAccountCommand,
+    BaseStoreTrait, BoxedStoreType, CreateAccountCommand, TableNames, UpdateAccountCommand,
 };
 use crate::data::stores::mongo::index_actor;
 use crate::data::stores::mongo::index_actor::IndexMongoActor;
 use crate::events::SYSTEM;
 
-fn get_id_query(id: &str) -> Document {
-    let oid = mongodb::bson::oid::ObjectId::with_string(id).unwrap();
-    doc! { "_id": oid, "delete_flag": false }
+/// The `_id` of the account `id`, a malformed id matches no account
+fn get_object_id(id: &str) -> AppResult<bson::oid::ObjectId> {
+    bson::oid::ObjectId::with_string(id)
+        .map_err(|_| ApiError::NotFound("account not found".to_string()))
 }
 
-fn get_id_query_without_delete(id: &str) -> Document {
-    let oid = mongodb::bson::oid::ObjectId::with_string(id).unwrap();
-    doc! { "_id": oid }
+fn get_id_query(id: &str) -> AppResult<Document> {
+    Ok(doc! { "_id": get_object_id(id)?, "delete_flag": false })
+}
+
+fn get_id_query_without_delete(id: &str) -> AppResult<Document> {
+    Ok(doc! { "_id": get_object_id(id)? })
 }
 
 #[derive(Clone)]
@@ -39,12 +43,23 @@ pub struct AccountStore {
     client: Client,
     config: DatastoreConfig,
     db: Database,
+    /// Accounts and activities are filtered by it, the records of an account are reached
+    /// through the account
+    tenant: String,
 }
 
 impl AccountStore {
+    /// `filter` narrowed to the documents of the tenant of the store
+    pub(crate) fn _tenant_filter(&self, mut filter: Document) -> Document {
+        filter.insert("tenant_id", self.tenant.as_str());
+        filter
+    }
+
     async fn _find_one_account(&self, filter: Document) -> AppResult<AccountModel> {
         let account_col = &self._get_collection(TableNames::Accounts);
-        let resp = account_col.find_one(filter, None).await?;
+        let resp = account_col
+            .find_one(self._tenant_filter(filter), None)
+            .await?;
 
         match resp {
             None => Err(ApiError::NotFound("account not found".to_string())),
@@ -57,7 +72,9 @@ impl AccountStore {
 
     async fn _update_one_account(&self, query: Document, payload: Document) -> AppResult<bool> {
         let account_col = &self._get_collection(TableNames::Accounts);
-        let rsp = account_col.update_one(query, payload, None).await;
+        let rsp = account_col
+            .update_one(self._tenant_filter(query), payload, None)
+            .await;
         match rsp {
             Ok(_) => Ok(true),
             Err(_) => Err(ApiError::DatabaseError(
@@ -66,7 +83,7 @@ impl AccountStore {
         }
     }
 
-    fn _get_collection(&self, col: TableNames) -> Collection<Document> {
+    pub(crate) fn _get_collection(&self, col: TableNames) -> Collection<Document> {
         self.db.collection::<Document>(col.to_string().as_str())
     }
 }
@@ -83,6 +100,7 @@ impl BaseStoreTrait for AccountStore {
             client,
             config,
             db: db.clone(),
+            tenant: CONFIG.tenancy.default_tenant.clone(),
         };
 
         let index_actor_rsp = SYSTEM.actor_of::<IndexMongoActor>("my-actor");
@@ -105,6 +123,17 @@ impl BaseStoreTrait for AccountStore {
         index_actor::index_db(&self.db).await
     }
 
+    fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    fn for_tenant(&self, tenant: &str) -> BoxedStoreType {
+        Box::new(AccountStore {
+            tenant: tenant.to_string(),
+            ..self.clone()
+        })
+    }
+
     async fn account_create(&self, cmd: CreateAccountCommand) -> AppResult<AccountModel> {
         let account_col = &self._get_collection(TableNames::Accounts);
 
@@ -131,12 +160,16 @@ impl BaseStoreTrait for AccountStore {
             "mobile": mobile,
             "locked": false,
             "require_new_password": false,
+            "roles": [],
+            "permissions": [],
             "password_changed_at": bson::Bson::Null,
             "created_at": &now,
             "updated_at": now,
             "password": cmd.password,
             "delete_flag": false,
             "enable_2fa": false,
+            "service_account": cmd.service_account,
+            "tenant_id": &self.tenant,
         };
 
         let result = account_col.insert_one(doc_data, None).await;
@@ -153,7 +186,7 @@ impl BaseStoreTrait for AccountStore {
     }
 
     async fn account_update(&self, id: &str, cmd: UpdateAccountCommand) -> AppResult<AccountModel> {
-        let query = get_id_query(&id);
+        let query = get_id_query(&id)?;
 
         let payload = mongodb::bson::to_bson::<UpdateAccountCommand>(&cmd)?;
         let update_payload = doc! {
@@ -175,7 +208,7 @@ impl BaseStoreTrait for AccountStore {
         }
     }
     async fn account_find_by_id(&self, id: &str) -> AppResult<AccountModel> {
-        let filter = get_id_query(&id);
+        let filter = get_id_query(&id)?;
         self._find_one_account(filter).await
     }
 
@@ -209,7 +242,7 @@ impl BaseStoreTrait for AccountStore {
         self._find_one_account(filter).await
     }
 
-    async fn account_lock(&self, id: &str) -> AppResult<bool> {
+    async fn account_lock(&self, id: &str, updated_by: &str) -> AppResult<bool> {
         let acct = self.account_find_by_id(&id).await?;
         if acct.locked {
             return Err(ApiError::Conflict(
@@ -217,11 +250,13 @@ impl BaseStoreTrait for AccountStore {
             ));
         }
 
-        let query = get_id_query(&id);
+        let query = get_id_query(&id)?;
         let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
 
         let update_payload = doc! { "$set": {
                 "locked": true,
+                "locked_at": &now,
+                "updated_by": updated_by,
                 "updated_at": now,
             },
         };
@@ -229,17 +264,19 @@ impl BaseStoreTrait for AccountStore {
         self._update_one_account(query, update_payload).await
     }
 
-    async fn account_unlock(&self, id: &str) -> AppResult<bool> {
+    async fn account_unlock(&self, id: &str, updated_by: &str) -> AppResult<bool> {
         let acct = self.account_find_by_id(&id).await?;
         if !acct.locked {
             return Err(ApiError::Conflict("the account is not locked".to_string()));
         }
 
-        let query = get_id_query(&id);
+        let query = get_id_query(&id)?;
         let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
 
         let update_payload = doc! { "$set": {
                 "locked": false,
+                "locked_at": bson::Bson::Null,
+                "updated_by": updated_by,
                 "updated_at": now,
             },
         };
@@ -247,13 +284,64 @@ impl BaseStoreTrait for AccountStore {
         self._update_one_account(query, update_payload).await
     }
 
-    async fn account_require_new_password(&self, id: &str) -> AppResult<bool> {
+    async fn account_require_new_password(&self, id: &str, updated_by: &str) -> AppResult<bool> {
         let _ = self.account_find_by_id(&id).await?;
-        let query = get_id_query(&id);
+        let query = get_id_query(&id)?;
         let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
 
         let update_payload = doc! { "$set": {
-                "locked": true,
+                "require_new_password": true,
+                "updated_by": updated_by,
+                "updated_at": now,
+            },
+        };
+
+        self._update_one_account(query, update_payload).await
+    }
+
+    async fn account_set_roles(
+        &self,
+        id: &str,
+        roles: Vec<String>,
+        permissions: Vec<String>,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        let _ = self.account_find_by_id(&id).await?;
+        let query = get_id_query(&id)?;
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let update_payload = doc! { "$set": {
+                "roles": roles,
+                "permissions": permissions,
+                "updated_by": updated_by,
+                "updated_at": now,
+            },
+        };
+
+        self._update_one_account(query, update_payload).await
+    }
+
+    async fn account_set_unconfirmed_email(
+        &self,
+        id: &str,
+        email: Option<&str>,
+        token: Option<&str>,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        let _ = self.account_find_by_id(&id).await?;
+        let query = get_id_query(&id)?;
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let sent_at = match email {
+            None => bson::Bson::Null,
+            Some(_) => bson::Bson::String(now.clone()),
+        };
+
+        let update_payload = doc! { "$set": {
+                "unconfirmed_email": email,
+                "confirmation_token": token,
+                "confirmation_sent_at": sent_at,
+                "updated_by": updated_by,
                 "updated_at": now,
             },
         };
@@ -261,14 +349,46 @@ impl BaseStoreTrait for AccountStore {
         self._update_one_account(query, update_payload).await
     }
 
-    async fn account_set_password(&self, id: &str, password: &str) -> AppResult<bool> {
+    async fn account_set_unconfirmed_mobile(
+        &self,
+        id: &str,
+        mobile: Option<&str>,
+        updated_by: &str,
+    ) -> AppResult<bool> {
         let _ = self.account_find_by_id(&id).await?;
-        let query = get_id_query(&id);
+        let query = get_id_query(&id)?;
         let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
 
+        let update_payload = doc! { "$set": {
+                "unconfirmed_mobile": mobile,
+                "updated_by": updated_by,
+                "updated_at": now,
+            },
+        };
+
+        self._update_one_account(query, update_payload).await
+    }
+
+    async fn account_set_password(
+        &self,
+        id: &str,
+        password: &str,
+        history_size: usize,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        let account = self.account_find_by_id(&id).await?;
+        let query = get_id_query(&id)?;
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let mut history = account.password_history;
+        history.insert(0, account.password);
+        history.truncate(history_size);
+
         let update_payload = doc! { "$set": {
                 "updated_at": now.clone(),
+                "updated_by": updated_by,
                 "password": password,
+                "password_history": history,
                 "require_new_password": false,
                 "password_changed_at": now,
             },
@@ -279,7 +399,7 @@ impl BaseStoreTrait for AccountStore {
 
     async fn account_set_last_login(&self, id: &str) -> AppResult<bool> {
         let _ = self.account_find_by_id(&id).await?;
-        let query = get_id_query(&id);
+        let query = get_id_query(&id)?;
         let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
 
         let update_payload = doc! { "$set": {
@@ -290,8 +410,13 @@ impl BaseStoreTrait for AccountStore {
         self._update_one_account(query, update_payload).await
     }
 
-    async fn account_delete(&self, id: &str, hard_delete: bool) -> AppResult<bool> {
-        let filter = get_id_query_without_delete(&id);
+    async fn account_delete(
+        &self,
+        id: &str,
+        hard_delete: bool,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        let filter = get_id_query_without_delete(&id)?;
         let resp = self._find_one_account(filter).await;
 
         if resp.is_err() {
@@ -302,19 +427,73 @@ impl BaseStoreTrait for AccountStore {
 
         if hard_delete {
             let account_col = &self._get_collection(TableNames::Accounts);
-            let query = get_id_query_without_delete(&id);
+            let query = self._tenant_filter(get_id_query_without_delete(&id)?);
             let _ = account_col.delete_one(query, None).await?;
+            return Ok(true);
         }
 
-        let query = get_id_query_without_delete(&id);
+        let query = get_id_query_without_delete(&id)?;
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
         let update_payload = doc! { "$set":
             {
                 "delete_flag": true,
+                "deleted_at": &now,
+                "updated_by": updated_by,
+                "updated_at": now,
             },
         };
         self._update_one_account(query, update_payload).await
     }
 
+    async fn account_restore(&self, id: &str, updated_by: &str) -> AppResult<bool> {
+        let filter = doc! {
+            "_id": get_object_id(id)?,
+            "delete_flag": true,
+        };
+        if self._find_one_account(filter.clone()).await.is_err() {
+            return Err(ApiError::NotFound(
+                "no deleted account with this id".to_string(),
+            ));
+        }
+
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let update_payload = doc! { "$set": {
+                "delete_flag": false,
+                "deleted_at": bson::Bson::Null,
+                "updated_by": updated_by,
+                "updated_at": now,
+            },
+        };
+
+        self._update_one_account(filter, update_payload).await
+    }
+
+    async fn account_set_confirmed(
+        &self,
+        id: &str,
+        confirmed: bool,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        let _ = self.account_find_by_id(&id).await?;
+        let query = get_id_query(&id)?;
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let confirmed_at = if confirmed {
+            bson::Bson::String(now.clone())
+        } else {
+            bson::Bson::Null
+        };
+
+        let update_payload = doc! { "$set": {
+                "confirmed_at": confirmed_at,
+                "updated_by": updated_by,
+                "updated_at": now,
+            },
+        };
+
+        self._update_one_account(query, update_payload).await
+    }
+
     async fn onetime_code_create(&self, account_id: &str) -> AppResult<OneTimeCodeModel> {
         let otp_col = &self._get_collection(TableNames::OneTimeCodes);
 
@@ -338,6 +517,7 @@ impl BaseStoreTrait for AccountStore {
 
         let doc_data = doc! {
             "code": code,
+            "failed_attempts": 0_i64,
             "expire_at": expire_at,
             "created_by": account_id,
             "created_at": now.clone(),
@@ -351,7 +531,7 @@ impl BaseStoreTrait for AccountStore {
         let id = bson::from_bson::<bson::oid::ObjectId>(resp.inserted_id)?;
 
         // get created otp
-        let filter = get_id_query(&id.to_hex());
+        let filter = get_id_query(&id.to_hex())?;
         let doc_resp = otp_col.find_one(filter, None).await?;
         let otp = bson::from_document::<OneTimeCodeModel>(doc_resp.unwrap())?;
         Ok(otp)
@@ -378,6 +558,50 @@ impl BaseStoreTrait for AccountStore {
             Some(docz) => Ok(bson::from_document::<OneTimeCodeModel>(docz)?),
         }
     }
+
+    async fn onetime_code_fail(&self, account_id: &str) -> AppResult<i64> {
+        let otp_col = &self._get_collection(TableNames::OneTimeCodes);
+        let update = doc! { "$inc": { "failed_attempts": 1_i64 } };
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(Some(ReturnDocument::After))
+            .build();
+
+        let doc_rsp = otp_col
+            .find_one_and_update(doc! { "created_by": account_id }, update, options)
+            .await?;
+        match doc_rsp {
+            None => Err(ApiError::DatabaseError(
+                "no onetime code for this account".to_string(),
+            )),
+            Some(docz) => Ok(bson::from_document::<OneTimeCodeModel>(docz)?.failed_attempts),
+        }
+    }
+
+    async fn onetime_code_delete(&self, account_id: &str) -> AppResult<()> {
+        let otp_col = &self._get_collection(TableNames::OneTimeCodes);
+        otp_col
+            .delete_many(doc! { "created_by": account_id }, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn signup_token_use(&self, id: &str, expires_at: DateTime<Utc>) -> AppResult<i64> {
+        let token_col = &self._get_collection(TableNames::SignupTokens);
+        let update = doc! {
+            "$inc": { "uses": 1_i64 },
+            // bson date the ttl index drops the token by
+            "$setOnInsert": { "expire_at": expires_at },
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(Some(true))
+            .return_document(Some(ReturnDocument::After))
+            .build();
+
+        let doc_rsp = token_col
+            .find_one_and_update(doc! { "_id": id }, update, options)
+            .await?;
+        Ok(doc_rsp.and_then(|d| d.get_i64("uses").ok()).unwrap_or(1))
+    }
 }
 
 #[cfg(test)]
@@ -385,6 +609,8 @@ mod tests {
     use super::*;
     use crate::config::DatastoreConfig;
     use crate::data::stores::base_store_trait::TableNames;
+    use crate::data::stores::profile_store_trait::ProfileStoreTrait;
+    use serde_json::{Map, Value};
 
     async fn get_db() -> AccountStore {
         let cfg = DatastoreConfig {
@@ -421,12 +647,24 @@ mod tests {
                 email: Some("test@test.com".to_string()),
                 username: Some("test".to_string()),
                 mobile: Some("35674677".to_string()),
+                service_account: false,
             })
             .await
     }
 
     async fn remove_doc(store: &AccountStore, id: String) {
-        let _ = store.account_delete(id.as_str(), false).await.unwrap();
+        let _ = store
+            .account_delete(id.as_str(), false, "test")
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn it_does_not_match_malformed_ids() {
+        let expect = ApiError::NotFound("account not found".to_string());
+        assert_eq!(get_id_query("not-an-object-id").unwrap_err(), expect);
+        assert_eq!(get_id_query_without_delete("").unwrap_err(), expect);
+        assert!(get_id_query(&bson::oid::ObjectId::new().to_hex()).is_ok());
     }
 
     #[actix_rt::test]
@@ -438,12 +676,13 @@ mod tests {
                 email: Some("test@test.com".to_string()),
                 username: Some("test".to_string()),
                 mobile: Some("35674677".to_string()),
+                service_account: false,
             })
             .await
             .unwrap();
 
         assert_eq!(acct.username.unwrap(), "test".to_string());
-        let _ = store.account_delete(acct.id.as_str(), true).await;
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
     }
 
     #[actix_rt::test]
@@ -456,6 +695,7 @@ mod tests {
                 email: None,
                 username: Some("tester".to_string()),
                 mobile: None,
+                service_account: false,
             })
             .await
             .unwrap();
@@ -467,13 +707,18 @@ mod tests {
                 email: None,
                 username: Some("test2".to_string()),
                 mobile: None,
+                service_account: false,
             })
             .await
             .unwrap();
         assert_eq!(second_acct.username.unwrap(), "test2".to_string());
 
-        let _ = store.account_delete(first_acct.id.as_str(), true).await;
-        let _ = store.account_delete(second_acct.id.as_str(), true).await;
+        let _ = store
+            .account_delete(first_acct.id.as_str(), true, "test")
+            .await;
+        let _ = store
+            .account_delete(second_acct.id.as_str(), true, "test")
+            .await;
     }
 
     #[actix_rt::test]
@@ -487,6 +732,7 @@ mod tests {
                 email: Some("test9@test.com".to_string()),
                 username: Some("test9".to_string()),
                 mobile: Some("35674679".to_string()),
+                service_account: false,
             })
             .await
             .unwrap();
@@ -497,12 +743,13 @@ mod tests {
                 email: Some("test9@test.com".to_string()),
                 username: Some("test9".to_string()),
                 mobile: Some("35674679".to_string()),
+                service_account: false,
             })
             .await
             .unwrap_err();
         let expect = ApiError::Conflict("account with auth not available".to_string());
         assert_eq!(result, expect);
-        let _ = store.account_delete(acct.id.as_str(), true).await;
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
     }
 
     #[actix_rt::test]
@@ -516,10 +763,11 @@ mod tests {
                 email: None,
                 username: Some("delete_user2".to_string()),
                 mobile: None,
+                service_account: false,
             })
             .await
             .unwrap();
-        let del_rsp = store.account_delete(acct.id.as_str(), false).await;
+        let del_rsp = store.account_delete(acct.id.as_str(), false, "test").await;
         assert_eq!(del_rsp.is_err(), false);
 
         let result = store
@@ -528,7 +776,7 @@ mod tests {
             .unwrap_err();
         let expect = ApiError::NotFound("account not found".to_string());
         assert_eq!(result, expect);
-        let _ = store.account_delete(acct.id.as_str(), true).await;
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
     }
 
     #[actix_rt::test]
@@ -542,10 +790,11 @@ mod tests {
                 email: None,
                 username: Some("delete_user2".to_string()),
                 mobile: None,
+                service_account: false,
             })
             .await
             .unwrap();
-        let del_rsp = store.account_delete(acct.id.as_str(), true).await;
+        let del_rsp = store.account_delete(acct.id.as_str(), true, "test").await;
         assert_eq!(del_rsp.is_err(), false);
 
         let result = store
@@ -563,12 +812,12 @@ mod tests {
         let acct = seed_db(&store).await.unwrap();
         assert_eq!(acct.locked, false);
 
-        let bool_resp = store.account_lock(acct.id.as_str()).await.unwrap();
+        let bool_resp = store.account_lock(acct.id.as_str(), "test").await.unwrap();
         assert_eq!(bool_resp, true);
 
         let result = store.account_find_by_id(acct.id.as_str()).await.unwrap();
         assert_eq!(result.locked, true);
-        let _ = store.account_delete(acct.id.as_str(), true).await;
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
     }
 
     #[actix_rt::test]
@@ -578,13 +827,172 @@ mod tests {
         let acct = seed_db(&store).await.unwrap();
         assert_eq!(acct.locked, false);
 
-        let bool_resp = store.account_lock(acct.id.as_str()).await.unwrap();
+        let bool_resp = store.account_lock(acct.id.as_str(), "test").await.unwrap();
         assert_eq!(bool_resp, true);
 
-        let result = store.account_lock(acct.id.as_str()).await.unwrap_err();
+        let result = store
+            .account_lock(acct.id.as_str(), "test")
+            .await
+            .unwrap_err();
         let expect = ApiError::Conflict("the account is already locked".to_string());
         assert_eq!(result, expect);
 
-        let _ = store.account_delete(acct.id.as_str(), true).await;
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
+    }
+
+    #[actix_rt::test]
+    async fn it_can_set_account_roles() {
+        let store = get_db().await;
+
+        let acct = seed_db(&store).await.unwrap();
+        assert_eq!(acct.roles.is_empty(), true);
+
+        let bool_resp = store
+            .account_set_roles(
+                acct.id.as_str(),
+                vec!["admin".to_string()],
+                vec!["account:lock".to_string()],
+                "test",
+            )
+            .await
+            .unwrap();
+        assert_eq!(bool_resp, true);
+
+        let result = store.account_find_by_id(acct.id.as_str()).await.unwrap();
+        assert_eq!(result.roles, vec!["admin".to_string()]);
+        assert_eq!(result.permissions, vec!["account:lock".to_string()]);
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
+    }
+
+    #[actix_rt::test]
+    async fn it_can_restore_a_soft_deleted_account() {
+        let store = get_db().await;
+
+        let acct = seed_db(&store).await.unwrap();
+        let _ = store
+            .account_delete(acct.id.as_str(), false, "admin")
+            .await
+            .unwrap();
+        assert!(store.account_find_by_id(acct.id.as_str()).await.is_err());
+
+        let bool_resp = store
+            .account_restore(acct.id.as_str(), "admin")
+            .await
+            .unwrap();
+        assert_eq!(bool_resp, true);
+
+        let result = store.account_find_by_id(acct.id.as_str()).await.unwrap();
+        assert_eq!(result.updated_by, Some("admin".to_string()));
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
+    }
+
+    #[actix_rt::test]
+    async fn it_can_confirm_an_account() {
+        let store = get_db().await;
+
+        let acct = seed_db(&store).await.unwrap();
+        assert_eq!(acct.confirmed_at.is_none(), true);
+
+        let _ = store
+            .account_set_confirmed(acct.id.as_str(), true, "admin")
+            .await
+            .unwrap();
+
+        let result = store.account_find_by_id(acct.id.as_str()).await.unwrap();
+        assert_eq!(result.confirmed_at.is_some(), true);
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
+    }
+
+    #[actix_rt::test]
+    async fn it_can_upsert_a_profile() {
+        let store = get_db().await;
+        let account_id = bson::oid::ObjectId::new().to_hex();
+
+        let mut fields = Map::new();
+        fields.insert("first_name".to_string(), Value::from("Ada"));
+        fields.insert("age".to_string(), Value::from(36));
+        let profile = store
+            .profile_upsert(&account_id, fields, &account_id)
+            .await
+            .unwrap();
+        assert_eq!(profile.fields["first_name"], Value::from("Ada"));
+
+        let mut fields = Map::new();
+        fields.insert("age".to_string(), Value::Null);
+        let profile = store
+            .profile_upsert(&account_id, fields, &account_id)
+            .await
+            .unwrap();
+        assert_eq!(profile.fields["first_name"], Value::from("Ada"));
+        assert_eq!(profile.fields.contains_key("age"), false);
+
+        let taken = store
+            .profile_field_taken("first_name", &Value::from("Ada"), Some(&account_id))
+            .await
+            .unwrap();
+        assert_eq!(taken, false);
+    }
+
+    #[actix_rt::test]
+    async fn it_can_set_an_unconfirmed_email() {
+        let store = get_db().await;
+        let acct = seed_db(&store).await.unwrap();
+
+        let _ = store
+            .account_set_unconfirmed_email(&acct.id, Some("new@test.com"), Some("token"), "test")
+            .await
+            .unwrap();
+        let pending = store.account_find_by_id(&acct.id).await.unwrap();
+        assert_eq!(pending.unconfirmed_email, Some("new@test.com".to_string()));
+        assert_eq!(pending.confirmation_token, Some("token".to_string()));
+        assert_eq!(pending.email, acct.email);
+
+        let _ = store
+            .account_set_unconfirmed_email(&acct.id, None, None, "test")
+            .await
+            .unwrap();
+        let cleared = store.account_find_by_id(&acct.id).await.unwrap();
+        assert_eq!(cleared.unconfirmed_email, None);
+        assert_eq!(cleared.confirmation_sent_at, None);
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
+    }
+
+    #[actix_rt::test]
+    async fn it_can_set_a_password_and_keep_its_history() {
+        let store = get_db().await;
+        let acct = seed_db(&store).await.unwrap();
+
+        let _ = store
+            .account_set_password(&acct.id, "second", 2, "test")
+            .await
+            .unwrap();
+        let _ = store
+            .account_set_password(&acct.id, "third", 2, "test")
+            .await
+            .unwrap();
+
+        let result = store.account_find_by_id(&acct.id).await.unwrap();
+        assert_eq!(result.password, "third".to_string());
+        assert_eq!(
+            result.password_history,
+            vec!["second".to_string(), acct.password]
+        );
+        assert!(result.password_changed_at.is_some());
+        let _ = store.account_delete(acct.id.as_str(), true, "test").await;
+    }
+
+    #[actix_rt::test]
+    async fn it_counts_the_wrong_codes_on_the_onetime_code() {
+        let store = get_db().await;
+        let account_id = bson::oid::ObjectId::new().to_hex();
+
+        let otp = store.onetime_code_create(&account_id).await.unwrap();
+        assert_eq!(otp.failed_attempts, 0);
+        assert_eq!(store.onetime_code_fail(&account_id).await.unwrap(), 1);
+        assert_eq!(store.onetime_code_fail(&account_id).await.unwrap(), 2);
+
+        // a code that is gone can't be guessed at, the failure is reported rather than ignored
+        store.onetime_code_delete(&account_id).await.unwrap();
+        assert!(store.onetime_code_fail(&account_id).await.is_err());
     }
 }
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson,
+    bson::{doc, Document},
+    options::FindOptions,
+};
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::data::models::account_activity_model::AccountActivityModel;
+use crate::data::stores::activity_store_trait::{
+    ActivityStoreTrait, CreateActivityCommand, FindActivitiesCommand,
+};
+use crate::data::stores::base_store_trait::{BaseStoreTrait, TableNames};
+use crate::data::stores::mongo::account_store::AccountStore;
+
+fn optional_string(value: Option<String>) -> bson::Bson {
+    match value {
+        None => bson::Bson::Null,
+        Some(s) => bson::Bson::String(s),
+    }
+}
+
+#[async_trait]
+impl ActivityStoreTrait for AccountStore {
+    async fn activity_create(&self, cmd: CreateActivityCommand) -> AppResult<AccountActivityModel> {
+        let activity_col = &self._get_collection(TableNames::AccountActivities);
+
+        let properties = match cmd.properties {
+            None => bson::Bson::Null,
+            Some(p) => bson::to_bson(&p)?,
+        };
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let doc_data = doc! {
+            "account_id": optional_string(cmd.account_id),
+            "log_name": cmd.log_name,
+            "description": cmd.description,
+            "subject_id": optional_string(cmd.subject_id),
+            "subject_type": optional_string(cmd.subject_type),
+            "causer_id": optional_string(cmd.causer_id),
+            "causer_type": optional_string(cmd.causer_type),
+            "ip": optional_string(cmd.ip),
+            "user_agent": optional_string(cmd.user_agent),
+            "correlation_id": optional_string(cmd.correlation_id),
+            "properties": properties,
+            "created_at": now,
+            "tenant_id": self.tenant(),
+        };
+
+        let resp = activity_col.insert_one(doc_data, None).await?;
+        let id = bson::from_bson::<bson::oid::ObjectId>(resp.inserted_id)?;
+
+        let doc_resp = activity_col.find_one(doc! { "_id": id }, None).await?;
+        match doc_resp {
+            None => Err(ApiError::DatabaseError(
+                "could not record the account activity".to_string(),
+            )),
+            Some(d) => Ok(bson::from_document::<AccountActivityModel>(d)?),
+        }
+    }
+
+    async fn activity_find(
+        &self,
+        cmd: FindActivitiesCommand,
+    ) -> AppResult<(Vec<AccountActivityModel>, i64)> {
+        let activity_col = &self._get_collection(TableNames::AccountActivities);
+
+        let filter = match cmd.account_id {
+            Some(account_id) => doc! { "account_id": account_id },
+            None => Document::new(),
+        };
+        let filter = self._tenant_filter(filter);
+        let skip = cmd
+            .page
+            .saturating_sub(1)
+            .max(0)
+            .saturating_mul(cmd.per_page.max(0)) as u64;
+        let options = FindOptions::builder()
+            .sort(Some(doc! { "created_at": -1 }))
+            .skip(Some(skip))
+            .limit(Some(cmd.per_page))
+            .build();
+
+        let total = activity_col.count_documents(filter.clone(), None).await?;
+        let mut cursor = activity_col.find(filter, options).await?;
+
+        let mut activities = vec![];
+        while let Some(d) = cursor.try_next().await? {
+            activities.push(bson::from_document::<AccountActivityModel>(d)?);
+        }
+
+        Ok((activities, total as i64))
+    }
+
+    async fn activity_count_by_identity(
+        &self,
+        log_name: &str,
+        identity: &str,
+        since: DateTime<Utc>,
+    ) -> AppResult<i64> {
+        let activity_col = &self._get_collection(TableNames::AccountActivities);
+        let filter = self._tenant_filter(doc! {
+            "log_name": log_name,
+            "properties.identity": identity,
+            "created_at": { "$gte": since.to_rfc3339_opts(SecondsFormat::Millis, true) },
+        });
+
+        let count = activity_col.count_documents(filter, None).await?;
+        Ok(count as i64)
+    }
+}
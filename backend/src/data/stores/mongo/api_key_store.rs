@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use chrono::{SecondsFormat, Utc};
+use futures::TryStreamExt;
+use mongodb::{bson, bson::doc, options::FindOptions};
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::data::models::api_key_model::ApiKeyModel;
+use crate::data::stores::api_key_store_trait::{ApiKeyStoreTrait, CreateApiKeyCommand};
+use crate::data::stores::base_store_trait::TableNames;
+use crate::data::stores::mongo::account_store::AccountStore;
+
+/// Dates are stored as sortable rfc3339 strings like the other collections
+fn now_string() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[async_trait]
+impl ApiKeyStoreTrait for AccountStore {
+    async fn api_key_create(&self, cmd: CreateApiKeyCommand) -> AppResult<ApiKeyModel> {
+        let key_col = &self._get_collection(TableNames::ApiKeys);
+        let expires_at = match cmd.expires_at {
+            None => bson::Bson::Null,
+            Some(at) => bson::Bson::String(at.to_rfc3339_opts(SecondsFormat::Millis, true)),
+        };
+        let created_by = match cmd.created_by {
+            None => bson::Bson::Null,
+            Some(s) => bson::Bson::String(s),
+        };
+
+        let doc_data = doc! {
+            "_id": &cmd.id,
+            "account_id": cmd.account_id,
+            "name": cmd.name,
+            "scopes": cmd.scopes,
+            "secret_hash": cmd.secret_hash,
+            "created_by": created_by,
+            "created_at": now_string(),
+            "expires_at": expires_at,
+            "last_used_at": bson::Bson::Null,
+            "revoked_at": bson::Bson::Null,
+        };
+
+        key_col.insert_one(doc_data, None).await?;
+        self.api_key_find_by_id(&cmd.id).await
+    }
+
+    async fn api_key_find_by_id(&self, id: &str) -> AppResult<ApiKeyModel> {
+        let key_col = &self._get_collection(TableNames::ApiKeys);
+        match key_col.find_one(doc! { "_id": id }, None).await? {
+            None => Err(ApiError::NotFound("api key not found".to_string())),
+            Some(d) => Ok(bson::from_document::<ApiKeyModel>(d)?),
+        }
+    }
+
+    async fn api_key_find_by_account(&self, account_id: &str) -> AppResult<Vec<ApiKeyModel>> {
+        let key_col = &self._get_collection(TableNames::ApiKeys);
+        let options = FindOptions::builder()
+            .sort(Some(doc! { "created_at": -1 }))
+            .build();
+
+        let mut cursor = key_col
+            .find(doc! { "account_id": account_id }, options)
+            .await?;
+        let mut keys = vec![];
+        while let Some(d) = cursor.try_next().await? {
+            keys.push(bson::from_document::<ApiKeyModel>(d)?);
+        }
+
+        Ok(keys)
+    }
+
+    async fn api_key_touch(&self, id: &str) -> AppResult<()> {
+        let key_col = &self._get_collection(TableNames::ApiKeys);
+        key_col
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "last_used_at": now_string() } },
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn api_key_revoke(&self, account_id: &str, id: &str) -> AppResult<bool> {
+        let key_col = &self._get_collection(TableNames::ApiKeys);
+        let filter = doc! {
+            "_id": id,
+            "account_id": account_id,
+            "revoked_at": bson::Bson::Null,
+        };
+
+        let rsp = key_col
+            .update_one(
+                filter,
+                doc! { "$set": { "revoked_at": now_string() } },
+                None,
+            )
+            .await?;
+        Ok(rsp.modified_count > 0)
+    }
+}
@@ -1,4 +1,7 @@
-use mongodb::{bson::doc, Database};
+use mongodb::{
+    bson::{doc, Document},
+    Database,
+};
 use riker::actors::{Actor, Context, Sender};
 use slog::{info, warn};
 use tokio::runtime;
@@ -6,29 +9,53 @@ use tokio::runtime;
 use crate::common::helpers::AppResult;
 use crate::common::utils::logger_utils::LOGGER;
 use crate::config::CONFIG;
+use crate::data::models::profile_schema::PROFILE_SCHEMA;
 use crate::data::stores::mongo::mongo_index_builder::{
-    sync_indexes, CollectionConfig, IndexOption, Indexes, MongoIndex,
+    sync_indexes, CollectionConfig, IndexOption, Indexes, MongoIndex, SortOrder,
 };
 
+/// Move the accounts and activities recorded before tenancy to the default tenant
+async fn backfill_tenant(db: &Database) -> AppResult<()> {
+    let filter = doc! { "tenant_id": { "$exists": false } };
+    let update = doc! { "$set": { "tenant_id": &CONFIG.tenancy.default_tenant } };
+
+    for name in &["accounts", "account_activities"] {
+        let col = db.collection::<Document>(name);
+        let rsp = col
+            .update_many(filter.clone(), update.clone(), None)
+            .await?;
+        if rsp.modified_count > 0 {
+            info!(
+                LOGGER,
+                "[indexing] {} {} moved to the default tenant", rsp.modified_count, name
+            );
+        }
+    }
+    Ok(())
+}
+
 async fn index_account_collection(db: &Database) -> AppResult<()> {
-    // index accounts collection
+    // index accounts collection, identities are unique within a tenant
     let index = Indexes::new()
         .with(
-            MongoIndex::new("username")
+            MongoIndex::new("tenant_id")
+                .with_key("username")
                 .with_option(IndexOption::Unique)
                 .with_option(IndexOption::PartialFilterExpression(
                     doc! { "username": { "$type": "string" } },
                 )),
         )
         .with(
-            MongoIndex::new("email")
+            MongoIndex::new("tenant_id")
+                .with_key("email")
                 .with_option(IndexOption::Unique)
                 .with_option(IndexOption::PartialFilterExpression(
                     doc! { "email": { "$type": "string" } },
                 )),
         )
         .with(
-            MongoIndex::new("mobile")
+            MongoIndex::new("tenant_id")
+                .with_key("mobile")
                 .with_option(IndexOption::Unique)
                 .with_option(IndexOption::PartialFilterExpression(
                     doc! { "mobile": { "$type": "string" } },
@@ -94,8 +121,250 @@ async fn index_onetime_collection(db: &Database) -> AppResult<()> {
     }
 }
 
+async fn index_activity_collection(db: &Database) -> AppResult<()> {
+    // index account activities collection
+    let index = Indexes::new()
+        .with(
+            MongoIndex::new("account_id")
+                .with_key_with_direction("created_at", SortOrder::Descending),
+        )
+        .with(
+            MongoIndex::new("tenant_id")
+                .with_key_with_direction("created_at", SortOrder::Descending),
+        )
+        .with(MongoIndex::new("correlation_id"))
+        .with(
+            MongoIndex::new("properties.identity")
+                .with_key("log_name")
+                .with_key_with_direction("created_at", SortOrder::Descending),
+        );
+
+    let s = sync_indexes(
+        db,
+        CollectionConfig {
+            collection_name: "account_activities",
+            indexes: index,
+        },
+    )
+    .await;
+
+    match s {
+        Ok(_) => {
+            info!(LOGGER, "[indexing] account_activities collection indexed");
+            Ok(())
+        }
+        Err(e) => {
+            warn!(
+                LOGGER,
+                "[indexing] account_activities collection indexing failed"
+            );
+            Err(e)
+        }
+    }
+}
+
+async fn index_profile_collection(db: &Database) -> AppResult<()> {
+    // index user profiles collection and the unique fields of the profile schema
+    let mut index =
+        Indexes::new().with(MongoIndex::new("account_id").with_option(IndexOption::Unique));
+
+    for field in PROFILE_SCHEMA.fields.iter().filter(|f| f.unique) {
+        let key = format!("fields.{}", field.name);
+        index = index.with(
+            MongoIndex::new(key.clone())
+                .with_option(IndexOption::Unique)
+                .with_option(IndexOption::PartialFilterExpression(
+                    doc! { key: { "$exists": true } },
+                )),
+        );
+    }
+
+    let s = sync_indexes(
+        db,
+        CollectionConfig {
+            collection_name: "user_profiles",
+            indexes: index,
+        },
+    )
+    .await;
+
+    match s {
+        Ok(_) => {
+            info!(LOGGER, "[indexing] user_profiles collection indexed");
+            Ok(())
+        }
+        Err(e) => {
+            warn!(
+                LOGGER,
+                "[indexing] user_profiles collection indexing failed"
+            );
+            Err(e)
+        }
+    }
+}
+
+async fn index_session_collection(db: &Database) -> AppResult<()> {
+    // index account sessions collection, sessions are dropped a day after they expire
+    let index = Indexes::new()
+        .with(
+            MongoIndex::new("account_id")
+                .with_key_with_direction("last_seen_at", SortOrder::Descending),
+        )
+        // expired impersonations the sweep ends
+        .with(
+            MongoIndex::new("tenant_id")
+                .with_key("impersonated_by")
+                .with_key("revoked_at"),
+        )
+        .with(MongoIndex::new("expire_at").with_option(IndexOption::ExpireAfterSeconds(86400)));
+
+    let s = sync_indexes(
+        db,
+        CollectionConfig {
+            collection_name: "account_sessions",
+            indexes: index,
+        },
+    )
+    .await;
+
+    match s {
+        Ok(_) => {
+            info!(LOGGER, "[indexing] account_sessions collection indexed");
+            Ok(())
+        }
+        Err(e) => {
+            warn!(
+                LOGGER,
+                "[indexing] account_sessions collection indexing failed"
+            );
+            Err(e)
+        }
+    }
+}
+
+async fn index_api_key_collection(db: &Database) -> AppResult<()> {
+    // index api keys collection
+    let index = Indexes::new().with(
+        MongoIndex::new("account_id").with_key_with_direction("created_at", SortOrder::Descending),
+    );
+
+    let s = sync_indexes(
+        db,
+        CollectionConfig {
+            collection_name: "api_keys",
+            indexes: index,
+        },
+    )
+    .await;
+
+    match s {
+        Ok(_) => {
+            info!(LOGGER, "[indexing] api_keys collection indexed");
+            Ok(())
+        }
+        Err(e) => {
+            warn!(LOGGER, "[indexing] api_keys collection indexing failed");
+            Err(e)
+        }
+    }
+}
+
+async fn index_organization_collections(db: &Database) -> AppResult<()> {
+    // an account is a member of an organization once, invitations are looked up by token
+    let members = Indexes::new()
+        .with(
+            MongoIndex::new("organization_id")
+                .with_key("account_id")
+                .with_option(IndexOption::Unique),
+        )
+        .with(
+            MongoIndex::new("account_id")
+                .with_key_with_direction("created_at", SortOrder::Descending),
+        );
+    let invitations = Indexes::new()
+        .with(MongoIndex::new("token_hash").with_option(IndexOption::Unique))
+        .with(
+            MongoIndex::new("organization_id")
+                .with_key_with_direction("created_at", SortOrder::Descending),
+        );
+
+    let s = tokio::try_join!(
+        sync_indexes(
+            db,
+            CollectionConfig {
+                collection_name: "organization_memberships",
+                indexes: members,
+            },
+        ),
+        sync_indexes(
+            db,
+            CollectionConfig {
+                collection_name: "organization_invitations",
+                indexes: invitations,
+            },
+        )
+    );
+
+    match s {
+        Ok(_) => {
+            info!(LOGGER, "[indexing] organization collections indexed");
+            Ok(())
+        }
+        Err(e) => {
+            warn!(
+                LOGGER,
+                "[indexing] organization collections indexing failed"
+            );
+            Err(e)
+        }
+    }
+}
+
+async fn index_signup_token_collection(db: &Database) -> AppResult<()> {
+    // signup token uses are dropped once the token expires
+    let index = Indexes::new()
+        .with(MongoIndex::new("expire_at").with_option(IndexOption::ExpireAfterSeconds(0)));
+
+    let s = sync_indexes(
+        db,
+        CollectionConfig {
+            collection_name: "signup_tokens",
+            indexes: index,
+        },
+    )
+    .await;
+
+    match s {
+        Ok(_) => {
+            info!(LOGGER, "[indexing] signup_tokens collection indexed");
+            Ok(())
+        }
+        Err(e) => {
+            warn!(
+                LOGGER,
+                "[indexing] signup_tokens collection indexing failed"
+            );
+            Err(e)
+        }
+    }
+}
+
 pub async fn index_db(db: &Database) -> AppResult<()> {
-    let _ = tokio::try_join!(index_account_collection(&db), index_onetime_collection(&db));
+    // the tenant unique indexes only cover documents holding a tenant
+    if let Err(e) = backfill_tenant(db).await {
+        warn!(LOGGER, "[indexing] tenant backfill failed: {}", e);
+    }
+
+    let _ = tokio::try_join!(
+        index_account_collection(&db),
+        index_onetime_collection(&db),
+        index_activity_collection(&db),
+        index_profile_collection(&db),
+        index_session_collection(&db),
+        index_api_key_collection(&db),
+        index_organization_collections(&db),
+        index_signup_token_collection(&db)
+    );
     Ok(())
 }
 
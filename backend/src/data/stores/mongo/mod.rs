@@ -1,3 +1,8 @@
 pub mod account_store;
+mod activity_store;
+mod api_key_store;
 mod index_actor;
 mod mongo_index_builder;
+mod organization_store;
+mod profile_store;
+mod session_store;
@@ -0,0 +1,260 @@
+use async_trait::async_trait;
+use chrono::{SecondsFormat, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson,
+    bson::{doc, Document},
+    options::FindOptions,
+};
+use serde::de::DeserializeOwned;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::data::models::invitation_model::InvitationModel;
+use crate::data::models::organization_model::{
+    MembershipModel, OrganizationModel, OrganizationRole,
+};
+use crate::data::stores::base_store_trait::{BaseStoreTrait, TableNames};
+use crate::data::stores::mongo::account_store::AccountStore;
+use crate::data::stores::organization_store_trait::{
+    CreateInvitationCommand, CreateMembershipCommand, CreateOrganizationCommand,
+    OrganizationStoreTrait,
+};
+
+/// Dates are stored as sortable rfc3339 strings like the other collections
+fn now_string() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+impl AccountStore {
+    /// The documents of `table` matching `filter` in the tenant of the store, sorted by `sort`
+    async fn _find_organization_docs<T: DeserializeOwned>(
+        &self,
+        table: TableNames,
+        filter: Document,
+        sort: Document,
+    ) -> AppResult<Vec<T>> {
+        let col = &self._get_collection(table);
+        let options = FindOptions::builder().sort(Some(sort)).build();
+
+        let mut cursor = col.find(self._tenant_filter(filter), options).await?;
+        let mut items = vec![];
+        while let Some(d) = cursor.try_next().await? {
+            items.push(bson::from_document::<T>(d)?);
+        }
+
+        Ok(items)
+    }
+}
+
+#[async_trait]
+impl OrganizationStoreTrait for AccountStore {
+    async fn organization_create(
+        &self,
+        cmd: CreateOrganizationCommand,
+    ) -> AppResult<OrganizationModel> {
+        let org_col = &self._get_collection(TableNames::Organizations);
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = now_string();
+
+        let doc_data = doc! {
+            "_id": &id,
+            "name": cmd.name,
+            "created_by": cmd.created_by,
+            "created_at": &now,
+            "updated_at": now,
+            "tenant_id": self.tenant(),
+        };
+
+        org_col.insert_one(doc_data, None).await?;
+        self.organization_find_by_id(&id).await
+    }
+
+    async fn organization_find_by_id(&self, id: &str) -> AppResult<OrganizationModel> {
+        let org_col = &self._get_collection(TableNames::Organizations);
+        let filter = self._tenant_filter(doc! { "_id": id });
+        match org_col.find_one(filter, None).await? {
+            None => Err(ApiError::NotFound("organization not found".to_string())),
+            Some(d) => Ok(bson::from_document::<OrganizationModel>(d)?),
+        }
+    }
+
+    async fn membership_create(&self, cmd: CreateMembershipCommand) -> AppResult<MembershipModel> {
+        let member_col = &self._get_collection(TableNames::OrganizationMemberships);
+        let invited_by = match cmd.invited_by {
+            None => bson::Bson::Null,
+            Some(s) => bson::Bson::String(s),
+        };
+        let now = now_string();
+
+        let doc_data = doc! {
+            "_id": uuid::Uuid::new_v4().to_string(),
+            "organization_id": &cmd.organization_id,
+            "account_id": &cmd.account_id,
+            "role": cmd.role.as_str(),
+            "invited_by": invited_by,
+            "created_at": &now,
+            "updated_at": now,
+            "tenant_id": self.tenant(),
+        };
+
+        member_col.insert_one(doc_data, None).await?;
+        self.membership_find(&cmd.organization_id, &cmd.account_id)
+            .await
+    }
+
+    async fn membership_find(
+        &self,
+        organization_id: &str,
+        account_id: &str,
+    ) -> AppResult<MembershipModel> {
+        let member_col = &self._get_collection(TableNames::OrganizationMemberships);
+        let filter = self._tenant_filter(doc! {
+            "organization_id": organization_id,
+            "account_id": account_id,
+        });
+        match member_col.find_one(filter, None).await? {
+            None => Err(ApiError::NotFound("membership not found".to_string())),
+            Some(d) => Ok(bson::from_document::<MembershipModel>(d)?),
+        }
+    }
+
+    async fn membership_find_by_organization(
+        &self,
+        organization_id: &str,
+    ) -> AppResult<Vec<MembershipModel>> {
+        self._find_organization_docs(
+            TableNames::OrganizationMemberships,
+            doc! { "organization_id": organization_id },
+            doc! { "created_at": 1 },
+        )
+        .await
+    }
+
+    async fn membership_find_by_account(
+        &self,
+        account_id: &str,
+    ) -> AppResult<Vec<MembershipModel>> {
+        self._find_organization_docs(
+            TableNames::OrganizationMemberships,
+            doc! { "account_id": account_id },
+            doc! { "created_at": -1 },
+        )
+        .await
+    }
+
+    async fn membership_set_role(
+        &self,
+        organization_id: &str,
+        account_id: &str,
+        role: OrganizationRole,
+    ) -> AppResult<bool> {
+        let member_col = &self._get_collection(TableNames::OrganizationMemberships);
+        let filter = self._tenant_filter(doc! {
+            "organization_id": organization_id,
+            "account_id": account_id,
+        });
+
+        let rsp = member_col
+            .update_one(
+                filter,
+                doc! { "$set": { "role": role.as_str(), "updated_at": now_string() } },
+                None,
+            )
+            .await?;
+        Ok(rsp.matched_count > 0)
+    }
+
+    async fn membership_delete(&self, organization_id: &str, account_id: &str) -> AppResult<bool> {
+        let member_col = &self._get_collection(TableNames::OrganizationMemberships);
+        let filter = self._tenant_filter(doc! {
+            "organization_id": organization_id,
+            "account_id": account_id,
+        });
+
+        let rsp = member_col.delete_one(filter, None).await?;
+        Ok(rsp.deleted_count > 0)
+    }
+
+    async fn invitation_create(&self, cmd: CreateInvitationCommand) -> AppResult<InvitationModel> {
+        let invite_col = &self._get_collection(TableNames::OrganizationInvitations);
+        let id = uuid::Uuid::new_v4().to_string();
+
+        let doc_data = doc! {
+            "_id": &id,
+            "organization_id": cmd.organization_id,
+            "email": cmd.email,
+            "role": cmd.role.as_str(),
+            "token_hash": &cmd.token_hash,
+            "invited_by": cmd.invited_by,
+            "created_at": now_string(),
+            "expires_at": cmd.expires_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+            "accepted_at": bson::Bson::Null,
+            "accepted_by": bson::Bson::Null,
+            "revoked_at": bson::Bson::Null,
+            "tenant_id": self.tenant(),
+        };
+
+        invite_col.insert_one(doc_data, None).await?;
+        self.invitation_find_by_token_hash(&cmd.token_hash).await
+    }
+
+    async fn invitation_find_by_token_hash(&self, token_hash: &str) -> AppResult<InvitationModel> {
+        let invite_col = &self._get_collection(TableNames::OrganizationInvitations);
+        let filter = self._tenant_filter(doc! { "token_hash": token_hash });
+        match invite_col.find_one(filter, None).await? {
+            None => Err(ApiError::NotFound("invitation not found".to_string())),
+            Some(d) => Ok(bson::from_document::<InvitationModel>(d)?),
+        }
+    }
+
+    async fn invitation_find_open(&self, organization_id: &str) -> AppResult<Vec<InvitationModel>> {
+        self._find_organization_docs(
+            TableNames::OrganizationInvitations,
+            doc! {
+                "organization_id": organization_id,
+                "accepted_at": bson::Bson::Null,
+                "revoked_at": bson::Bson::Null,
+            },
+            doc! { "created_at": -1 },
+        )
+        .await
+    }
+
+    async fn invitation_accept(&self, id: &str, account_id: &str) -> AppResult<bool> {
+        let invite_col = &self._get_collection(TableNames::OrganizationInvitations);
+        let filter = self._tenant_filter(doc! {
+            "_id": id,
+            "accepted_at": bson::Bson::Null,
+            "revoked_at": bson::Bson::Null,
+        });
+
+        let rsp = invite_col
+            .update_one(
+                filter,
+                doc! { "$set": { "accepted_at": now_string(), "accepted_by": account_id } },
+                None,
+            )
+            .await?;
+        Ok(rsp.modified_count > 0)
+    }
+
+    async fn invitation_revoke(&self, organization_id: &str, id: &str) -> AppResult<bool> {
+        let invite_col = &self._get_collection(TableNames::OrganizationInvitations);
+        let filter = self._tenant_filter(doc! {
+            "_id": id,
+            "organization_id": organization_id,
+            "accepted_at": bson::Bson::Null,
+            "revoked_at": bson::Bson::Null,
+        });
+
+        let rsp = invite_col
+            .update_one(
+                filter,
+                doc! { "$set": { "revoked_at": now_string() } },
+                None,
+            )
+            .await?;
+        Ok(rsp.modified_count > 0)
+    }
+}
@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use chrono::{SecondsFormat, Utc};
+use mongodb::{
+    bson,
+    bson::{doc, Document},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+};
+use serde_json::{Map, Value};
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::data::models::profile_model::ProfileModel;
+use crate::data::stores::base_store_trait::TableNames;
+use crate::data::stores::mongo::account_store::AccountStore;
+use crate::data::stores::profile_store_trait::ProfileStoreTrait;
+
+#[async_trait]
+impl ProfileStoreTrait for AccountStore {
+    async fn profile_find_by_account(&self, account_id: &str) -> AppResult<ProfileModel> {
+        let profile_col = &self._get_collection(TableNames::UserProfiles);
+        let resp = profile_col
+            .find_one(doc! { "account_id": account_id }, None)
+            .await?;
+
+        match resp {
+            None => Err(ApiError::NotFound("profile not found".to_string())),
+            Some(d) => Ok(bson::from_document::<ProfileModel>(d)?),
+        }
+    }
+
+    async fn profile_upsert(
+        &self,
+        account_id: &str,
+        fields: Map<String, Value>,
+        updated_by: &str,
+    ) -> AppResult<ProfileModel> {
+        let profile_col = &self._get_collection(TableNames::UserProfiles);
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+
+        let mut set = doc! { "updated_at": &now, "updated_by": updated_by };
+        let mut unset = Document::new();
+        for (name, value) in fields {
+            let key = format!("fields.{}", name);
+            match value {
+                Value::Null => {
+                    unset.insert(key, "");
+                }
+                v => {
+                    set.insert(key, bson::to_bson(&v)?);
+                }
+            }
+        }
+
+        let mut update = doc! {
+            "$set": set,
+            "$setOnInsert": {
+                "account_id": account_id,
+                "created_at": now,
+                "created_by": updated_by,
+            },
+        };
+        if !unset.is_empty() {
+            update.insert("$unset", unset);
+        }
+
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(Some(true))
+            .return_document(Some(ReturnDocument::After))
+            .build();
+        let resp = profile_col
+            .find_one_and_update(doc! { "account_id": account_id }, update, options)
+            .await?;
+
+        match resp {
+            None => Err(ApiError::DatabaseError(
+                "there was a problem updating the profile".to_string(),
+            )),
+            Some(d) => Ok(bson::from_document::<ProfileModel>(d)?),
+        }
+    }
+
+    async fn profile_field_taken(
+        &self,
+        name: &str,
+        value: &Value,
+        account_id: Option<&str>,
+    ) -> AppResult<bool> {
+        let profile_col = &self._get_collection(TableNames::UserProfiles);
+
+        let mut filter = Document::new();
+        filter.insert(format!("fields.{}", name), bson::to_bson(value)?);
+        if let Some(id) = account_id {
+            filter.insert("account_id", doc! { "$ne": id });
+        }
+
+        let count = profile_col.count_documents(filter, None).await?;
+        Ok(count > 0)
+    }
+}
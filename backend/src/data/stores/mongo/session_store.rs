@@ -0,0 +1,182 @@
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson,
+    bson::{doc, Document},
+    options::FindOptions,
+};
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::data::models::session_model::SessionModel;
+use crate::data::stores::base_store_trait::{BaseStoreTrait, TableNames};
+use crate::data::stores::mongo::account_store::AccountStore;
+use crate::data::stores::session_store_trait::{CreateSessionCommand, SessionStoreTrait};
+
+fn optional_string(value: Option<String>) -> bson::Bson {
+    match value {
+        None => bson::Bson::Null,
+        Some(s) => bson::Bson::String(s),
+    }
+}
+
+/// Dates are stored as sortable rfc3339 strings like the other collections
+fn now_string() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Filter on the sessions of `account_id` that tokens are still accepted for
+fn active_filter(account_id: &str) -> Document {
+    doc! {
+        "account_id": account_id,
+        "revoked_at": bson::Bson::Null,
+        "expires_at": { "$gt": now_string() },
+    }
+}
+
+#[async_trait]
+impl SessionStoreTrait for AccountStore {
+    async fn session_create(&self, cmd: CreateSessionCommand) -> AppResult<SessionModel> {
+        let session_col = &self._get_collection(TableNames::AccountSessions);
+        let now = now_string();
+
+        let doc_data = doc! {
+            "_id": &cmd.id,
+            "account_id": cmd.account_id,
+            "ip": optional_string(cmd.ip),
+            "user_agent": optional_string(cmd.user_agent),
+            "created_at": &now,
+            "last_seen_at": now,
+            "expires_at": cmd.expires_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+            "revoked_at": bson::Bson::Null,
+            "organization_id": optional_string(cmd.organization_id),
+            "impersonated_by": optional_string(cmd.impersonated_by),
+            "tenant_id": self.tenant(),
+            // bson date the ttl index drops the session by
+            "expire_at": cmd.expires_at,
+        };
+
+        session_col.insert_one(doc_data, None).await?;
+        self.session_find_by_id(&cmd.id).await
+    }
+
+    async fn session_find_by_id(&self, id: &str) -> AppResult<SessionModel> {
+        let session_col = &self._get_collection(TableNames::AccountSessions);
+        match session_col.find_one(doc! { "_id": id }, None).await? {
+            None => Err(ApiError::NotFound("session not found".to_string())),
+            Some(d) => Ok(bson::from_document::<SessionModel>(d)?),
+        }
+    }
+
+    async fn session_find_active(&self, account_id: &str) -> AppResult<Vec<SessionModel>> {
+        let session_col = &self._get_collection(TableNames::AccountSessions);
+        let options = FindOptions::builder()
+            .sort(Some(doc! { "last_seen_at": -1 }))
+            .build();
+
+        let mut cursor = session_col.find(active_filter(account_id), options).await?;
+        let mut sessions = vec![];
+        while let Some(d) = cursor.try_next().await? {
+            sessions.push(bson::from_document::<SessionModel>(d)?);
+        }
+
+        Ok(sessions)
+    }
+
+    async fn session_touch(&self, id: &str, expires_at: Option<DateTime<Utc>>) -> AppResult<()> {
+        let session_col = &self._get_collection(TableNames::AccountSessions);
+
+        let mut set = doc! { "last_seen_at": now_string() };
+        if let Some(expires_at) = expires_at {
+            set.insert(
+                "expires_at",
+                expires_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+            );
+            set.insert("expire_at", expires_at);
+        }
+
+        session_col
+            .update_one(doc! { "_id": id }, doc! { "$set": set }, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn session_set_organization(
+        &self,
+        id: &str,
+        organization_id: Option<&str>,
+    ) -> AppResult<()> {
+        let session_col = &self._get_collection(TableNames::AccountSessions);
+        let organization_id = optional_string(organization_id.map(|o| o.to_string()));
+
+        session_col
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "organization_id": organization_id } },
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn session_revoke(
+        &self,
+        account_id: &str,
+        id: Option<&str>,
+        keep_id: Option<&str>,
+    ) -> AppResult<u64> {
+        let session_col = &self._get_collection(TableNames::AccountSessions);
+
+        let mut filter = active_filter(account_id);
+        match (id, keep_id) {
+            (Some(id), _) => filter.insert("_id", id),
+            (None, Some(keep_id)) => filter.insert("_id", doc! { "$ne": keep_id }),
+            (None, None) => None,
+        };
+
+        let rsp = session_col
+            .update_many(
+                filter,
+                doc! { "$set": { "revoked_at": now_string() } },
+                None,
+            )
+            .await?;
+        Ok(rsp.modified_count)
+    }
+
+    async fn session_end_expired_impersonations(&self) -> AppResult<Vec<SessionModel>> {
+        let session_col = &self._get_collection(TableNames::AccountSessions);
+        let filter = self._tenant_filter(doc! {
+            "impersonated_by": { "$ne": bson::Bson::Null },
+            "revoked_at": bson::Bson::Null,
+            "expires_at": { "$lte": now_string() },
+        });
+
+        let mut cursor = session_col.find(filter, None).await?;
+        let mut expired = vec![];
+        while let Some(d) = cursor.try_next().await? {
+            expired.push(bson::from_document::<SessionModel>(d)?);
+        }
+
+        // another replica may end the same sessions, only the one updating a session keeps it
+        let mut ended = vec![];
+        for session in expired {
+            let ended_at = session
+                .expires_at
+                .to_rfc3339_opts(SecondsFormat::Millis, true);
+            let rsp = session_col
+                .update_one(
+                    doc! { "_id": &session.id, "revoked_at": bson::Bson::Null },
+                    doc! { "$set": { "revoked_at": ended_at } },
+                    None,
+                )
+                .await?;
+            if rsp.modified_count == 1 {
+                ended.push(session);
+            }
+        }
+
+        Ok(ended)
+    }
+}
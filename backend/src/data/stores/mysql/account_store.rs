@@ -1,12 +1,32 @@
 use crate::common::errors::ApiError;
 use crate::common::helpers::AppResult;
-use crate::config::DatastoreConfig;
+use crate::config::{DatastoreConfig, CONFIG};
+use crate::data::models::account_activity_model::AccountActivityModel;
 use crate::data::models::accounts_model::AccountModel;
+use crate::data::models::api_key_model::ApiKeyModel;
+use crate::data::models::invitation_model::InvitationModel;
 use crate::data::models::onetime_code_model::OneTimeCodeModel;
+use crate::data::models::organization_model::{
+    MembershipModel, OrganizationModel, OrganizationRole,
+};
+use crate::data::models::profile_model::ProfileModel;
+use crate::data::models::session_model::SessionModel;
+use crate::data::stores::activity_store_trait::{
+    ActivityStoreTrait, CreateActivityCommand, FindActivitiesCommand,
+};
+use crate::data::stores::api_key_store_trait::{ApiKeyStoreTrait, CreateApiKeyCommand};
 use crate::data::stores::base_store_trait::{
-    BaseStoreTrait, CreateAccountCommand, UpdateAccountCommand,
+    BaseStoreTrait, BoxedStoreType, CreateAccountCommand, UpdateAccountCommand,
 };
+use crate::data::stores::organization_store_trait::{
+    CreateInvitationCommand, CreateMembershipCommand, CreateOrganizationCommand,
+    OrganizationStoreTrait,
+};
+use crate::data::stores::profile_store_trait::ProfileStoreTrait;
+use crate::data::stores::session_store_trait::{CreateSessionCommand, SessionStoreTrait};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
 
 pub struct AccountStore {
     pub client: String,
@@ -19,71 +39,135 @@ impl BaseStoreTrait for AccountStore {
     where
         Self: Sized,
     {
-        todo!()
+        not_implemented()
     }
 
     fn ping(&self) -> Result<(), ApiError> {
-        todo!()
+        not_implemented()
     }
 
     async fn index_db(&self) -> AppResult<()> {
-        todo!()
+        not_implemented()
+    }
+
+    fn tenant(&self) -> &str {
+        &CONFIG.tenancy.default_tenant
+    }
+
+    fn for_tenant(&self, tenant: &str) -> BoxedStoreType {
+        Box::new(AccountStore {
+            _client: self._client.clone(),
+            config: self.config.clone(),
+        })
     }
 
     async fn account_create(&self, cmd: CreateAccountCommand) -> AppResult<AccountModel> {
-        todo!()
+        not_implemented()
     }
 
     async fn account_update(&self, id: &str, cmd: UpdateAccountCommand) -> AppResult<AccountModel> {
-        todo!()
+        not_implemented()
     }
 
     async fn account_find_by_id(&self, id: &str) -> AppResult<AccountModel> {
-        todo!()
+        not_implemented()
     }
 
     async fn account_find_by_username(&self, username: &str) -> AppResult<AccountModel> {
-        todo!()
+        not_implemented()
     }
 
     async fn account_find_by_email(&self, email: &str) -> AppResult<AccountModel> {
-        todo!()
+        not_implemented()
     }
 
     async fn account_find_by_mobile(&self, mobile: &str) -> AppResult<AccountModel> {
-        todo!()
+        not_implemented()
     }
 
     async fn account_find_by_identity(&self, identity: &str) -> AppResult<AccountModel> {
-        todo!()
+        not_implemented()
+    }
+
+    async fn account_lock(&self, id: &str, updated_by: &str) -> AppResult<bool> {
+        not_implemented()
+    }
+
+    async fn account_unlock(&self, id: &str, updated_by: &str) -> AppResult<bool> {
+        not_implemented()
+    }
+
+    async fn account_require_new_password(&self, id: &str, updated_by: &str) -> AppResult<bool> {
+        not_implemented()
+    }
+
+    async fn account_set_roles(
+        &self,
+        id: &str,
+        roles: Vec<String>,
+        permissions: Vec<String>,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        not_implemented()
+    }
+
+    async fn account_restore(&self, id: &str, updated_by: &str) -> AppResult<bool> {
+        not_implemented()
     }
 
-    async fn account_lock(&self, id: &str) -> AppResult<bool> {
-        todo!()
+    async fn account_set_confirmed(
+        &self,
+        id: &str,
+        confirmed: bool,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        not_implemented()
     }
 
-    async fn account_unlock(&self, id: &str) -> AppResult<bool> {
-        todo!()
+    async fn account_set_unconfirmed_email(
+        &self,
+        id: &str,
+        email: Option<&str>,
+        token: Option<&str>,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        not_implemented()
     }
 
-    async fn account_require_new_password(&self, id: &str) -> AppResult<bool> {
-        todo!()
+    async fn account_set_unconfirmed_mobile(
+        &self,
+        id: &str,
+        mobile: Option<&str>,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        not_implemented()
     }
 
-    async fn account_set_password(&self, id: &str, password: &str) -> AppResult<bool> {
-        todo!()
+    async fn account_set_password(
+        &self,
+        id: &str,
+        password: &str,
+        history_size: usize,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        not_implemented()
     }
 
     async fn account_set_last_login(&self, id: &str) -> AppResult<bool> {
-        todo!()
+        not_implemented()
     }
 
-    async fn account_delete(&self, id: &str, hard_delete: bool) -> AppResult<bool> {
-        todo!()
+    async fn account_delete(
+        &self,
+        id: &str,
+        hard_delete: bool,
+        updated_by: &str,
+    ) -> AppResult<bool> {
+        not_implemented()
     }
 
     async fn onetime_code_create(&self, id: &str) -> AppResult<OneTimeCodeModel> {
-        todo!()
+        not_implemented()
     }
 
     async fn onetime_code_find_by_account(
@@ -91,6 +175,202 @@ impl BaseStoreTrait for AccountStore {
         account_id: &str,
         code: Option<&str>,
     ) -> AppResult<OneTimeCodeModel> {
-        todo!()
+        not_implemented()
+    }
+
+    async fn onetime_code_fail(&self, account_id: &str) -> AppResult<i64> {
+        not_implemented()
+    }
+
+    async fn onetime_code_delete(&self, account_id: &str) -> AppResult<()> {
+        not_implemented()
+    }
+
+    async fn signup_token_use(&self, id: &str, expires_at: DateTime<Utc>) -> AppResult<i64> {
+        not_implemented()
+    }
+}
+
+#[async_trait]
+impl ActivityStoreTrait for AccountStore {
+    async fn activity_create(&self, cmd: CreateActivityCommand) -> AppResult<AccountActivityModel> {
+        not_implemented()
+    }
+
+    async fn activity_find(
+        &self,
+        cmd: FindActivitiesCommand,
+    ) -> AppResult<(Vec<AccountActivityModel>, i64)> {
+        not_implemented()
+    }
+
+    async fn activity_count_by_identity(
+        &self,
+        log_name: &str,
+        identity: &str,
+        since: DateTime<Utc>,
+    ) -> AppResult<i64> {
+        not_implemented()
+    }
+}
+
+#[async_trait]
+impl ProfileStoreTrait for AccountStore {
+    async fn profile_find_by_account(&self, account_id: &str) -> AppResult<ProfileModel> {
+        not_implemented()
+    }
+
+    async fn profile_upsert(
+        &self,
+        account_id: &str,
+        fields: Map<String, Value>,
+        updated_by: &str,
+    ) -> AppResult<ProfileModel> {
+        not_implemented()
+    }
+
+    async fn profile_field_taken(
+        &self,
+        name: &str,
+        value: &Value,
+        account_id: Option<&str>,
+    ) -> AppResult<bool> {
+        not_implemented()
+    }
+}
+
+#[async_trait]
+impl SessionStoreTrait for AccountStore {
+    async fn session_create(&self, cmd: CreateSessionCommand) -> AppResult<SessionModel> {
+        not_implemented()
+    }
+
+    async fn session_find_by_id(&self, id: &str) -> AppResult<SessionModel> {
+        not_implemented()
+    }
+
+    async fn session_find_active(&self, account_id: &str) -> AppResult<Vec<SessionModel>> {
+        not_implemented()
+    }
+
+    async fn session_touch(&self, id: &str, expires_at: Option<DateTime<Utc>>) -> AppResult<()> {
+        not_implemented()
+    }
+
+    async fn session_set_organization(
+        &self,
+        id: &str,
+        organization_id: Option<&str>,
+    ) -> AppResult<()> {
+        not_implemented()
+    }
+
+    async fn session_revoke(
+        &self,
+        account_id: &str,
+        id: Option<&str>,
+        keep_id: Option<&str>,
+    ) -> AppResult<u64> {
+        not_implemented()
+    }
+
+    async fn session_end_expired_impersonations(&self) -> AppResult<Vec<SessionModel>> {
+        not_implemented()
+    }
+}
+
+#[async_trait]
+impl ApiKeyStoreTrait for AccountStore {
+    async fn api_key_create(&self, cmd: CreateApiKeyCommand) -> AppResult<ApiKeyModel> {
+        not_implemented()
+    }
+
+    async fn api_key_find_by_id(&self, id: &str) -> AppResult<ApiKeyModel> {
+        not_implemented()
+    }
+
+    async fn api_key_find_by_account(&self, account_id: &str) -> AppResult<Vec<ApiKeyModel>> {
+        not_implemented()
+    }
+
+    async fn api_key_touch(&self, id: &str) -> AppResult<()> {
+        not_implemented()
+    }
+
+    async fn api_key_revoke(&self, account_id: &str, id: &str) -> AppResult<bool> {
+        not_implemented()
+    }
+}
+
+#[async_trait]
+impl OrganizationStoreTrait for AccountStore {
+    async fn organization_create(
+        &self,
+        cmd: CreateOrganizationCommand,
+    ) -> AppResult<OrganizationModel> {
+        not_implemented()
+    }
+
+    async fn organization_find_by_id(&self, id: &str) -> AppResult<OrganizationModel> {
+        not_implemented()
+    }
+
+    async fn membership_create(&self, cmd: CreateMembershipCommand) -> AppResult<MembershipModel> {
+        not_implemented()
+    }
+
+    async fn membership_find(
+        &self,
+        organization_id: &str,
+        account_id: &str,
+    ) -> AppResult<MembershipModel> {
+        not_implemented()
+    }
+
+    async fn membership_find_by_organization(
+        &self,
+        organization_id: &str,
+    ) -> AppResult<Vec<MembershipModel>> {
+        not_implemented()
+    }
+
+    async fn membership_find_by_account(
+        &self,
+        account_id: &str,
+    ) -> AppResult<Vec<MembershipModel>> {
+        not_implemented()
+    }
+
+    async fn membership_set_role(
+        &self,
+        organization_id: &str,
+        account_id: &str,
+        role: OrganizationRole,
+    ) -> AppResult<bool> {
+        not_implemented()
+    }
+
+    async fn membership_delete(&self, organization_id: &str, account_id: &str) -> AppResult<bool> {
+        not_implemented()
+    }
+
+    async fn invitation_create(&self, cmd: CreateInvitationCommand) -> AppResult<InvitationModel> {
+        not_implemented()
+    }
+
+    async fn invitation_find_by_token_hash(&self, token_hash: &str) -> AppResult<InvitationModel> {
+        not_implemented()
+    }
+
+    async fn invitation_find_open(&self, organization_id: &str) -> AppResult<Vec<InvitationModel>> {
+        not_implemented()
+    }
+
+    async fn invitation_accept(&self, id: &str, account_id: &str) -> AppResult<bool> {
+        not_implemented()
+    }
+
+    async fn invitation_revoke(&self, organization_id: &str, id: &str) -> AppResult<bool> {
+        not_implemented()
     }
 }
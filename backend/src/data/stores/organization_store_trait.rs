@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::common::helpers::AppResult;
+use crate::data::models::invitation_model::InvitationModel;
+use crate::data::models::organization_model::{
+    MembershipModel, OrganizationModel, OrganizationRole,
+};
+
+#[derive(Debug)]
+pub struct CreateOrganizationCommand {
+    pub name: String,
+    pub created_by: String,
+}
+
+#[derive(Debug)]
+pub struct CreateMembershipCommand {
+    pub organization_id: String,
+    pub account_id: String,
+    pub role: OrganizationRole,
+    pub invited_by: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CreateInvitationCommand {
+    pub organization_id: String,
+    pub email: String,
+    pub role: OrganizationRole,
+    pub token_hash: String,
+    pub invited_by: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[async_trait]
+/// Store trait for organizations, their members and invitations, implemented by all store type
+pub trait OrganizationStoreTrait {
+    /// Create a new organization
+    ///
+    /// Returns the created organization
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The organization payload CreateOrganizationCommand
+    async fn organization_create(
+        &self,
+        cmd: CreateOrganizationCommand,
+    ) -> AppResult<OrganizationModel>;
+
+    /// Find an organization by its id
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An organization ID
+    async fn organization_find_by_id(&self, id: &str) -> AppResult<OrganizationModel>;
+
+    /// Add an account to an organization, an account is a member once
+    ///
+    /// Returns the created membership
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The membership payload CreateMembershipCommand
+    async fn membership_create(&self, cmd: CreateMembershipCommand) -> AppResult<MembershipModel>;
+
+    /// Find the membership of an account in an organization
+    ///
+    /// # Arguments
+    ///
+    /// * `organization_id` - An organization ID
+    /// * `account_id` - An account ID
+    async fn membership_find(
+        &self,
+        organization_id: &str,
+        account_id: &str,
+    ) -> AppResult<MembershipModel>;
+
+    /// Find the members of an organization, oldest first
+    ///
+    /// # Arguments
+    ///
+    /// * `organization_id` - An organization ID
+    async fn membership_find_by_organization(
+        &self,
+        organization_id: &str,
+    ) -> AppResult<Vec<MembershipModel>>;
+
+    /// Find the memberships of an account, the most recently joined first
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - An account ID
+    async fn membership_find_by_account(&self, account_id: &str)
+        -> AppResult<Vec<MembershipModel>>;
+
+    /// Change the role of a member
+    ///
+    /// Returns whether the member was found
+    ///
+    /// # Arguments
+    ///
+    /// * `organization_id` - An organization ID
+    /// * `account_id` - An account ID
+    /// * `role` - The new role
+    async fn membership_set_role(
+        &self,
+        organization_id: &str,
+        account_id: &str,
+        role: OrganizationRole,
+    ) -> AppResult<bool>;
+
+    /// Remove an account from an organization
+    ///
+    /// Returns whether the member was found
+    ///
+    /// # Arguments
+    ///
+    /// * `organization_id` - An organization ID
+    /// * `account_id` - An account ID
+    async fn membership_delete(&self, organization_id: &str, account_id: &str) -> AppResult<bool>;
+
+    /// Record a new invitation
+    ///
+    /// Returns the recorded invitation
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The invitation payload CreateInvitationCommand
+    async fn invitation_create(&self, cmd: CreateInvitationCommand) -> AppResult<InvitationModel>;
+
+    /// Find an invitation by the hash of its token, accepted, revoked and expired ones included
+    ///
+    /// # Arguments
+    ///
+    /// * `token_hash` - The sha256 hex digest of the token
+    async fn invitation_find_by_token_hash(&self, token_hash: &str) -> AppResult<InvitationModel>;
+
+    /// Find the invitations of an organization that were neither accepted nor revoked, newest first
+    ///
+    /// # Arguments
+    ///
+    /// * `organization_id` - An organization ID
+    async fn invitation_find_open(&self, organization_id: &str) -> AppResult<Vec<InvitationModel>>;
+
+    /// Mark an open invitation accepted
+    ///
+    /// Returns whether it was still open, an invitation is accepted once
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - An invitation ID
+    /// * `account_id` - The account that accepted it
+    async fn invitation_accept(&self, id: &str, account_id: &str) -> AppResult<bool>;
+
+    /// Revoke an open invitation of an organization
+    ///
+    /// Returns whether an open invitation was revoked
+    ///
+    /// # Arguments
+    ///
+    /// * `organization_id` - An organization ID
+    /// * `id` - An invitation ID
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// let revoked = BoxedStoreType::invitation_revoke(organization_id, invitation_id).await?;
+    async fn invitation_revoke(&self, organization_id: &str, id: &str) -> AppResult<bool>;
+}
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+
+use crate::common::helpers::AppResult;
+use crate::data::models::profile_model::ProfileModel;
+
+#[async_trait]
+/// Store trait for the schema driven account profiles, implemented by all store type
+pub trait ProfileStoreTrait {
+    /// Find the profile of an account
+    ///
+    /// Returns the profile if found or a not found error
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - An account ID
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// let profile = BoxedStoreType::profile_find_by_account("id").await?;
+    async fn profile_find_by_account(&self, account_id: &str) -> AppResult<ProfileModel>;
+
+    /// Create or update the profile of an account
+    ///
+    /// Given fields are set and null fields are removed, other fields are kept
+    ///
+    /// Returns the updated profile
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - An account ID
+    /// * `fields` - The validated profile fields
+    /// * `updated_by` - The account performing the update
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// let mut fields = Map::new();
+    /// fields.insert("first_name".to_string(), Value::from("Ada"));
+    /// let profile = BoxedStoreType::profile_upsert("id", fields, "id").await?;
+    async fn profile_upsert(
+        &self,
+        account_id: &str,
+        fields: Map<String, Value>,
+        updated_by: &str,
+    ) -> AppResult<ProfileModel>;
+
+    /// Check if another account's profile already holds `value` for the field `name`
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A profile field name
+    /// * `value` - The field value
+    /// * `account_id` - The account to exclude, if any
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// let taken = BoxedStoreType::profile_field_taken("employee_id", &Value::from("E0001"), None).await?;
+    async fn profile_field_taken(
+        &self,
+        name: &str,
+        value: &Value,
+        account_id: Option<&str>,
+    ) -> AppResult<bool>;
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::common::helpers::AppResult;
+use crate::data::models::session_model::SessionModel;
+
+#[derive(Debug)]
+pub struct CreateSessionCommand {
+    /// The `jti` of the tokens of the session
+    pub id: String,
+    pub account_id: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    /// The active organization of the session
+    pub organization_id: Option<String>,
+    /// The admin the session was opened for to impersonate the account
+    pub impersonated_by: Option<String>,
+}
+
+#[async_trait]
+/// Store trait for the server side records of account sessions, implemented by all store type
+pub trait SessionStoreTrait {
+    /// Record a new session
+    ///
+    /// Returns the recorded session
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - The session payload CreateSessionCommand
+    async fn session_create(&self, cmd: CreateSessionCommand) -> AppResult<SessionModel>;
+
+    /// Find a session by its id, revoked and expired sessions included
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The session id, i.e. the `jti` of its tokens
+    async fn session_find_by_id(&self, id: &str) -> AppResult<SessionModel>;
+
+    /// Find the sessions of an account that are neither revoked nor expired, newest first
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - An account ID
+    async fn session_find_active(&self, account_id: &str) -> AppResult<Vec<SessionModel>>;
+
+    /// Record that a session was used just now
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The session id
+    /// * `expires_at` - Extend the session to this time, e.g. when a fresh token is issued
+    async fn session_touch(&self, id: &str, expires_at: Option<DateTime<Utc>>) -> AppResult<()>;
+
+    /// Switch the active organization of a session
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The session id
+    /// * `organization_id` - The organization, None to leave the current one
+    async fn session_set_organization(
+        &self,
+        id: &str,
+        organization_id: Option<&str>,
+    ) -> AppResult<()>;
+
+    /// Revoke the active sessions of an account
+    ///
+    /// Returns the number of sessions revoked
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - An account ID
+    /// * `id` - Only revoke this session when given
+    /// * `keep_id` - Leave this session active, e.g. the one of the caller
+    ///
+    /// # Examples
+    /// use crate::data::stores::base_store_trait::BoxedStoreType;
+    /// // sign out every other device
+    /// let revoked = BoxedStoreType::session_revoke(account_id, None, Some(session_id)).await?;
+    async fn session_revoke(
+        &self,
+        account_id: &str,
+        id: Option<&str>,
+        keep_id: Option<&str>,
+    ) -> AppResult<u64>;
+
+    /// End the impersonation sessions of the tenant that expired without being logged out
+    ///
+    /// Returns the sessions ended, each one is only returned by a single call
+    async fn session_end_expired_impersonations(&self) -> AppResult<Vec<SessionModel>>;
+}
@@ -1,9 +1,78 @@
+use chrono::{DateTime, Utc};
 use riker::actors::ActorSystem;
+use tokio::sync::broadcast;
+
+/// Number of events a slow subscriber may fall behind before it starts skipping events
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, PartialEq)]
+pub enum AccountEventKind {
+    Created,
+    LoggedIn,
+    LoggedOut,
+    Locked,
+    Unlocked,
+    Deleted,
+    Updated,
+    PasswordChanged,
+    /// A new email waits for confirmation, the token is delivered by the subscriber
+    EmailChangeRequested,
+    /// A new mobile waits for verification, the one time code is delivered by the subscriber
+    MobileChangeRequested,
+}
+
+/// An account or session event published on the in-process event bus
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountEvent {
+    pub kind: AccountEventKind,
+    /// The tenant of the account, subscribers only see the events of their own tenant
+    pub tenant_id: String,
+    pub account_id: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl AccountEvent {
+    pub fn new(tenant_id: &str, kind: AccountEventKind, account_id: &str) -> Self {
+        AccountEvent {
+            kind,
+            tenant_id: tenant_id.to_string(),
+            account_id: account_id.to_string(),
+            occurred_at: Utc::now(),
+        }
+    }
+}
 
 // Throw the Config struct into a CONFIG lazy_static to avoid multiple processing
 lazy_static! {
     pub static ref SYSTEM: ActorSystem = ActorSystem::new().unwrap();
+    pub static ref EVENT_BUS: broadcast::Sender<AccountEvent> =
+        broadcast::channel(EVENT_BUS_CAPACITY).0;
+}
+
+/// Publish an event of an account of `tenant_id` to every subscriber.
+///
+/// Events are dropped when nobody is listening.
+pub fn publish_event(tenant_id: &str, kind: AccountEventKind, account_id: &str) {
+    let _ = EVENT_BUS.send(AccountEvent::new(tenant_id, kind, account_id));
+}
+
+/// Subscribe to the events published from now on
+pub fn subscribe_events() -> broadcast::Receiver<AccountEvent> {
+    EVENT_BUS.subscribe()
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn it_delivers_published_events_to_subscribers() {
+        let mut rx = subscribe_events();
+        publish_event("acme", AccountEventKind::Locked, "1234");
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.kind, AccountEventKind::Locked);
+        assert_eq!(event.tenant_id, "acme".to_string());
+        assert_eq!(event.account_id, "1234".to_string());
+    }
+}
@@ -0,0 +1,162 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use openssl::sha::sha1;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+
+/// Length of the SHA-1 prefix naming a range, the rest of the hash never leaves the server
+pub const RANGE_PREFIX_LEN: usize = 5;
+
+/// Generic trait for the sources of a k-anonymity breached password corpus
+#[async_trait]
+pub trait PasswordBreachIntegration: Send + Sync {
+    /// The `SUFFIX:COUNT` lines of the range whose hashes start with `prefix`
+    async fn range(&self, prefix: &str) -> AppResult<String>;
+}
+
+/// Upper case hex SHA-1 of `password` split into its range prefix and suffix
+pub fn range_key(password: &str) -> (String, String) {
+    let digest: String = sha1(password.as_bytes())
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect();
+    let (prefix, suffix) = digest.split_at(RANGE_PREFIX_LEN);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// How often `suffix` appears in the lines of a range
+pub fn occurrences(range: &str, suffix: &str) -> u64 {
+    range
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.trim().splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(s), Some(count)) if s.eq_ignore_ascii_case(suffix) => {
+                    count.trim().parse::<u64>().ok()
+                }
+                _ => None,
+            }
+        })
+        .sum()
+}
+
+/// How often `password` appears in the corpus of `integration`
+pub async fn times_breached(
+    integration: &dyn PasswordBreachIntegration,
+    password: &str,
+) -> AppResult<u64> {
+    let (prefix, suffix) = range_key(password);
+    let range = integration.range(&prefix).await?;
+    Ok(occurrences(&range, &suffix))
+}
+
+/// Range files on disk, one `<PREFIX>.txt` per prefix as written by the HIBP downloader
+pub struct LocalPasswordBreachIntegration {
+    path: PathBuf,
+}
+
+impl LocalPasswordBreachIntegration {
+    pub fn new(path: &str) -> Self {
+        LocalPasswordBreachIntegration {
+            path: PathBuf::from(path),
+        }
+    }
+}
+
+#[async_trait]
+impl PasswordBreachIntegration for LocalPasswordBreachIntegration {
+    /// A missing range file is an empty range
+    async fn range(&self, prefix: &str) -> AppResult<String> {
+        let file = self.path.join(format!("{}.txt", prefix.to_uppercase()));
+        match tokio::fs::read_to_string(&file).await {
+            Ok(range) => Ok(range),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(ApiError::InternalServerError(format!(
+                "can't read breached password range {}: {}",
+                file.display(),
+                e
+            ))),
+        }
+    }
+}
+
+/// In memory corpus standing in for the range files in tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryPasswordBreachIntegration {
+    ranges: std::collections::HashMap<String, String>,
+}
+
+#[cfg(test)]
+impl MemoryPasswordBreachIntegration {
+    pub fn with_passwords(passwords: &[(&str, u64)]) -> Self {
+        let mut integration = MemoryPasswordBreachIntegration::default();
+        for (password, count) in passwords {
+            let (prefix, suffix) = range_key(password);
+            let range = integration.ranges.entry(prefix).or_default();
+            range.push_str(&format!("{}:{}\r\n", suffix, count));
+        }
+        integration
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl PasswordBreachIntegration for MemoryPasswordBreachIntegration {
+    async fn range(&self, prefix: &str) -> AppResult<String> {
+        Ok(self.ranges.get(prefix).cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_splits_the_sha1_of_a_password() {
+        let (prefix, suffix) = range_key("password");
+        assert_eq!(prefix, "5BAA6");
+        assert_eq!(suffix, "1E4C9B93F3F0682250B6CF8331B7EE68FD8");
+    }
+
+    #[test]
+    fn it_counts_occurrences_in_a_range() {
+        let range = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n\
+                     1E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493\r\n";
+        assert_eq!(
+            occurrences(range, "1E4C9B93F3F0682250B6CF8331B7EE68FD8"),
+            3861493
+        );
+        assert_eq!(occurrences(range, "00000000000000000000000000000000000"), 0);
+    }
+
+    #[actix_rt::test]
+    async fn it_checks_passwords_against_a_corpus() {
+        let integration = MemoryPasswordBreachIntegration::with_passwords(&[("password", 42)]);
+        assert_eq!(times_breached(&integration, "password").await.unwrap(), 42);
+        assert_eq!(
+            times_breached(&integration, "@43874hfdfweak_password")
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[actix_rt::test]
+    async fn it_reads_local_range_files() {
+        let dir = std::env::temp_dir().join("guardian_breach_ranges");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("5BAA6.txt"),
+            "1E4C9B93F3F0682250B6CF8331B7EE68FD8:7\r\n",
+        )
+        .unwrap();
+
+        let integration = LocalPasswordBreachIntegration::new(dir.to_str().unwrap());
+        assert_eq!(times_breached(&integration, "password").await.unwrap(), 7);
+        assert_eq!(integration.range("FFFFF").await.unwrap(), "");
+    }
+}
@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::integrations::breach::breach_integration::PasswordBreachIntegration;
+
+/// Client of a remote HIBP style range api such as `https://api.pwnedpasswords.com/range`
+pub struct HibpPasswordBreachIntegration {
+    client: reqwest::Client,
+    api_url: String,
+}
+
+impl HibpPasswordBreachIntegration {
+    pub fn new(api_url: &str) -> Self {
+        HibpPasswordBreachIntegration {
+            client: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl PasswordBreachIntegration for HibpPasswordBreachIntegration {
+    /// Padding is requested so the size of the response says nothing about the range
+    async fn range(&self, prefix: &str) -> AppResult<String> {
+        let unavailable =
+            |e: reqwest::Error| ApiError::InternalServerError(format!("range api failed: {}", e));
+
+        self.client
+            .get(format!("{}/{}", self.api_url, prefix))
+            .header("Add-Padding", "true")
+            .send()
+            .await
+            .and_then(|rsp| rsp.error_for_status())
+            .map_err(unavailable)?
+            .text()
+            .await
+            .map_err(unavailable)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod breach_integration;
+#[cfg(feature = "hibp_integration")]
+pub mod hibp_integration;
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+
+use crate::common::helpers::AppResult;
+
+/// Generic trait for the CAPTCHA style services proving a caller is not a bot
+#[async_trait]
+pub trait ChallengeVerifier: Send + Sync {
+    /// Whether `token`, solved by the client at `remote_ip`, passes the challenge
+    ///
+    /// An error means the service could not be asked, a failed challenge is `Ok(false)`
+    async fn verify(&self, token: &str, remote_ip: Option<&str>) -> AppResult<bool>;
+}
+
+/// Verifier accepting a single fixed token, for local development and tests
+pub struct MockChallengeVerifier {
+    passing_token: String,
+}
+
+impl MockChallengeVerifier {
+    pub fn new(passing_token: &str) -> Self {
+        MockChallengeVerifier {
+            passing_token: passing_token.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl ChallengeVerifier for MockChallengeVerifier {
+    async fn verify(&self, token: &str, _remote_ip: Option<&str>) -> AppResult<bool> {
+        Ok(!token.is_empty() && token == self.passing_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn it_only_passes_the_mock_token() {
+        let verifier = MockChallengeVerifier::new("10000000-aaaa-bbbb-cccc-000000000001");
+        assert!(verifier
+            .verify("10000000-aaaa-bbbb-cccc-000000000001", None)
+            .await
+            .unwrap());
+        assert!(!verifier.verify("forged", Some("10.0.0.1")).await.unwrap());
+        assert!(!MockChallengeVerifier::new("")
+            .verify("", None)
+            .await
+            .unwrap());
+    }
+}
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::integrations::challenge::challenge_integration::ChallengeVerifier;
+
+/// The part of a siteverify reply the verifier reads
+#[derive(Debug, Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+}
+
+/// Client of a `siteverify` endpoint as offered by hCaptcha, reCAPTCHA and Turnstile
+///
+/// They all take the `secret`, `response` and `remoteip` form fields and reply with a json
+/// object whose `success` tells whether the token passed
+pub struct HttpChallengeVerifier {
+    client: reqwest::Client,
+    verify_url: String,
+    secret: String,
+}
+
+impl HttpChallengeVerifier {
+    pub fn new(verify_url: &str, secret: &str) -> Self {
+        HttpChallengeVerifier {
+            client: reqwest::Client::new(),
+            verify_url: verify_url.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl ChallengeVerifier for HttpChallengeVerifier {
+    async fn verify(&self, token: &str, remote_ip: Option<&str>) -> AppResult<bool> {
+        let unavailable = |e: reqwest::Error| {
+            ApiError::InternalServerError(format!("challenge verification failed: {}", e))
+        };
+
+        let mut form = vec![("secret", self.secret.as_str()), ("response", token)];
+        if let Some(ip) = remote_ip {
+            form.push(("remoteip", ip));
+        }
+
+        let body = self
+            .client
+            .post(&self.verify_url)
+            .form(&form)
+            .send()
+            .await
+            .and_then(|rsp| rsp.error_for_status())
+            .map_err(unavailable)?
+            .text()
+            .await
+            .map_err(unavailable)?;
+
+        let rsp: SiteVerifyResponse = serde_json::from_str(&body).map_err(|e| {
+            ApiError::InternalServerError(format!("unexpected challenge reply: {}", e))
+        })?;
+        Ok(rsp.success)
+    }
+}
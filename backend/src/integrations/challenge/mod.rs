@@ -0,0 +1,3 @@
+pub mod challenge_integration;
+#[cfg(feature = "challenge_integration")]
+pub mod http_challenge_integration;
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
+use slog::info;
 
-use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::common::utils::logger_utils::LOGGER;
 
 /// Generic message option for all email clients
 pub struct MessageOption {
@@ -18,10 +20,24 @@ pub struct MessageOption {
 /// Generic email trait for all email integration. This will enable
 /// us have a generic interface for all clients
 #[async_trait]
-pub trait EmailIntegration {
-    type Client;
+pub trait EmailIntegration: Send + Sync {
+    async fn send_message(&self, opt: MessageOption) -> AppResult<()>;
+}
 
-    fn new(&self, api_key: String) -> Self::Client;
+/// Email client writing the messages to the log, for local development
+#[derive(Default)]
+pub struct LogEmailIntegration;
 
-    async fn send_message(&self, opt: MessageOption) -> Result<(), ApiError>;
+#[async_trait]
+impl EmailIntegration for LogEmailIntegration {
+    async fn send_message(&self, opt: MessageOption) -> AppResult<()> {
+        info!(
+            LOGGER,
+            "[email] to: {}, subject: {}\n{}",
+            opt.to,
+            opt.subject.unwrap_or_default(),
+            opt.content.unwrap_or_default()
+        );
+        Ok(())
+    }
 }
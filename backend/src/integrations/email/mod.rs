@@ -1,2 +1,3 @@
 pub mod email_integration;
+#[cfg(feature = "sendgrid_integration")]
 pub mod sendgrid_integration;
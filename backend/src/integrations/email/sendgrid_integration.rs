@@ -1,50 +1,54 @@
 use async_trait::async_trait;
+use sendgrid::v3::{Content, Email, Message, Personalization, Sender};
 
+use crate::common::errors::ApiError;
 use crate::common::helpers::AppResult;
 use crate::integrations::email::email_integration::{EmailIntegration, MessageOption};
 
 /// Sendgrid email client
-#[cfg(sendgrid_integration)]
 pub struct SendgridEmailIntegration {
-    client: sendgrid::v3::Sender,
+    client: Sender,
 }
 
-#[cfg(sendgrid_integration)]
-#[async_trait]
-impl EmailIntegration for SendgridEmailIntegration {
-    type Client = SendgridEmailIntegration;
-
-    /// Initialize a new instance of `SendgridEmailClient` which contains a `sendgrid` client
-    fn new(&self, api_key: String) -> SendgridEmailIntegration {
+impl SendgridEmailIntegration {
+    /// Initialize a new instance of `SendgridEmailIntegration` which contains a `sendgrid` client
+    pub fn new(api_key: &str) -> Self {
         SendgridEmailIntegration {
-            client: sendgrid::v3::Sender::new(api_key),
+            client: Sender::new(api_key.to_string()),
         }
     }
+}
 
+#[async_trait]
+impl EmailIntegration for SendgridEmailIntegration {
     /// Helper method that sends method using a generic interface
     async fn send_message(&self, opt: MessageOption) -> AppResult<()> {
-        use sendgrid::v3::*;
-
-        let p = Personalization::new(Email::new(opt.to));
-        if opt.headers.is_some() {
-            p.add_headers(opt.headers?)
+        let mut personalization = Personalization::new(Email::new(opt.to.as_str()));
+        if let Some(headers) = opt.headers {
+            personalization = personalization.add_headers(headers);
         }
 
-        let message = sendgrid::v3::Message::new(Email::new(opt.from));
-        message.add_personalization(p);
-
-        if opt.subject.is_some() {
-            message.set_subject(opt.message.unwrap().as_str());
+        let mut from = Email::new(opt.from.as_str());
+        if let Some(name) = &opt.from_name {
+            from = from.set_name(name.as_str());
         }
 
-        if opt.content.is_some() {
-            let c = Content::new()
-                .set_content_type(opt.content_type)
-                .set_value(opt.content?);
-
-            message.add_content(c);
+        let mut message = Message::new(from).add_personalization(personalization);
+        if let Some(subject) = &opt.subject {
+            message = message.set_subject(subject.as_str());
+        }
+        if let Some(content) = &opt.content {
+            message = message.add_content(
+                Content::new()
+                    .set_content_type(opt.content_type.as_str())
+                    .set_value(content.as_str()),
+            );
         }
 
-        self.client.send(&message).await
+        self.client
+            .send(&message)
+            .await
+            .map(|_| ())
+            .map_err(|e| ApiError::InternalServerError(e.to_string()))
     }
 }
@@ -1 +1,4 @@
+pub mod breach;
+pub mod challenge;
 pub mod email;
+pub mod sms;
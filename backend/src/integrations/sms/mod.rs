@@ -0,0 +1 @@
+pub mod sms_integration;
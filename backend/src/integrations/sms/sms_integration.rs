@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use slog::info;
+
+use crate::common::helpers::AppResult;
+use crate::common::utils::logger_utils::LOGGER;
+
+/// Generic text message trait for all sms integrations
+#[async_trait]
+pub trait SmsIntegration: Send + Sync {
+    async fn send_sms(&self, to: &str, body: &str) -> AppResult<()>;
+}
+
+/// Sms client writing the messages to the log, for local development
+#[derive(Default)]
+pub struct LogSmsIntegration;
+
+#[async_trait]
+impl SmsIntegration for LogSmsIntegration {
+    async fn send_sms(&self, to: &str, body: &str) -> AppResult<()> {
+        info!(LOGGER, "[sms] to: {}\n{}", to, body);
+        Ok(())
+    }
+}
@@ -9,6 +9,9 @@ extern crate redis_async;
 
 #[cfg(feature = "server-actix")]
 use server::actix_server::start_http_server;
+#[cfg(feature = "server-grpc")]
+use server::grpc_server::spawn_grpc_server;
+use server::impersonation_sweep::spawn_impersonation_sweep;
 
 mod api;
 mod common;
@@ -24,5 +27,9 @@ mod tasks;
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     // start_task_server().await;
+    #[cfg(feature = "server-grpc")]
+    spawn_grpc_server();
+    spawn_impersonation_sweep();
+
     start_http_server().await
 }
@@ -0,0 +1,322 @@
+//! Conversions between the protobuf messages and the api dtos
+use std::collections::HashMap;
+
+use crate::data::dtos::account_dto::{
+    AccountResponse, AccountUpdateResponse, PostAccountResponse, PostSessionResponse,
+    SignupTokenResponse,
+};
+use crate::data::dtos::auth_dto::{
+    ChangePasswordRequest, ConfirmChangeRequest, IdentifierRequest, MobileInput,
+    PostAccountRequest, PostSessionRequest, RenewPasswordRequest, SetAccountRolesRequest,
+    SignupTokenRequest, UpdateAccountRequest,
+};
+use crate::data::dtos::profile_dto::ProfileFieldInput;
+use crate::data::dtos::session_dto::{
+    ActiveSessionResponse, ActiveSessionsResponse, RevokedSessionsResponse,
+};
+use crate::proto::gen;
+
+/// proto3 has no optional scalars, so empty strings are treated as missing values
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// protobuf maps are unordered, the fields are sorted by name to keep validation errors stable
+fn profile_fields(profile: HashMap<String, String>) -> Option<Vec<ProfileFieldInput>> {
+    if profile.is_empty() {
+        return None;
+    }
+
+    let mut fields: Vec<ProfileFieldInput> = profile
+        .into_iter()
+        .map(|(name, value)| ProfileFieldInput { name, value })
+        .collect();
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+    Some(fields)
+}
+
+impl From<gen::MobileInput> for MobileInput {
+    fn from(msg: gen::MobileInput) -> Self {
+        MobileInput {
+            prefix: msg.prefix,
+            digit: msg.digit,
+        }
+    }
+}
+
+impl From<gen::AccountCreateRequest> for PostAccountRequest {
+    fn from(msg: gen::AccountCreateRequest) -> Self {
+        PostAccountRequest {
+            email: non_empty(msg.email),
+            password: msg.password,
+            confirm_password: msg.confirm_password,
+            username: non_empty(msg.username),
+            mobile: msg.mobile.map(|m| m.into()),
+            profile: profile_fields(msg.profile),
+            challenge_token: non_empty(msg.challenge_token),
+            invitation_token: non_empty(msg.invitation_token),
+        }
+    }
+}
+
+impl From<gen::SessionCreateRequest> for PostSessionRequest {
+    fn from(msg: gen::SessionCreateRequest) -> Self {
+        PostSessionRequest {
+            identity: msg.identity,
+            password: msg.password,
+            challenge_token: non_empty(msg.challenge_token),
+            invitation_token: non_empty(msg.invitation_token),
+        }
+    }
+}
+
+impl From<gen::IdentifierRequest> for IdentifierRequest {
+    fn from(msg: gen::IdentifierRequest) -> Self {
+        IdentifierRequest {
+            identity: msg.identity,
+            signup_token: non_empty(msg.signup_token),
+        }
+    }
+}
+
+impl From<gen::SignupTokenRequest> for SignupTokenRequest {
+    fn from(msg: gen::SignupTokenRequest) -> Self {
+        SignupTokenRequest {
+            challenge_token: non_empty(msg.challenge_token),
+        }
+    }
+}
+
+impl From<gen::AdminUpdateAccountRequest> for UpdateAccountRequest {
+    fn from(msg: gen::AdminUpdateAccountRequest) -> Self {
+        UpdateAccountRequest {
+            email: non_empty(msg.email),
+            username: non_empty(msg.username),
+            mobile: msg.mobile.map(|m| m.into()),
+        }
+    }
+}
+
+impl From<gen::AccountUpdateRequest> for UpdateAccountRequest {
+    fn from(msg: gen::AccountUpdateRequest) -> Self {
+        UpdateAccountRequest {
+            email: non_empty(msg.email),
+            username: non_empty(msg.username),
+            mobile: msg.mobile.map(|m| m.into()),
+        }
+    }
+}
+
+impl From<gen::ChangePasswordRequest> for ChangePasswordRequest {
+    fn from(msg: gen::ChangePasswordRequest) -> Self {
+        ChangePasswordRequest {
+            current_password: msg.current_password,
+            password: msg.password,
+            confirm_password: msg.confirm_password,
+        }
+    }
+}
+
+impl From<gen::RenewPasswordRequest> for RenewPasswordRequest {
+    fn from(msg: gen::RenewPasswordRequest) -> Self {
+        RenewPasswordRequest {
+            change_password_token: msg.change_password_token,
+            password: msg.password,
+            confirm_password: msg.confirm_password,
+        }
+    }
+}
+
+impl From<gen::ConfirmChangeRequest> for ConfirmChangeRequest {
+    fn from(msg: gen::ConfirmChangeRequest) -> Self {
+        ConfirmChangeRequest { code: msg.code }
+    }
+}
+
+impl From<gen::AdminRolesRequest> for SetAccountRolesRequest {
+    fn from(msg: gen::AdminRolesRequest) -> Self {
+        SetAccountRolesRequest {
+            roles: msg.roles,
+            permissions: msg.permissions,
+        }
+    }
+}
+
+impl From<AccountResponse> for gen::AccountResponse {
+    fn from(acct: AccountResponse) -> Self {
+        gen::AccountResponse {
+            id: acct.id,
+            username: acct.username.unwrap_or_default(),
+            mobile: acct.mobile.unwrap_or_default(),
+            email: acct.email.unwrap_or_default(),
+            created_at: acct.created_at.to_rfc3339(),
+            updated_at: acct.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<AccountUpdateResponse> for gen::AccountUpdateResponse {
+    fn from(rsp: AccountUpdateResponse) -> Self {
+        gen::AccountUpdateResponse {
+            account: Some(rsp.account.into()),
+            id_token: rsp.id_token.unwrap_or_default(),
+            email_confirmation_required: rsp.email_confirmation_required,
+            mobile_verification_required: rsp.mobile_verification_required,
+        }
+    }
+}
+
+impl From<PostAccountResponse> for gen::AccountCreateResponse {
+    fn from(rsp: PostAccountResponse) -> Self {
+        gen::AccountCreateResponse {
+            id_token: rsp.id_token.unwrap_or_default(),
+            password_expired: rsp.password_expired,
+            change_password_token: rsp.change_password_token.unwrap_or_default(),
+            password_expires_at: rsp
+                .password_expires_at
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<PostSessionResponse> for gen::SessionResponse {
+    fn from(rsp: PostSessionResponse) -> Self {
+        gen::SessionResponse {
+            id_token: rsp.id_token.unwrap_or_default(),
+            password_expired: rsp.password_expired,
+            change_password_token: rsp.change_password_token.unwrap_or_default(),
+            password_expires_at: rsp
+                .password_expires_at
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<bool> for gen::StatusResponse {
+    fn from(status: bool) -> Self {
+        gen::StatusResponse { status }
+    }
+}
+
+impl From<SignupTokenResponse> for gen::SignupTokenResponse {
+    fn from(rsp: SignupTokenResponse) -> Self {
+        gen::SignupTokenResponse {
+            signup_token: rsp.signup_token,
+        }
+    }
+}
+
+impl From<ActiveSessionResponse> for gen::ActiveSession {
+    fn from(session: ActiveSessionResponse) -> Self {
+        gen::ActiveSession {
+            id: session.id,
+            ip: session.ip.unwrap_or_default(),
+            user_agent: session.user_agent.unwrap_or_default(),
+            current: session.current,
+            created_at: session.created_at.to_rfc3339(),
+            last_seen_at: session.last_seen_at.to_rfc3339(),
+            expires_at: session.expires_at.to_rfc3339(),
+            impersonated_by: session.impersonated_by.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<ActiveSessionsResponse> for gen::ActiveSessionsResponse {
+    fn from(rsp: ActiveSessionsResponse) -> Self {
+        gen::ActiveSessionsResponse {
+            items: rsp.items.into_iter().map(|s| s.into()).collect(),
+        }
+    }
+}
+
+impl From<RevokedSessionsResponse> for gen::RevokedSessionsResponse {
+    fn from(rsp: RevokedSessionsResponse) -> Self {
+        gen::RevokedSessionsResponse {
+            revoked: rsp.revoked,
+        }
+    }
+}
+
+impl From<bool> for gen::AvailabilityResponse {
+    fn from(available: bool) -> Self {
+        gen::AvailabilityResponse { available }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_treats_empty_proto_strings_as_missing() {
+        let msg = gen::AccountCreateRequest {
+            email: "".to_string(),
+            password: "password".to_string(),
+            confirm_password: "password".to_string(),
+            mobile: None,
+            username: "user_a".to_string(),
+            profile: HashMap::new(),
+            challenge_token: "".to_string(),
+            invitation_token: "".to_string(),
+        };
+
+        let cmd: PostAccountRequest = msg.into();
+        assert_eq!(cmd.email, None);
+        assert_eq!(cmd.username, Some("user_a".to_string()));
+        assert!(cmd.mobile.is_none());
+        assert!(cmd.profile.is_none());
+        assert!(cmd.challenge_token.is_none());
+        assert!(cmd.invitation_token.is_none());
+    }
+
+    #[test]
+    fn it_converts_mobile_input() {
+        let msg = gen::AccountCreateRequest {
+            email: "".to_string(),
+            password: "password".to_string(),
+            confirm_password: "password".to_string(),
+            mobile: Some(gen::MobileInput {
+                prefix: "+44".to_string(),
+                digit: "7700900000".to_string(),
+            }),
+            username: "".to_string(),
+            profile: vec![("last_name", "Lovelace"), ("first_name", "Ada")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            challenge_token: "".to_string(),
+            invitation_token: "".to_string(),
+        };
+
+        let cmd: PostAccountRequest = msg.into();
+        let mobile = cmd.mobile.unwrap();
+        assert_eq!(mobile.prefix, "+44".to_string());
+        assert_eq!(mobile.digit, "7700900000".to_string());
+
+        let names: Vec<String> = cmd.profile.unwrap().into_iter().map(|f| f.name).collect();
+        assert_eq!(
+            names,
+            vec!["first_name".to_string(), "last_name".to_string()]
+        );
+    }
+
+    #[test]
+    fn it_converts_admin_update_requests() {
+        let msg = gen::AdminUpdateAccountRequest {
+            id: "1234".to_string(),
+            email: "".to_string(),
+            username: "user_b".to_string(),
+            mobile: None,
+        };
+
+        let cmd: UpdateAccountRequest = msg.into();
+        assert_eq!(cmd.email, None);
+        assert_eq!(cmd.username, Some("user_b".to_string()));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod convert;
+
 pub mod gen {
     include!(concat!(env!("OUT_DIR"), "/service_proto.rs"));
 }
@@ -3,15 +3,25 @@ use actix_web::web;
 
 use crate::api::graphql::graphql_module::graphql_module;
 use crate::api::rest::account::account_module::accounts_module;
-use crate::api::rest::health::health_controller::get_health;
+use crate::api::rest::admin::admin_module::admin_module;
+use crate::api::rest::docs::docs_module::docs_module;
+use crate::api::rest::docs::openapi::API_PREFIX;
+use crate::api::rest::health::health_controller::health_module;
+use crate::api::rest::organization::organization_module::organization_module;
 use crate::common::auth::Authorizer;
 use crate::config::CONFIG;
 
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg
         // Health check
-        .route("/health", web::get().to(get_health))
-        .service(web::scope("/api/v1").configure(accounts_module))
+        .configure(health_module)
+        .service(
+            web::scope(API_PREFIX)
+                .configure(accounts_module)
+                .configure(admin_module)
+                .configure(organization_module)
+                .configure(docs_module),
+        )
         .service(web::scope("/graphql").configure(graphql_module))
         .service(
             web::scope("/secure").wrap(Authorizer).service(
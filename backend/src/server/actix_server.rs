@@ -6,14 +6,34 @@ use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
 
 use crate::common::appstate::state::new_state;
 use crate::common::cache::redis::add_cache;
-use crate::common::utils::cookie_utils::get_cookie_policy;
+use crate::common::middleware::api_key::ApiKeyAuthentication;
+use crate::common::middleware::csrf::CsrfProtection;
+use crate::common::middleware::rate_limit::RATE_LIMITER;
+use crate::common::middleware::tenant::TenantResolution;
+use crate::common::tenant::validate_tenancy;
+use crate::common::utils::cookie_utils::{
+    get_identity_policy, redis_session_key, validate_session_keys,
+};
 use crate::common::utils::logger_utils::{build_logger, init_logger};
 use crate::config::CONFIG;
 use crate::data::connect::{add_pool, add_shared_state};
+use crate::data::models::oso_init::OSO;
+use crate::data::models::profile_schema::PROFILE_SCHEMA;
 use crate::routes::routes;
 use actix_redis::RedisSession;
 use actix_web::http::header;
-use rand::Rng;
+
+/// Whether `origin` is a local development server, `http://localhost` or `http://127.0.0.1` on
+/// any port. Hosts merely starting with those names, like `localhost.example.com`, aren't
+fn is_local_origin(origin: &[u8]) -> bool {
+    [&b"http://localhost"[..], &b"http://127.0.0.1"[..]]
+        .iter()
+        .any(|host| {
+            origin
+                .strip_prefix(*host)
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with(b":"))
+        })
+}
 
 /// HTTP entry server
 pub async fn start_http_server() -> std::io::Result<()> {
@@ -29,6 +49,15 @@ pub async fn start_http_server() -> std::io::Result<()> {
         ..Default::default()
     });
 
+    // Checked once here rather than by the cookie policy of every worker
+    validate_session_keys(&CONFIG.security)?;
+    validate_tenancy(&CONFIG.tenancy)?;
+
+    // Load the authorization policy so a broken policy fails at startup
+    lazy_static::initialize(&OSO);
+    // Same for the profile schema declaring the custom profile fields
+    lazy_static::initialize(&PROFILE_SCHEMA);
+
     // Create the application state
     let data = new_state::<String>();
 
@@ -44,37 +73,45 @@ pub async fn start_http_server() -> std::io::Result<()> {
     // initialize logger
     let root_logger = init_logger();
 
-    // Generate a random 32 byte key. Note that it is important to use a unique
-    // private key for every project. Anyone with access to the key can generate
-    // authentication cookies for any user!
-    let private_key = rand::thread_rng().gen::<[u8; 32]>();
+    // Derived from `security.session_key` so sessions survive restarts and are shared by
+    // replicas. Anyone with access to the key can generate session cookies for any user!
+    let session_key = redis_session_key();
+
+    // shared by the workers and the logins so the memory buckets count every request
+    let rate_limiter = RATE_LIMITER.clone();
 
     // initialize actix server
     let server = HttpServer::new(move || {
-        let cookie_policy = get_cookie_policy();
+        let identity_policy = get_identity_policy();
 
         App::new()
             .app_data(data.clone())
-            .wrap(RedisSession::new("127.0.0.1:6379", &private_key))
+            .wrap(RedisSession::new("127.0.0.1:6379", &session_key))
+            .wrap(build_logger(&root_logger))
+            .wrap(middleware::Compress::default())
+            .wrap(middleware::Logger::default())
+            // inside the identity service so identity keyed limits see the signed in account
+            .wrap(rate_limiter.clone())
+            // inside the identity and api key middleware so it knows how a request was identified
+            .wrap(CsrfProtection::new(&CONFIG.security))
+            // service accounts are resolved before the limits so they count per account
+            .wrap(ApiKeyAuthentication)
+            .wrap(IdentityService::new(identity_policy))
+            // before the other middleware so they and the router see the tenant and stripped path
+            .wrap(TenantResolution::new(&CONFIG.tenancy))
+            // outermost so the rejections of the middleware above carry the cors headers too
             .wrap(
                 Cors::default()
                     .allowed_origin(&CONFIG.address)
                     .allowed_origin(&CONFIG.ssl_address)
-                    .allowed_origin_fn(|origin, _req_head| {
-                        origin.as_bytes().starts_with(b"http://localhost")
-                    })
-                    .allowed_origin_fn(|origin, _req_head| {
-                        origin.as_bytes().starts_with(b"http://127.0.0.1")
-                    })
+                    .allowed_origin_fn(|origin, _req_head| is_local_origin(origin.as_bytes()))
                     .allowed_headers(&[header::AUTHORIZATION, header::ACCEPT])
                     .allowed_header(header::CONTENT_TYPE)
+                    .allowed_header(CONFIG.security.csrf.header_name.as_str())
+                    .allowed_header(CONFIG.tenancy.header_name.as_str())
                     .expose_headers(&[header::CONTENT_DISPOSITION])
                     .supports_credentials(),
             )
-            .wrap(build_logger(&root_logger))
-            .wrap(middleware::Compress::default())
-            .wrap(middleware::Logger::default())
-            .wrap(IdentityService::new(cookie_policy))
             .configure(add_cache)
             .app_data(add_shared_state)
             .configure(add_pool)
@@ -87,3 +124,18 @@ pub async fn start_http_server() -> std::io::Result<()> {
     // run server
     server.await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_only_allows_local_origins() {
+        assert!(is_local_origin(b"http://localhost"));
+        assert!(is_local_origin(b"http://localhost:3000"));
+        assert!(is_local_origin(b"http://127.0.0.1:8080"));
+        assert!(!is_local_origin(b"http://localhost.attacker.tld"));
+        assert!(!is_local_origin(b"http://127.0.0.1.attacker.tld"));
+        assert!(!is_local_origin(b"https://example.com"));
+    }
+}
@@ -0,0 +1,47 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use slog::{error, info};
+use tonic::transport::Server;
+
+use crate::api::grpc::account::account_module::{accounts_grpc_service, session_grpc_service};
+use crate::api::grpc::admin::admin_module::admin_grpc_service;
+use crate::common::errors::ApiError;
+use crate::common::helpers::AppResult;
+use crate::common::tenant::validate_tenancy;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::config::CONFIG;
+use crate::data::connect::init_store;
+
+/// gRPC entry server
+pub async fn start_grpc_server() -> AppResult<()> {
+    validate_tenancy(&CONFIG.tenancy)?;
+    let addr: SocketAddr = CONFIG
+        .grpc_address
+        .parse()
+        .map_err(|_| ApiError::InternalServerError("invalid grpc_address".to_string()))?;
+    let store = Arc::new(init_store(CONFIG.clone())?);
+
+    info!(LOGGER, "[grpc] listening on {}", addr);
+    Server::builder()
+        .add_optional_service(accounts_grpc_service(store.clone()))
+        .add_optional_service(session_grpc_service(store.clone()))
+        .add_service(admin_grpc_service(store))
+        .serve(addr)
+        .await?;
+
+    Ok(())
+}
+
+/// Run the gRPC server alongside the HTTP server when a `grpc_address` is configured
+pub fn spawn_grpc_server() {
+    if CONFIG.grpc_address.is_empty() {
+        return;
+    }
+
+    actix_rt::spawn(async {
+        if let Err(e) = start_grpc_server().await {
+            error!(LOGGER, "[grpc] server stopped: {}", e);
+        }
+    });
+}
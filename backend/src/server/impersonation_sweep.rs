@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use slog::{error, info};
+
+use crate::api::services::session_service::end_expired_impersonations;
+use crate::common::utils::logger_utils::LOGGER;
+use crate::config::CONFIG;
+use crate::data::connect::init_store;
+use crate::data::stores::base_store_trait::BoxedStoreType;
+
+/// Seconds between two sweeps of the expired impersonations
+const SWEEP_INTERVAL_SECS: u64 = 60;
+
+/// The tenants whose impersonations are swept
+fn tenant_ids() -> Vec<String> {
+    let tenancy = &CONFIG.tenancy;
+    let mut ids = vec![tenancy.default_tenant.clone()];
+    if tenancy.enabled {
+        ids.extend(tenancy.tenants.iter().map(|t| t.id.clone()));
+    }
+    ids
+}
+
+async fn sweep(store: &BoxedStoreType) {
+    for tenant in tenant_ids() {
+        match end_expired_impersonations(&store.for_tenant(&tenant)).await {
+            Ok(0) => {}
+            Ok(n) => info!(LOGGER, "[impersonation] {} expired in {}", n, tenant),
+            Err(e) => error!(LOGGER, "[impersonation] sweep of {} failed: {}", tenant, e),
+        }
+    }
+}
+
+/// Record the end of the impersonations that expire instead of being logged out
+pub fn spawn_impersonation_sweep() {
+    if !CONFIG.security.impersonation.enabled {
+        return;
+    }
+
+    actix_rt::spawn(async {
+        let store = match init_store(CONFIG.clone()) {
+            Ok(store) => store,
+            Err(e) => {
+                error!(LOGGER, "[impersonation] sweep not started: {}", e);
+                return;
+            }
+        };
+
+        let mut interval = actix_rt::time::interval(Duration::from_secs(SWEEP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            sweep(&store).await;
+        }
+    });
+}
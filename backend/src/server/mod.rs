@@ -1,2 +1,5 @@
 pub mod actix_server;
+#[cfg(feature = "server-grpc")]
+pub mod grpc_server;
+pub mod impersonation_sweep;
 pub mod task_server;